@@ -31,6 +31,18 @@ struct Game {
 	cam: PerspectiveCam,
 	shader: Shader<Uniform>,
 	floor: Mesh,
+	shadow: ShadowMap,
+	lights: Vec<Light>,
+}
+
+impl Game {
+
+	fn draw_scene(&self, gfx: &mut Gfx) -> Result<()> {
+		gfx.draw(&shapes::model(&self.model))?;
+		gfx.draw(&shapes::mesh(&self.floor))?;
+		return Ok(());
+	}
+
 }
 
 impl State for Game {
@@ -59,6 +71,17 @@ impl State for Game {
 			},
 			shader: Shader::from_frag(d.gfx, include_str!("res/fog.frag"))?,
 			floor: Mesh::from_meshdata(d.gfx, &floor)?,
+			shadow: ShadowMap::new(d.gfx, 2048)?
+				.bounds(vec3!(0), 10.0),
+			lights: vec![
+				Light::ambient()
+					.intensity(0.2),
+				Light::directional(vec3!(-1, -2, -1))
+					.intensity(0.8),
+				Light::point(vec3!(3, 1, 3))
+					.color(rgba!(1, 0.6, 0.2, 1))
+					.intensity(6.0),
+			],
 		});
 
 	}
@@ -150,6 +173,10 @@ impl State for Game {
 
 	fn draw(&self, d: &mut Ctx) -> Result<()> {
 
+		d.gfx.draw_shadow(&self.shadow, &self.lights, |gfx| {
+			return self.draw_scene(gfx);
+		})?;
+
 		d.gfx.use_cam(&self.cam, |gfx| {
 
 			gfx.draw_with(&self.shader, &Uniform {
@@ -158,6 +185,10 @@ impl State for Game {
 				fog_level: 3.0,
 			}, |gfx| {
 
+				gfx.use_lights_ex(&self.lights, Some(&self.shadow), |gfx| {
+					return self.draw_scene(gfx);
+				})?;
+
 				let bbox = self.model.bbox().transform(mat4!());
 				let mray = Ray3::new(self.cam.pos, self.cam.dir);
 
@@ -167,16 +198,12 @@ impl State for Game {
 					rgba!(1)
 				};
 
-				gfx.draw(&shapes::model(&self.model))?;
-
 				gfx.draw(
 					&shapes::Rect3D::from_bbox(bbox)
 						.line_width(1.0)
 						.color(c)
 				)?;
 
-				gfx.draw(&shapes::mesh(&self.floor))?;
-
 				return Ok(());
 
 			})?;
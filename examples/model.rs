@@ -7,7 +7,7 @@ use input::Key;
 use input::Mouse;
 
 struct Viewer {
-	shader: gfx::Shader<()>,
+	lights: Vec<gfx::Light>,
 	model: gfx::Model,
 	rot: Vec2,
 	pos: Vec2,
@@ -25,7 +25,12 @@ impl State for Viewer {
 		return Ok(Self {
 			model: gfx::Model::from_glb(d.gfx, include_bytes!("res/btfly.glb"))?,
 			pos: vec2!(0),
-			shader: gfx::Shader::from_frag(d.gfx, include_str!("res/normal.frag"))?,
+			lights: vec![
+				gfx::Light::ambient()
+					.intensity(0.3),
+				gfx::Light::directional(vec3!(1, -1, -1))
+					.intensity(0.9),
+			],
 			rot: vec2!(0),
 			resetting: true,
 			draw_wireframe: false,
@@ -174,14 +179,19 @@ impl State for Viewer {
 			.t3(-center)
 		, |gfx| {
 
-			let t = if self.run_anim {
-				let anim_len = self.model.anim_len();
+			let anim_len = self.model
+				.anims()
+				.first()
+				.map(|a| a.len())
+				.unwrap_or(0.0);
+
+			let t = if self.run_anim && anim_len > 0.0 {
 				time - f32::floor(time / anim_len) * anim_len
 			} else {
 				0.0
 			};
 
-			gfx.draw_with(&self.shader, &(), |gfx| {
+			gfx.use_lights(&self.lights, |gfx| {
 				gfx.draw(
 					&shapes::model(&self.model)
 						.time(t)
@@ -12,7 +12,7 @@ use input::Key;
 use input::Mouse;
 
 struct Viewer {
-	shader: gfx::Shader<()>,
+	lights: Vec<gfx::Light>,
 	model: Option<gfx::Model>,
 	rot: Vec2,
 	pos: Vec2,
@@ -21,6 +21,7 @@ struct Viewer {
 	loader: Task<Result<gfx::ModelData>>,
 	draw_wireframe: bool,
 	run_anim: bool,
+	anim: usize,
 	draw_bound: bool,
 	helping: bool,
 }
@@ -40,6 +41,7 @@ impl Viewer {
 	fn update_model(&mut self, model: gfx::Model) {
 
 		self.resetting = true;
+		self.anim = 0;
 		self.model = Some(model);
 
 	}
@@ -64,13 +66,19 @@ impl State for Viewer {
 		return Ok(Self {
 			model: None,
 			pos: vec2!(0),
-			shader: gfx::Shader::from_frag(d.gfx, include_str!("res/normal.frag"))?,
+			lights: vec![
+				gfx::Light::ambient()
+					.intensity(0.3),
+				gfx::Light::directional(vec3!(1, -1, -1))
+					.intensity(0.9),
+			],
 			rot: vec2!(0),
 			resetting: false,
 			loader: load_file("examples/res/truck.obj")?,
 			draw_wireframe: false,
 			draw_bound: false,
 			run_anim: true,
+			anim: 0,
 			helping: false,
 			scale: 0.0,
 		});
@@ -96,6 +104,11 @@ impl State for Viewer {
 					Key::B => self.draw_bound = !self.draw_bound,
 					Key::H => self.helping = !self.helping,
 					Key::T => self.run_anim = !self.run_anim,
+					Key::N => {
+						if let Some(model) = &self.model {
+							self.anim = (self.anim + 1) % model.anims().len().max(1);
+						}
+					},
 					_ => {},
 				}
 
@@ -239,20 +252,30 @@ impl State for Viewer {
 				.t3(-center)
 			, |gfx| {
 
-				let t = if self.run_anim {
-					let anim_len = model.anim_len();
-					time - f32::floor(time / anim_len) * anim_len
-				} else {
-					0.0
+				let clip = model.anims().get(self.anim);
+
+				let t = match clip {
+					Some(clip) if self.run_anim && clip.len() > 0.0 => {
+						time - f32::floor(time / clip.len()) * clip.len()
+					},
+					_ => 0.0,
 				};
 
-				gfx.draw_with(&self.shader, &(), |gfx| {
-					gfx.draw(
-						&shapes::model(&model)
-// 							.wireframe(self.draw_wireframe)
-							.time(t)
-					)?;
+				gfx.use_lights(&self.lights, |gfx| {
+
+					let mut shape = shapes::model(&model)
+// 						.wireframe(self.draw_wireframe)
+						.time(t)
+						;
+
+					if let Some(clip) = clip {
+						shape = shape.clip(clip);
+					}
+
+					gfx.draw(&shape)?;
+
 					return Ok(());
+
 				})?;
 
 				if self.draw_bound {
@@ -323,6 +346,7 @@ impl State for Viewer {
 					"B:        bound",
 					"F:        fullscreen",
 					"T:        anim",
+					"N:        next anim",
 					"<esc>:    quit",
 				];
 
@@ -0,0 +1,121 @@
+// wengwengweng
+
+use dirty::*;
+use audio::*;
+use gfx::shapes;
+use input::Key;
+
+const PROJ: &str = "dirty_sfx_example";
+const ENTRY: &str = "sfx";
+
+struct Game {
+	params: sfx::Params,
+	seed: u64,
+}
+
+impl Game {
+
+	fn play(&self, d: &mut Ctx) -> Result<()> {
+		#[cfg(not(web))]
+		self.params.to_sound(d.audio).play()?;
+		return Ok(());
+	}
+
+}
+
+impl State for Game {
+
+	fn init(_: &mut Ctx) -> Result<Self> {
+
+		// pick up where we left off
+		let params = data::load::<sfx::Params>(PROJ, ENTRY)
+			.unwrap_or_else(|_| sfx::Params::preset(sfx::Preset::Pickup, 0));
+
+		return Ok(Self {
+			params: params,
+			seed: 0,
+		});
+
+	}
+
+	fn event(&mut self, d: &mut Ctx, e: &input::Event) -> Result<()> {
+
+		use input::Event::*;
+
+		match e {
+			KeyPress(k) => {
+
+				let preset = match *k {
+					Key::Key1 => Some(sfx::Preset::Pickup),
+					Key::Key2 => Some(sfx::Preset::Laser),
+					Key::Key3 => Some(sfx::Preset::Explosion),
+					Key::Key4 => Some(sfx::Preset::Jump),
+					Key::Key5 => Some(sfx::Preset::Hit),
+					Key::Key6 => Some(sfx::Preset::Blip),
+					_ => None,
+				};
+
+				if let Some(p) = preset {
+					self.seed += 1;
+					self.params = sfx::Params::preset(p, self.seed);
+					self.play(d)?;
+				}
+
+				match *k {
+					Key::Esc => d.window.quit(),
+					Key::Space => self.play(d)?,
+					Key::M => {
+						self.seed += 1;
+						self.params = self.params.mutate(self.seed);
+						self.play(d)?;
+					},
+					Key::S => {
+						data::save(PROJ, ENTRY, &self.params)?;
+						println!("{}", data::to_json(&self.params)?);
+					},
+					_ => {},
+				}
+
+			},
+			_ => {},
+		}
+
+		return Ok(());
+
+	}
+
+	fn draw(&self, d: &mut Ctx) -> Result<()> {
+
+		let top_left = d.gfx.coord(gfx::Origin::TopLeft);
+
+		let lines = [
+			"1 - 6: pickup, laser, explosion, jump, hit, blip",
+			"space: play again",
+			"m:     mutate",
+			"s:     save",
+		];
+
+		for (i, l) in lines.iter().enumerate() {
+			d.gfx.draw_t(
+				mat4!()
+					.t2(top_left + vec2!(24, -24.0 - i as f32 * 24.0))
+					,
+				&shapes::text(l)
+					.align(gfx::Origin::TopLeft)
+					.size(12.0)
+					,
+			)?;
+		}
+
+		return Ok(());
+
+	}
+
+}
+
+fn main() {
+	if let Err(e) = launcher()
+		.run::<Game>() {
+		elog!("{}", e);
+	}
+}
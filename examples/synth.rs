@@ -3,6 +3,7 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use dirty::*;
 use gfx::shapes;
@@ -12,6 +13,10 @@ use synth::BasicSynth;
 use synth::Waveform;
 use synth::Voice;
 use synth::Envelope;
+use synth::Patch;
+use synth::Oscillator;
+use synth::Lfo;
+use synth::VoiceFilter;
 use input::Key;
 
 fn key_to_note(k: Key, o: i32) -> Option<Note> {
@@ -41,23 +46,153 @@ fn key_to_note(k: Key, o: i32) -> Option<Note> {
 
 }
 
+fn supersaw() -> Patch {
+	return Patch {
+		oscs: vec![
+			Oscillator::new(Waveform::Saw).unison(7, 40.0),
+			Oscillator::new(Waveform::Saw).transpose(-12).volume(0.5),
+		],
+		envelope: Envelope {
+			attack: 0.02,
+			decay: 0.2,
+			sustain: 0.8,
+			release: 0.4,
+		},
+		filter: Some(VoiceFilter::new(FilterType::Lowpass, 2000.0, 0.7)),
+		volume: 0.3,
+		..Patch::default()
+	};
+}
+
+fn pwm_pad() -> Patch {
+	return Patch {
+		oscs: vec![
+			Oscillator::new(Waveform::Square).unison(2, 10.0),
+		],
+		envelope: Envelope {
+			attack: 0.6,
+			decay: 0.5,
+			sustain: 0.8,
+			release: 1.5,
+		},
+		lfo: Lfo {
+			freq: 0.5,
+			pwm: 0.35,
+			cutoff: 0.5,
+			..Lfo::default()
+		},
+		filter: Some(VoiceFilter::new(FilterType::Lowpass, 1200.0, 1.0)),
+		volume: 0.4,
+	};
+}
+
+fn acid() -> Patch {
+	return Patch {
+		oscs: vec![
+			Oscillator::new(Waveform::Saw),
+		],
+		envelope: Envelope {
+			attack: 0.005,
+			decay: 0.3,
+			sustain: 0.6,
+			release: 0.1,
+		},
+		filter: Some(VoiceFilter::new(FilterType::Lowpass, 200.0, 8.0).envelope(Envelope {
+			attack: 0.0,
+			decay: 0.25,
+			sustain: 0.0,
+			release: 0.1,
+		}, 4.0)),
+		volume: 0.5,
+		..Patch::default()
+	};
+}
+
+fn vibrato_lead() -> Patch {
+	return Patch {
+		oscs: vec![
+			Oscillator::new(Waveform::Triangle),
+			Oscillator::new(Waveform::Square).detune(7.0).pulse_width(0.25).volume(0.3),
+		],
+		lfo: Lfo {
+			freq: 6.0,
+			pitch: 0.2,
+			..Lfo::default()
+		},
+		volume: 0.6,
+		..Patch::default()
+	};
+}
+
+#[derive(Clone)]
+enum JamEvent {
+	Press(Note, Patch),
+	Release(Note),
+}
+
+// replay a recorded jam on a new synth offline and write it out
+fn bounce(jam: &[(Duration, JamEvent)], path: &str) -> Result<()> {
+
+	let mut r = Renderer::new();
+	let synth = Arc::new(Mutex::new(BasicSynth::new()));
+
+	r.play(synth.clone());
+
+	for (t, e) in jam {
+
+		if *t > r.position() {
+			r.render(*t - r.position());
+		}
+
+		if let Ok(mut synth) = synth.lock() {
+			match e {
+				JamEvent::Press(n, p) => synth.play(Voice::builder(*n).patch(p.clone()).build()),
+				JamEvent::Release(n) => synth.release(*n),
+			}
+		}
+
+	}
+
+	if let Ok(mut synth) = synth.lock() {
+		synth.release_all();
+	}
+
+	// leave room for release tails
+	r.render(Duration::from_secs(2));
+	r.finish().save_wav(path, WavFormat::Int16)?;
+
+	return Ok(());
+
+}
+
 struct Game {
 	octave: i32,
-	waveform: Waveform,
-	envelope: Envelope,
+	patch: Patch,
 	pressed: HashSet<Note>,
 	synth: Arc<Mutex<BasicSynth>>,
+	analyser: Analyser,
+	// (start time, events)
+	recording: Option<(Duration, Vec<(Duration, JamEvent)>)>,
+	// instrument take from the mic, drained every frame so it's not limited by the ring buffer
+	#[cfg(not(web))]
+	take: Option<(Recording, Vec<Frame>)>,
 }
 
 impl Game {
 
-	fn press_note(&mut self, note: Note) {
+	fn record(&mut self, time: Duration, e: JamEvent) {
+		if let Some((start, events)) = &mut self.recording {
+			events.push((time - *start, e));
+		}
+	}
+
+	fn press_note(&mut self, note: Note, time: Duration) {
 
 		self.pressed.insert(note);
+		self.record(time, JamEvent::Press(note, self.patch.clone()));
 
 		let v = Voice::builder(note)
-			.waveform(self.waveform)
-			.envelope(self.envelope)
+			.patch(self.patch.clone())
 			.build();
 
 		if let Ok(mut synth) = self.synth.lock() {
@@ -66,10 +201,11 @@ impl Game {
 
 	}
 
-	fn release_note(&mut self, note: Note) {
+	fn release_note(&mut self, note: Note, time: Duration) {
 
 		if self.pressed.contains(&note) {
 			self.pressed.remove(&note);
+			self.record(time, JamEvent::Release(note));
 			if let Ok(mut synth) = self.synth.lock() {
 				synth.release(note);
 			}
@@ -77,6 +213,47 @@ impl Game {
 
 	}
 
+	fn toggle_recording(&mut self, time: Duration) -> Result<()> {
+
+		match self.recording.take() {
+			Some((_, events)) => {
+				bounce(&events, "jam.wav")?;
+				println!("saved jam.wav");
+			},
+			None => {
+				self.recording = Some((time, vec![]));
+				println!("recording...");
+			},
+		}
+
+		return Ok(());
+
+	}
+
+	#[cfg(not(web))]
+	fn toggle_take(&mut self, d: &mut Ctx) -> Result<()> {
+
+		match self.take.take() {
+			Some((mut rec, mut frames)) => {
+				frames.extend(rec.read());
+				AudioBuffer::from_frames(frames, rec.sample_rate()).save_wav("take.wav", WavFormat::Int16)?;
+				println!("saved take.wav");
+			},
+			None => {
+				match d.audio.record() {
+					Ok(rec) => {
+						self.take = Some((rec, vec![]));
+						println!("recording take...");
+					},
+					Err(err) => elog!("{}", err),
+				}
+			},
+		}
+
+		return Ok(());
+
+	}
+
 }
 
 impl State for Game {
@@ -88,17 +265,22 @@ impl State for Game {
 		#[cfg(not(web))]
 		d.audio.play(Arc::clone(&synth))?;
 
+		let analyser = Analyser::new(2048);
+
+		d.audio.add_master_effect(Arc::new(Mutex::new(analyser.tap())));
+
 		return Ok(Self {
 			octave: 4,
-			waveform: Waveform::Triangle,
+			patch: Patch {
+				oscs: vec![Oscillator::new(Waveform::Triangle)],
+				..Patch::default()
+			},
 			pressed: hset![],
 			synth: synth,
-			envelope: Envelope {
-				attack: 0.01,
-				decay: 0.01,
-				sustain: 1.0,
-				release: 1.0,
-			},
+			analyser: analyser,
+			recording: None,
+			#[cfg(not(web))]
+			take: None,
 		});
 	}
 
@@ -111,6 +293,7 @@ impl State for Game {
 			KeyPress(k) => {
 
 				let mods = d.window.key_mods();
+				let time = d.app.time();
 
 				match *k {
 					Key::Esc => d.window.quit(),
@@ -123,16 +306,23 @@ impl State for Game {
 					Key::Down => {},
 					Key::Left => self.octave -= 1,
 					Key::Right => self.octave += 1,
-					Key::Key1 => self.waveform = Waveform::Sine,
-					Key::Key2 => self.waveform = Waveform::Triangle,
-					Key::Key3 => self.waveform = Waveform::Square,
-					Key::Key4 => self.waveform = Waveform::Saw,
-					Key::Key5 => self.waveform = Waveform::Noise,
+					Key::Key1 => self.patch.oscs = vec![Oscillator::new(Waveform::Sine)],
+					Key::Key2 => self.patch.oscs = vec![Oscillator::new(Waveform::Triangle)],
+					Key::Key3 => self.patch.oscs = vec![Oscillator::new(Waveform::Square)],
+					Key::Key4 => self.patch.oscs = vec![Oscillator::new(Waveform::Saw)],
+					Key::Key5 => self.patch.oscs = vec![Oscillator::new(Waveform::Noise)],
+					Key::Key6 => self.patch = supersaw(),
+					Key::Key7 => self.patch = pwm_pad(),
+					Key::Key8 => self.patch = acid(),
+					Key::Key9 => self.patch = vibrato_lead(),
+					Key::R => self.toggle_recording(time)?,
+					#[cfg(not(web))]
+					Key::M => self.toggle_take(d)?,
 					_ => {},
 				}
 
 				if let Some(note) = key_to_note(*k, self.octave) {
-					self.press_note(note);
+					self.press_note(note, time);
 				}
 
 			},
@@ -140,7 +330,7 @@ impl State for Game {
 			KeyRelease(k) => {
 
 				if let Some(note) = key_to_note(*k, self.octave) {
-					self.release_note(note);
+					self.release_note(note, d.app.time());
 				}
 
 			},
@@ -148,8 +338,8 @@ impl State for Game {
 			MIDI(msg) => {
 
 				match msg {
-					midi::Msg::NoteOn(n, _) => self.press_note(Note::new(*n)),
-					midi::Msg::NoteOff(n, _) => self.release_note(Note::new(*n)),
+					midi::Msg::NoteOn(n, _) => self.press_note(Note::new(*n), d.app.time()),
+					midi::Msg::NoteOff(n, _) => self.release_note(Note::new(*n), d.app.time()),
 					_ => {},
 				}
 
@@ -163,6 +353,17 @@ impl State for Game {
 
 	}
 
+	fn update(&mut self, _: &mut Ctx) -> Result<()> {
+
+		#[cfg(not(web))]
+		if let Some((rec, frames)) = &mut self.take {
+			frames.extend(rec.read());
+		}
+
+		return Ok(());
+
+	}
+
 	fn draw(&self, d: &mut Ctx) -> Result<()> {
 
 		if let Ok(synth) = self.synth.lock() {
@@ -192,6 +393,31 @@ impl State for Game {
 
 		}
 
+		// spectrum on a log frequency scale
+		let spectrum = self.analyser.spectrum();
+		let bars = 64;
+		let w = d.gfx.width() as f32;
+		let bottom = -d.gfx.height() as f32 / 2.0;
+		let bar_w = w / bars as f32;
+
+		let max = spectrum.len() - 1;
+		// 20hz - 20khz
+		let bin = |i: usize| ((20.0 * 1000f32.powf(i as f32 / bars as f32) / self.analyser.bin_freq(1)) as usize).min(max);
+
+		for i in 0..bars {
+
+			let level = spectrum[bin(i)..=bin(i + 1)]
+				.iter()
+				.cloned()
+				.fold(0.0, f32::max);
+			// -60db - 0db
+			let h = ((20.0 * level.max(0.000001).log10() + 60.0) / 60.0).max(0.0) * 160.0;
+			let x = -w / 2.0 + i as f32 * bar_w;
+
+			d.gfx.draw(&shapes::rect(vec2!(x, bottom), vec2!(x + bar_w - 1.0, bottom + h)))?;
+
+		}
+
 		return Ok(());
 
 	}
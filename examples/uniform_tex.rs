@@ -9,6 +9,11 @@ struct TexUniform {
 }
 
 impl UniformLayout for TexUniform {
+	fn values(&self) -> UniformValues {
+		return vec![
+			("u_tex2", &1),
+		];
+	}
 	fn textures(&self) -> Vec<&Texture> {
 		return vec![&self.tex];
 	}
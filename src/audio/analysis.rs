@@ -0,0 +1,284 @@
+// wengwengweng
+
+use std::f32::consts::PI;
+use std::collections::VecDeque;
+use super::*;
+
+const MIN_SIZE: usize = 32;
+const MAX_SIZE: usize = 16384;
+// frames a tap collects before handing them over
+const TAP_CHUNK: usize = 256;
+
+// the latest frames that went through a tap
+struct History {
+	frames: Vec<Frame>,
+	pos: usize,
+}
+
+impl History {
+
+	fn new(size: usize) -> Self {
+		return Self {
+			frames: vec![Frame::default(); size],
+			pos: 0,
+		};
+	}
+
+	fn push(&mut self, f: Frame) {
+		self.frames[self.pos] = f;
+		self.pos = (self.pos + 1) % self.frames.len();
+	}
+
+	// oldest first
+	fn ordered(&self) -> Vec<Frame> {
+		return self.frames[self.pos..]
+			.iter()
+			.chain(self.frames[..self.pos].iter())
+			.cloned()
+			.collect();
+	}
+
+}
+
+/// Level & Spectrum Meter
+///
+/// put its [`tap`](#method.tap) in the effect chain of a channel, [`Bus`](struct.Bus.html) or master, then read from the analyser on the game thread, the tap never waits for the reader
+/// ```ignore
+/// let analyser = Analyser::new(1024);
+///
+/// track.add_effect(Arc::new(Mutex::new(analyser.tap())));
+///
+/// // every frame
+/// let kick = analyser.spectrum()[1..4].iter().sum::<f32>();
+/// ui.window("audio", pos, 240.0, 120.0, |p| {
+///     return p.meter("music", analyser.rms(), analyser.peak());
+/// })?;
+/// ```
+#[derive(Clone)]
+pub struct Analyser {
+	history: Arc<Mutex<History>>,
+}
+
+impl Analyser {
+
+	/// size is the number of frames analysed, rounded up to a power of 2, also the fft size
+	pub fn new(size: usize) -> Self {
+		return Self {
+			history: Arc::new(Mutex::new(History::new(fft_size(size)))),
+		};
+	}
+
+	/// the effect that feeds this analyser, it passes audio through untouched
+	pub fn tap(&self) -> AnalyserTap {
+		return AnalyserTap {
+			history: self.history.clone(),
+			pending: VecDeque::with_capacity(self.size().max(TAP_CHUNK)),
+		};
+	}
+
+	pub fn size(&self) -> usize {
+		return self.history
+			.lock()
+			.map(|h| h.frames.len())
+			.unwrap_or(0);
+	}
+
+	/// change the number of frames analysed, clears history
+	pub fn set_size(&self, size: usize) {
+		if let Ok(mut h) = self.history.lock() {
+			*h = History::new(fft_size(size));
+		}
+	}
+
+	/// the latest frames, oldest first, e.g. for drawing a waveform
+	pub fn frames(&self) -> Vec<Frame> {
+		return self.history
+			.lock()
+			.map(|h| h.ordered())
+			.unwrap_or_default();
+	}
+
+	/// root mean square level of both channels
+	pub fn rms(&self) -> f32 {
+
+		let frames = self.frames();
+
+		if frames.is_empty() {
+			return 0.0;
+		}
+
+		let sum = frames
+			.iter()
+			.map(|f| f.left * f.left + f.right * f.right)
+			.sum::<f32>();
+
+		return (sum / (frames.len() * 2) as f32).sqrt();
+
+	}
+
+	/// highest absolute sample value of both channels
+	pub fn peak(&self) -> f32 {
+		return self.frames()
+			.iter()
+			.map(|f| f.left.abs().max(f.right.abs()))
+			.fold(0.0, f32::max);
+	}
+
+	/// magnitude of each frequency bin with a hann window, `size / 2` bins, a full scale sine reads 1.0 at its bin
+	pub fn spectrum(&self) -> Vec<f32> {
+
+		let frames = self.frames();
+		let n = frames.len();
+
+		if n == 0 {
+			return vec![];
+		}
+
+		let window = (0..n)
+			.map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos())
+			.collect::<Vec<f32>>();
+
+		let window_sum = window.iter().sum::<f32>();
+
+		let mut re = frames
+			.iter()
+			.zip(&window)
+			.map(|(f, w)| (f.left + f.right) * 0.5 * w)
+			.collect::<Vec<f32>>();
+
+		let mut im = vec![0.0; n];
+
+		fft(&mut re, &mut im);
+
+		return (0..n / 2)
+			.map(|i| (re[i] * re[i] + im[i] * im[i]).sqrt() * 2.0 / window_sum)
+			.collect();
+
+	}
+
+	/// center frequency of a spectrum bin
+	pub fn bin_freq(&self, i: usize) -> f32 {
+		return i as f32 * SPEC.sample_rate as f32 / self.size().max(1) as f32;
+	}
+
+}
+
+/// The Audio Thread Side of an [`Analyser`](struct.Analyser.html)
+pub struct AnalyserTap {
+	history: Arc<Mutex<History>>,
+	// never grows past its initial capacity, the oldest frames go first if the reader stalls
+	pending: VecDeque<Frame>,
+}
+
+impl Effect for AnalyserTap {
+
+	fn process(&mut self, f: Frame) -> Frame {
+
+		if self.pending.len() == self.pending.capacity() {
+			self.pending.pop_front();
+		}
+
+		self.pending.push_back(f);
+
+		if self.pending.len() >= TAP_CHUNK {
+			// skip this round if the reader is holding it
+			if let Ok(mut h) = self.history.try_lock() {
+				for f in self.pending.drain(..) {
+					h.push(f);
+				}
+			}
+		}
+
+		return f;
+
+	}
+
+}
+
+fn fft_size(size: usize) -> usize {
+	return size.max(MIN_SIZE).min(MAX_SIZE).next_power_of_two();
+}
+
+// in place radix-2 fft, length has to be a power of 2
+fn fft(re: &mut [f32], im: &mut [f32]) {
+
+	let n = re.len();
+	let mut j = 0;
+
+	// bit reversal permutation
+	for i in 1..n {
+		let mut bit = n >> 1;
+		while j & bit != 0 {
+			j ^= bit;
+			bit >>= 1;
+		}
+		j |= bit;
+		if i < j {
+			re.swap(i, j);
+			im.swap(i, j);
+		}
+	}
+
+	let mut len = 2;
+
+	while len <= n {
+
+		let a = -2.0 * PI / len as f32;
+		let (wr, wi) = (a.cos(), a.sin());
+
+		for start in (0..n).step_by(len) {
+
+			let (mut cr, mut ci) = (1.0, 0.0);
+
+			for k in 0..len / 2 {
+
+				let i = start + k;
+				let j = i + len / 2;
+				let tr = re[j] * cr - im[j] * ci;
+				let ti = re[j] * ci + im[j] * cr;
+
+				re[j] = re[i] - tr;
+				im[j] = im[i] - ti;
+				re[i] += tr;
+				im[i] += ti;
+
+				let next = cr * wr - ci * wi;
+				ci = cr * wi + ci * wr;
+				cr = next;
+
+			}
+
+		}
+
+		len <<= 1;
+
+	}
+
+}
+
+#[test]
+fn tap_stalled_reader() {
+
+	let analyser = Analyser::new(1024);
+	let mut tap = analyser.tap();
+	let cap = tap.pending.capacity();
+
+	{
+		// the reader holds the history while lots of audio goes through
+		let _h = analyser.history.lock().unwrap();
+
+		for i in 0..cap * 4 {
+			tap.process(Frame::new(i as f32, 0.0));
+		}
+	}
+
+	assert_eq!(tap.pending.capacity(), cap);
+	assert_eq!(tap.pending.len(), cap);
+	assert_eq!(tap.pending.back().map(|f| f.left), Some((cap * 4 - 1) as f32));
+
+	// once the reader lets go the latest frames make it through
+	tap.process(Frame::new(-1.0, 0.0));
+	assert!(tap.pending.is_empty());
+	assert_eq!(analyser.frames().last().map(|f| f.left), Some(-1.0));
+
+}
@@ -0,0 +1,219 @@
+// wengwengweng
+
+use std::time::Instant;
+use super::*;
+
+// fade on stolen voices so they don't click
+const STEAL_FADE: Duration = Duration::from_millis(5);
+
+/// What a [`SoundBank`](struct.SoundBank.html) Does When All Voices Are Playing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoiceSteal {
+	/// stop the voice that started first
+	Oldest,
+	/// stop the voice with the lowest volume
+	Quietest,
+	/// don't play the new one
+	Skip,
+}
+
+// keeps track of what a bank is playing
+pub(super) struct VoiceLimit {
+	max: Option<usize>,
+	steal: VoiceSteal,
+	cooldown: Duration,
+	last: Option<Instant>,
+	// oldest first
+	voices: Vec<Arc<Mutex<Control>>>,
+}
+
+impl VoiceLimit {
+
+	// check cooldown and make room for a new voice, false if it shouldn't play
+	pub fn admit(&mut self) -> bool {
+
+		let now = Instant::now();
+
+		if let Some(last) = self.last {
+			if now.duration_since(last) < self.cooldown {
+				return false;
+			}
+		}
+
+		// the mixer drops its handle when a voice is done
+		self.voices.retain(|v| Arc::strong_count(v) > 1);
+
+		if let Some(max) = self.max {
+
+			while self.voices.len() >= max.max(1) {
+
+				let i = match self.steal {
+					VoiceSteal::Oldest => 0,
+					VoiceSteal::Quietest => {
+						self.voices
+							.iter()
+							.map(|v| v.lock().map(|c| c.volume).unwrap_or(0.0))
+							.enumerate()
+							.fold((0, std::f32::MAX), |(mi, mv), (i, v)| {
+								return if v < mv { (i, v) } else { (mi, mv) };
+							}).0
+					},
+					VoiceSteal::Skip => return false,
+				};
+
+				if let Ok(mut ctrl) = self.voices.remove(i).lock() {
+					ctrl.fade_stop(STEAL_FADE);
+				}
+
+			}
+
+		}
+
+		self.last = Some(now);
+
+		return true;
+
+	}
+
+	pub fn track(&mut self, ctrl: Arc<Mutex<Control>>) {
+		self.voices.push(ctrl);
+	}
+
+}
+
+/// A Group of Variations of a Sound Effect
+///
+/// each play picks a random variation with random pitch and volume, and can limit how many are playing at once and how often it can be triggered
+/// ```ignore
+/// let mut shots = SoundBank::from_bytes(&d.audio, &[
+///     include_bytes!("res/shot1.ogg"),
+///     include_bytes!("res/shot2.ogg"),
+///     include_bytes!("res/shot3.ogg"),
+/// ])?
+///     .pitch(0.9, 1.1)
+///     .volume(0.8, 1.0)
+///     .max_voices(4, VoiceSteal::Oldest)
+///     .cooldown(Duration::from_millis(30));
+///
+/// // fifty bullets in one frame still plays one
+/// for _ in 0..50 {
+///     shots.play()?;
+/// }
+///
+/// shots.builder().bus("sfx").emitter(gun.clone()).play()?;
+/// ```
+pub struct SoundBank {
+	sounds: Vec<Sound>,
+	pitch: (f32, f32),
+	volume: (f32, f32),
+	limit: VoiceLimit,
+	last_pick: Option<usize>,
+}
+
+impl SoundBank {
+
+	/// create from loaded sounds
+	pub fn new(sounds: Vec<Sound>) -> Result<Self> {
+
+		if sounds.is_empty() {
+			return Err(format!("sound bank needs at least 1 sound"));
+		}
+
+		return Ok(Self {
+			sounds: sounds,
+			pitch: (1.0, 1.0),
+			volume: (1.0, 1.0),
+			limit: VoiceLimit {
+				max: None,
+				steal: VoiceSteal::Oldest,
+				cooldown: Duration::from_secs(0),
+				last: None,
+				voices: vec![],
+			},
+			last_pick: None,
+		});
+
+	}
+
+	/// create from bytes of audio files
+	pub fn from_bytes(ctx: &Audio, data: &[&[u8]]) -> Result<Self> {
+		return Self::new(data
+			.iter()
+			.map(|d| Sound::from_bytes(ctx, d))
+			.collect::<Result<Vec<Sound>>>()?);
+	}
+
+	/// random playback rate range, also changes pitch
+	pub fn pitch(mut self, min: f32, max: f32) -> Self {
+		self.pitch = (min, max);
+		return self;
+	}
+
+	/// random volume range
+	pub fn volume(mut self, min: f32, max: f32) -> Self {
+		self.volume = (min, max);
+		return self;
+	}
+
+	/// max number of voices playing at once, and what to do when a new one comes in
+	pub fn max_voices(mut self, max: usize, steal: VoiceSteal) -> Self {
+		self.limit.max = Some(max);
+		self.limit.steal = steal;
+		return self;
+	}
+
+	/// minimum time between plays, plays within it are ignored
+	pub fn cooldown(mut self, d: Duration) -> Self {
+		self.limit.cooldown = d;
+		return self;
+	}
+
+	/// number of voices currently playing
+	pub fn voice_count(&self) -> usize {
+		return self.limit.voices
+			.iter()
+			.filter(|v| Arc::strong_count(v) > 1)
+			.count();
+	}
+
+	/// stop all voices
+	pub fn stop(&mut self) {
+		for v in self.limit.voices.drain(..) {
+			if let Ok(mut ctrl) = v.lock() {
+				ctrl.fade_stop(STEAL_FADE);
+			}
+		}
+	}
+
+	/// play a random variation
+	pub fn play(&mut self) -> Result<()> {
+		return self.builder().play();
+	}
+
+	/// a [`SoundBuilder`](struct.SoundBuilder.html) with a random variation, pitch and volume picked, voice limit and cooldown are checked when it plays
+	pub fn builder(&mut self) -> SoundBuilder {
+
+		let len = self.sounds.len();
+
+		// rand() can land on the upper bound, keep it in range
+		let mut i = rand(0, len).min(len - 1);
+
+		// don't repeat the same one twice in a row
+		if len > 1 && Some(i) == self.last_pick {
+			i = (i + rand(1, len).min(len - 1)) % len;
+		}
+
+		self.last_pick = Some(i);
+
+		let mut b = self.sounds[i]
+			.builder()
+			.volume(rand_t(self.volume))
+			.speed(rand_t(self.pitch));
+
+		b.limit = Some(&mut self.limit);
+
+		return b;
+
+	}
+
+}
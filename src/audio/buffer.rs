@@ -51,6 +51,73 @@ impl AudioBuffer {
 		return self.sample_rate;
 	}
 
+	/// convert to another sample rate
+	pub fn resample(&self, sample_rate: u32, quality: ResampleQuality) -> Self {
+		return Self {
+			frames: resample(&self.frames, self.sample_rate, sample_rate, quality),
+			sample_rate: sample_rate,
+		};
+	}
+
+	/// encode to a wav file
+	pub fn to_wav(&self, format: WavFormat) -> Result<Vec<u8>> {
+
+		let (bits, sample_format) = match format {
+			WavFormat::Int16 => (16, hound::SampleFormat::Int),
+			WavFormat::Int24 => (24, hound::SampleFormat::Int),
+			WavFormat::Float32 => (32, hound::SampleFormat::Float),
+		};
+
+		let spec = hound::WavSpec {
+			channels: 2,
+			sample_rate: self.sample_rate,
+			bits_per_sample: bits,
+			sample_format: sample_format,
+		};
+
+		let mut data = vec![];
+
+		{
+
+			let mut writer = hound::WavWriter::new(Cursor::new(&mut data), spec)
+				.map_err(|_| format!("failed to create wav writer"))?;
+
+			for f in &self.frames {
+				for s in &[f.left, f.right] {
+
+					let s = s.max(-1.0).min(1.0);
+
+					let res = match format {
+						WavFormat::Int16 => writer.write_sample(utils::f32_to_i16(s)),
+						WavFormat::Int24 => writer.write_sample(utils::f32_to_i24(s)),
+						WavFormat::Float32 => writer.write_sample(s),
+					};
+
+					res.map_err(|_| format!("failed to write wav sample"))?;
+
+				}
+			}
+
+			writer
+				.finalize()
+				.map_err(|_| format!("failed to write wav"))?;
+
+		}
+
+		return Ok(data);
+
+	}
+
+	/// write to a wav file
+	pub fn save_wav(&self, path: impl AsRef<std::path::Path>, format: WavFormat) -> Result<()> {
+
+		let path = path.as_ref();
+
+		return std::fs::write(path, self.to_wav(format)?)
+			.map_err(|_| format!("failed to write file {}", path.display()));
+
+	}
+
 	pub fn process(&mut self, e: &mut impl Effect) {
 		for f in &mut self.frames {
 			*f = e.process(*f);
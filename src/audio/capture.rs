@@ -0,0 +1,376 @@
+// wengwengweng
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Instant;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Weak;
+
+use super::*;
+
+// frames buffered before a live source starts playing, and after it runs dry
+const MONITOR_LATENCY: u64 = 2048;
+// how often the fake device pushes
+const FAKE_TICK: Duration = Duration::from_millis(10);
+
+/// Where to Record From
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputDevice {
+	/// the system default input, usually the microphone
+	Default,
+	/// play an audio file in real time as if it's coming from a device, for testing
+	File(PathBuf),
+}
+
+// fixed size history of captured frames, indexed by total frames written
+pub(super) struct Ring {
+	frames: Vec<Frame>,
+	written: u64,
+}
+
+impl Ring {
+
+	pub fn new(capacity: usize) -> Self {
+		return Self {
+			frames: vec![Frame::default(); capacity.max(1)],
+			written: 0,
+		};
+	}
+
+	pub fn push(&mut self, f: Frame) {
+		let len = self.frames.len() as u64;
+		self.frames[(self.written % len) as usize] = f;
+		self.written += 1;
+	}
+
+	fn get(&self, i: u64) -> Frame {
+		return self.frames[(i % self.frames.len() as u64) as usize];
+	}
+
+	// index of the oldest frame still kept
+	fn oldest(&self) -> u64 {
+		return self.written.saturating_sub(self.frames.len() as u64);
+	}
+
+}
+
+// runs when a recording is dropped
+pub(super) struct Closer(Box<dyn Fn() + Send + Sync>);
+
+impl Closer {
+	pub fn new(f: impl Fn() + Send + Sync + 'static) -> Self {
+		return Self(Box::new(f));
+	}
+}
+
+impl Drop for Closer {
+	fn drop(&mut self) {
+		(self.0)();
+	}
+}
+
+/// Captured Audio From an Input Device
+///
+/// keeps the latest frames in a ring buffer, the device is closed and every [`source`](#method.source) made from it ends when this is dropped
+pub struct Recording {
+	ring: Arc<Mutex<Ring>>,
+	cursor: u64,
+	sample_rate: u32,
+	closer: Arc<Closer>,
+}
+
+impl Recording {
+
+	pub(super) fn new(ring: Arc<Mutex<Ring>>, sample_rate: u32, closer: Closer) -> Self {
+		return Self {
+			ring: ring,
+			cursor: 0,
+			sample_rate: sample_rate,
+			closer: Arc::new(closer),
+		};
+	}
+
+	pub(super) fn from_file(path: impl AsRef<Path>, buffer: Duration) -> Result<Self> {
+
+		let (rec, mut input) = Self::fake(path, buffer)?;
+		let stop = Arc::new(AtomicBool::new(false));
+		let t_stop = stop.clone();
+
+		thread::Builder::new()
+			.name(format!("dirty_audio_input"))
+			.spawn(move || {
+
+			let start = Instant::now();
+
+			while !t_stop.load(Ordering::Relaxed) && input.tick(start.elapsed()) {
+				thread::sleep(FAKE_TICK);
+			}
+
+		}).map_err(|_| format!("failed to spawn audio input thread"))?;
+
+		return Ok(Self {
+			closer: Arc::new(Closer::new(move || {
+				stop.store(true, Ordering::Relaxed);
+			})),
+			..rec
+		});
+
+	}
+
+	// a recording fed by a file, the input has to be ticked by the caller
+	fn fake(path: impl AsRef<Path>, buffer: Duration) -> Result<(Self, FakeInput)> {
+
+		let path = path.as_ref();
+		let data = std::fs::read(path)
+			.map_err(|_| format!("failed to read {}", path.display()))?;
+		let decoder = Decoder::new(Cursor::new(data))?;
+		let sample_rate = decoder.sample_rate();
+		let ring = Arc::new(Mutex::new(Ring::new((buffer.as_secs_f64() * sample_rate as f64) as usize)));
+
+		let input = FakeInput {
+			decoder: decoder,
+			ring: ring.clone(),
+			sample_rate: sample_rate,
+			pushed: 0,
+		};
+
+		return Ok((Self::new(ring, sample_rate, Closer::new(|| {})), input));
+
+	}
+
+	/// sample rate of the device
+	pub fn sample_rate(&self) -> u32 {
+		return self.sample_rate;
+	}
+
+	/// total frames captured so far
+	pub fn captured(&self) -> u64 {
+		return self.ring
+			.lock()
+			.map(|r| r.written)
+			.unwrap_or(0);
+	}
+
+	/// frames captured since the last read, frames that already fell out of the buffer are lost
+	pub fn read(&mut self) -> Vec<Frame> {
+
+		let ring = match self.ring.lock() {
+			Ok(ring) => ring,
+			Err(_) => return vec![],
+		};
+
+		let from = self.cursor.max(ring.oldest());
+		let frames = (from..ring.written)
+			.map(|i| ring.get(i))
+			.collect();
+
+		self.cursor = ring.written;
+
+		return frames;
+
+	}
+
+	/// the latest n frames, oldest first, doesn't affect [`read`](#method.read)
+	pub fn latest(&self, n: usize) -> Vec<Frame> {
+
+		let ring = match self.ring.lock() {
+			Ok(ring) => ring,
+			Err(_) => return vec![],
+		};
+
+		let from = ring.written.saturating_sub(n as u64).max(ring.oldest());
+
+		return (from..ring.written)
+			.map(|i| ring.get(i))
+			.collect();
+
+	}
+
+	/// a [`Source`](trait.Source.html) that plays what's coming in live, converted to the mixer sample rate
+	pub fn source(&self) -> RecordingSource {
+
+		let written = self.ring
+			.lock()
+			.map(|r| r.written)
+			.unwrap_or(0);
+
+		let reader = RingReader {
+			ring: self.ring.clone(),
+			cursor: written,
+			sample_rate: self.sample_rate,
+			buffering: true,
+		};
+
+		return RecordingSource {
+			inner: Resampler::new(reader, SPEC.sample_rate, ResampleQuality::Low),
+			closer: Arc::downgrade(&self.closer),
+		};
+
+	}
+
+}
+
+// pushes a decoded file into a ring at the pace of the time it's given
+struct FakeInput {
+	decoder: Decoder<Cursor<Vec<u8>>>,
+	ring: Arc<Mutex<Ring>>,
+	sample_rate: u32,
+	pushed: u64,
+}
+
+impl FakeInput {
+
+	// push every frame due by elapsed, false when the file ran out
+	fn tick(&mut self, elapsed: Duration) -> bool {
+
+		let due = (elapsed.as_secs_f64() * self.sample_rate as f64) as u64;
+
+		if let Ok(mut ring) = self.ring.lock() {
+			while self.pushed < due {
+				match self.decoder.next() {
+					Some(f) => ring.push(f),
+					None => return false,
+				}
+				self.pushed += 1;
+			}
+		}
+
+		return true;
+
+	}
+
+}
+
+// reads a ring live, outputs silence while waiting for more frames
+struct RingReader {
+	ring: Arc<Mutex<Ring>>,
+	cursor: u64,
+	sample_rate: u32,
+	buffering: bool,
+}
+
+impl Iterator for RingReader {
+
+	type Item = Frame;
+
+	fn next(&mut self) -> Option<Self::Item> {
+
+		let ring = match self.ring.lock() {
+			Ok(ring) => ring,
+			Err(_) => return None,
+		};
+
+		self.cursor = self.cursor.max(ring.oldest());
+
+		let available = ring.written - self.cursor.min(ring.written);
+
+		if available == 0 {
+			self.buffering = true;
+		}
+
+		if self.buffering {
+			if available < MONITOR_LATENCY {
+				return Some(Frame::default());
+			}
+			self.buffering = false;
+		}
+
+		let f = ring.get(self.cursor);
+
+		self.cursor += 1;
+
+		return Some(f);
+
+	}
+
+}
+
+impl Source for RingReader {
+	fn sample_rate(&self) -> u32 {
+		return self.sample_rate;
+	}
+}
+
+/// Live Playback of a [`Recording`](struct.Recording.html)
+pub struct RecordingSource {
+	inner: Resampler<RingReader>,
+	closer: Weak<Closer>,
+}
+
+impl Iterator for RecordingSource {
+	type Item = Frame;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.closer.upgrade()?;
+		return self.inner.next();
+	}
+}
+
+impl Source for RecordingSource {
+	fn sample_rate(&self) -> u32 {
+		return self.inner.sample_rate();
+	}
+}
+
+#[test]
+fn record_from_file() {
+
+	let path = std::env::temp_dir().join(format!(
+		"dirty_record_from_file_{}_{}.wav",
+		std::process::id(),
+		std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|d| d.as_nanos())
+			.unwrap_or(0),
+	));
+
+	let frames = (0..22050)
+		.map(|i| {
+			let v = (i % 100) as f32 / 100.0 - 0.5;
+			return Frame::new(v, -v);
+		})
+		.collect::<Vec<Frame>>();
+
+	AudioBuffer::from_frames(frames.clone(), 22050)
+		.save_wav(&path, WavFormat::Float32)
+		.unwrap();
+
+	let (mut rec, mut input) = Recording::fake(&path, Duration::from_secs(1)).unwrap();
+
+	std::fs::remove_file(&path).ok();
+
+	let src = rec.source();
+
+	assert_eq!(rec.sample_rate(), 22050);
+	assert_eq!(src.sample_rate(), SPEC.sample_rate);
+
+	// arrives at real time pace, in order
+	assert!(input.tick(Duration::from_millis(200)));
+
+	let a = rec.read();
+
+	assert_eq!(a.len(), 4410);
+	assert_eq!(&a[..], &frames[..a.len()]);
+
+	assert!(input.tick(Duration::from_millis(300)));
+
+	let b = rec.read();
+
+	assert_eq!(b.len(), 2205);
+	assert_eq!(&b[..], &frames[a.len()..a.len() + b.len()]);
+	assert_eq!(rec.latest(b.len()), b);
+
+	// stops when the file ends
+	assert!(input.tick(Duration::from_millis(1000)));
+	assert_eq!(rec.read().len(), 22050 - 6615);
+	assert!(!input.tick(Duration::from_millis(1100)));
+
+	// live sources end with the recording
+	drop(rec);
+
+	let mut src = src;
+
+	assert!(src.next().is_none());
+
+}
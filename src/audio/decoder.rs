@@ -45,6 +45,27 @@ impl<R: Read + Seek> Source for Decoder<R> {
 			Decoder::Vorbis(decoder) => decoder.seek_start(),
 		};
 	}
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+		return match self {
+			Decoder::Wav(decoder) => decoder.seek(pos),
+			Decoder::Mp3(decoder) => decoder.seek(pos),
+			Decoder::Vorbis(decoder) => decoder.seek(pos),
+		};
+	}
+	fn position(&self) -> Option<Duration> {
+		return match self {
+			Decoder::Wav(decoder) => decoder.position(),
+			Decoder::Mp3(decoder) => decoder.position(),
+			Decoder::Vorbis(decoder) => decoder.position(),
+		};
+	}
+	fn duration(&self) -> Option<Duration> {
+		return match self {
+			Decoder::Wav(decoder) => decoder.duration(),
+			Decoder::Mp3(decoder) => decoder.duration(),
+			Decoder::Vorbis(decoder) => decoder.duration(),
+		};
+	}
 }
 
 impl<R: Read + Seek> Iterator for Decoder<R> {
@@ -4,16 +4,86 @@ use super::*;
 
 pub struct Audio {
 	mixer: Arc<Mutex<Mixer>>,
+	resample_quality: ResampleQuality,
 }
 
 impl Audio {
 	pub(crate) fn new(_: &conf::Conf) -> Result<Self> {
 		return Ok(Self {
 			mixer: Arc::new(Mutex::new(Mixer::new(SPEC))),
+			resample_quality: ResampleQuality::default(),
 		});
 	}
+	pub fn backend(&self) -> &conf::AudioBackend {
+		return &conf::AudioBackend::Null;
+	}
 	pub(super) fn mixer(&self) -> &Arc<Mutex<Mixer>> {
 		return &self.mixer;
 	}
+	pub fn set_limiter(&mut self, b: bool) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.set_limiter(b);
+		}
+	}
+	pub fn limiter(&self) -> bool {
+		return self.mixer
+			.lock()
+			.map(|m| m.limiter())
+			.unwrap_or(false);
+	}
+	pub fn set_listener(&mut self, l: Listener) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.set_listener(l);
+		}
+	}
+	pub fn listener(&self) -> Listener {
+		return self.mixer
+			.lock()
+			.map(|m| m.listener())
+			.unwrap_or_default();
+	}
+	pub fn set_resample_quality(&mut self, q: ResampleQuality) {
+		self.resample_quality = q;
+	}
+	pub fn resample_quality(&self) -> ResampleQuality {
+		return self.resample_quality;
+	}
+	pub fn add_master_effect(&mut self, e: Arc<Mutex<dyn Effect + Send>>) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.add_effect(e);
+		}
+	}
+	pub fn record(&mut self) -> Result<Recording> {
+		return self.record_from(InputDevice::Default, Duration::from_secs(10));
+	}
+	pub fn record_from(&mut self, device: InputDevice, buffer: Duration) -> Result<Recording> {
+		return match device {
+			InputDevice::Default => Err(format!("audio input not supported")),
+			InputDevice::File(path) => Recording::from_file(path, buffer),
+		};
+	}
+	pub fn add_bus(&mut self, name: &str, parent: Option<&str>) -> Result<Arc<Mutex<Bus>>> {
+		return self.mixer
+			.lock()
+			.map_err(|_| format!("failed to get mixer"))?
+			.add_bus(name, parent);
+	}
+	pub fn bus(&self, name: &str) -> Option<Arc<Mutex<Bus>>> {
+		return self.mixer
+			.lock()
+			.ok()
+			.and_then(|m| m.bus(name));
+	}
+	pub fn set_bus_parent(&mut self, name: &str, parent: Option<&str>) -> Result<()> {
+		return self.mixer
+			.lock()
+			.map_err(|_| format!("failed to get mixer"))?
+			.set_bus_parent(name, parent);
+	}
+	pub fn remove_bus(&mut self, name: &str) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.remove_bus(name);
+		}
+	}
 }
 
@@ -0,0 +1,503 @@
+// wengwengweng
+
+use std::collections::VecDeque;
+
+use super::*;
+
+/// Sidechain Send
+///
+/// chain this on a source to use its signal as the detector input of a [`Compressor`](struct.Compressor.html) or [`Gate`](struct.Gate.html) on another source, audio passes through unchanged
+/// ```ignore
+/// let duck = Sidechain::new();
+///
+/// voice.add_effect(Arc::new(Mutex::new(duck.clone())));
+/// music.add_effect(Arc::new(Mutex::new(Compressor::new(-30.0, 4.0).sidechain(duck))));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sidechain {
+	frame: Arc<Mutex<Frame>>,
+}
+
+impl Sidechain {
+
+	pub fn new() -> Self {
+		return Self {
+			frame: Arc::new(Mutex::new(Frame::default())),
+		};
+	}
+
+	fn frame(&self) -> Frame {
+		return self.frame
+			.lock()
+			.map(|f| *f)
+			.unwrap_or_default();
+	}
+
+}
+
+impl Default for Sidechain {
+	fn default() -> Self {
+		return Self::new();
+	}
+}
+
+impl Effect for Sidechain {
+
+	fn process(&mut self, f: Frame) -> Frame {
+
+		if let Ok(mut frame) = self.frame.lock() {
+			*frame = f;
+		}
+
+		return f;
+
+	}
+
+	fn leftover(&mut self) -> Option<Frame> {
+
+		// the source stopped, don't keep the last frame around as the key
+		if let Ok(mut frame) = self.frame.lock() {
+			*frame = Frame::default();
+		}
+
+		return None;
+
+	}
+
+}
+
+fn peak(f: Frame) -> f32 {
+	return f32::max(f.left.abs(), f.right.abs());
+}
+
+/// Downward Compressor
+#[derive(Clone, Debug)]
+pub struct Compressor {
+	threshold: f32,
+	ratio: f32,
+	knee: f32,
+	makeup: f32,
+	attack: f32,
+	release: f32,
+	// current gain reduction in db, <= 0.0
+	reduction: f32,
+	sidechain: Option<Sidechain>,
+}
+
+impl Compressor {
+
+	/// create with threshold in db and ratio, 10ms attack, 100ms release, 6db knee and no makeup gain
+	pub fn new(threshold: f32, ratio: f32) -> Self {
+		return Self {
+			threshold: threshold,
+			ratio: ratio.max(1.0),
+			knee: 6.0,
+			makeup: 0.0,
+			attack: utils::time_coeff(0.01, SPEC.sample_rate),
+			release: utils::time_coeff(0.1, SPEC.sample_rate),
+			reduction: 0.0,
+			sidechain: None,
+		};
+	}
+
+	pub fn threshold(mut self, t: f32) -> Self {
+		self.set_threshold(t);
+		return self;
+	}
+
+	pub fn ratio(mut self, r: f32) -> Self {
+		self.set_ratio(r);
+		return self;
+	}
+
+	pub fn knee(mut self, k: f32) -> Self {
+		self.set_knee(k);
+		return self;
+	}
+
+	pub fn makeup(mut self, m: f32) -> Self {
+		self.set_makeup(m);
+		return self;
+	}
+
+	pub fn attack(mut self, t: Duration) -> Self {
+		self.set_attack(t);
+		return self;
+	}
+
+	pub fn release(mut self, t: Duration) -> Self {
+		self.set_release(t);
+		return self;
+	}
+
+	/// use another source's signal to drive the gain reduction
+	pub fn sidechain(mut self, s: Sidechain) -> Self {
+		self.sidechain = Some(s);
+		return self;
+	}
+
+	/// level in db where compression starts
+	pub fn set_threshold(&mut self, t: f32) {
+		self.threshold = t;
+	}
+
+	/// how many db of input over the threshold makes 1db of output
+	pub fn set_ratio(&mut self, r: f32) {
+		self.ratio = r.max(1.0);
+	}
+
+	/// width in db of the soft transition around the threshold
+	pub fn set_knee(&mut self, k: f32) {
+		self.knee = k.max(0.0);
+	}
+
+	/// gain in db applied after compression
+	pub fn set_makeup(&mut self, m: f32) {
+		self.makeup = m;
+	}
+
+	pub fn set_attack(&mut self, t: Duration) {
+		self.attack = utils::time_coeff(t.as_secs_f32(), SPEC.sample_rate);
+	}
+
+	pub fn set_release(&mut self, t: Duration) {
+		self.release = utils::time_coeff(t.as_secs_f32(), SPEC.sample_rate);
+	}
+
+	pub fn set_sidechain(&mut self, s: Option<Sidechain>) {
+		self.sidechain = s;
+	}
+
+	/// current gain reduction in db
+	pub fn reduction(&self) -> f32 {
+		return self.reduction;
+	}
+
+	fn curve(&self, x: f32) -> f32 {
+
+		let over = x - self.threshold;
+
+		if 2.0 * over < -self.knee {
+			return x;
+		} else if self.knee > 0.0 && 2.0 * over.abs() <= self.knee {
+			let k = over + self.knee / 2.0;
+			return x + (1.0 / self.ratio - 1.0) * k * k / (2.0 * self.knee);
+		} else {
+			return self.threshold + over / self.ratio;
+		}
+
+	}
+
+}
+
+impl Default for Compressor {
+	fn default() -> Self {
+		return Self::new(-12.0, 4.0);
+	}
+}
+
+impl Effect for Compressor {
+
+	fn process(&mut self, f: Frame) -> Frame {
+
+		let key = match &self.sidechain {
+			Some(s) => s.frame(),
+			None => f,
+		};
+
+		let x = utils::gain_to_db(peak(key));
+		let target = self.curve(x) - x;
+
+		let coeff = if target < self.reduction {
+			self.attack
+		} else {
+			self.release
+		};
+
+		self.reduction = target + (self.reduction - target) * coeff;
+
+		return f * utils::db_to_gain(self.reduction + self.makeup);
+
+	}
+
+}
+
+/// Lookahead Brickwall Limiter
+///
+/// output never goes above the ceiling, at the cost of a few milliseconds of latency
+#[derive(Clone, Debug)]
+pub struct Limiter {
+	ceiling: f32,
+	release: f32,
+	gain: f32,
+	lookahead: usize,
+	attack: f32,
+	buffer: VecDeque<Frame>,
+	// (frame index, required gain), increasing gain for sliding window minimum
+	window: VecDeque<(usize, f32)>,
+	count: usize,
+	pending: usize,
+}
+
+impl Limiter {
+
+	/// create with ceiling in db, 5ms lookahead and 50ms release
+	pub fn new(ceiling: f32) -> Self {
+
+		let lookahead = (0.005 * SPEC.sample_rate as f32) as usize;
+		let mut buffer = VecDeque::with_capacity(lookahead + 1);
+
+		for _ in 0..lookahead {
+			buffer.push_back(Frame::default());
+		}
+
+		return Self {
+			ceiling: utils::db_to_gain(ceiling.min(0.0)),
+			release: utils::time_coeff(0.05, SPEC.sample_rate),
+			gain: 1.0,
+			lookahead: lookahead,
+			attack: 1.0 - f32::exp(-4.0 / lookahead.max(1) as f32),
+			buffer: buffer,
+			window: VecDeque::new(),
+			count: 0,
+			pending: 0,
+		};
+
+	}
+
+	pub fn release(mut self, t: Duration) -> Self {
+		self.set_release(t);
+		return self;
+	}
+
+	/// max output level in db
+	pub fn set_ceiling(&mut self, c: f32) {
+		self.ceiling = utils::db_to_gain(c.min(0.0));
+	}
+
+	pub fn set_release(&mut self, t: Duration) {
+		self.release = utils::time_coeff(t.as_secs_f32(), SPEC.sample_rate);
+	}
+
+	/// current gain reduction in db
+	pub fn reduction(&self) -> f32 {
+		return utils::gain_to_db(self.gain);
+	}
+
+	fn limit(&mut self, f: Frame) -> Frame {
+
+		let p = peak(f);
+		let required = if p > self.ceiling {
+			self.ceiling / p
+		} else {
+			1.0
+		};
+
+		while let Some((_, g)) = self.window.back() {
+			if *g >= required {
+				self.window.pop_back();
+			} else {
+				break;
+			}
+		}
+
+		self.window.push_back((self.count, required));
+
+		while let Some((i, _)) = self.window.front() {
+			if *i + self.lookahead < self.count {
+				self.window.pop_front();
+			} else {
+				break;
+			}
+		}
+
+		self.count += 1;
+
+		let target = self.window
+			.front()
+			.map(|(_, g)| *g)
+			.unwrap_or(1.0);
+
+		if target < self.gain {
+			self.gain += (target - self.gain) * self.attack;
+		} else {
+			self.gain = target + (self.gain - target) * self.release;
+		}
+
+		self.buffer.push_back(f);
+
+		let out = self.buffer.pop_front().unwrap_or_default() * self.gain;
+		let c = self.ceiling;
+
+		return Frame::new(out.left.max(-c).min(c), out.right.max(-c).min(c));
+
+	}
+
+}
+
+impl Default for Limiter {
+	fn default() -> Self {
+		return Self::new(0.0);
+	}
+}
+
+impl Effect for Limiter {
+
+	fn process(&mut self, f: Frame) -> Frame {
+		self.pending = self.lookahead;
+		return self.limit(f);
+	}
+
+	fn leftover(&mut self) -> Option<Frame> {
+
+		if self.pending == 0 {
+			return None;
+		}
+
+		self.pending -= 1;
+
+		return Some(self.limit(Frame::default()));
+
+	}
+
+}
+
+/// Noise Gate
+#[derive(Clone, Debug)]
+pub struct Gate {
+	threshold: f32,
+	range: f32,
+	attack: f32,
+	release: f32,
+	hold: usize,
+	held: usize,
+	gain: f32,
+	sidechain: Option<Sidechain>,
+}
+
+impl Gate {
+
+	/// create with threshold in db, 1ms attack, 50ms hold, 100ms release, fully closes
+	pub fn new(threshold: f32) -> Self {
+		return Self {
+			threshold: threshold,
+			range: 0.0,
+			attack: utils::time_coeff(0.001, SPEC.sample_rate),
+			release: utils::time_coeff(0.1, SPEC.sample_rate),
+			hold: (0.05 * SPEC.sample_rate as f32) as usize,
+			held: 0,
+			gain: 0.0,
+			sidechain: None,
+		};
+	}
+
+	pub fn range(mut self, r: f32) -> Self {
+		self.set_range(r);
+		return self;
+	}
+
+	pub fn attack(mut self, t: Duration) -> Self {
+		self.set_attack(t);
+		return self;
+	}
+
+	pub fn hold(mut self, t: Duration) -> Self {
+		self.set_hold(t);
+		return self;
+	}
+
+	pub fn release(mut self, t: Duration) -> Self {
+		self.set_release(t);
+		return self;
+	}
+
+	/// use another source's signal to open / close the gate
+	pub fn sidechain(mut self, s: Sidechain) -> Self {
+		self.sidechain = Some(s);
+		return self;
+	}
+
+	/// level in db under which the gate closes
+	pub fn set_threshold(&mut self, t: f32) {
+		self.threshold = t;
+	}
+
+	/// attenuation in db when closed, e.g. -20.0, default fully closes
+	pub fn set_range(&mut self, r: f32) {
+		self.range = utils::db_to_gain(r.min(0.0));
+	}
+
+	pub fn set_attack(&mut self, t: Duration) {
+		self.attack = utils::time_coeff(t.as_secs_f32(), SPEC.sample_rate);
+	}
+
+	/// how long to stay open after the signal drops below threshold
+	pub fn set_hold(&mut self, t: Duration) {
+		self.hold = (t.as_secs_f32() * SPEC.sample_rate as f32) as usize;
+	}
+
+	pub fn set_release(&mut self, t: Duration) {
+		self.release = utils::time_coeff(t.as_secs_f32(), SPEC.sample_rate);
+	}
+
+	pub fn set_sidechain(&mut self, s: Option<Sidechain>) {
+		self.sidechain = s;
+	}
+
+	pub fn open(&self) -> bool {
+		return self.held > 0;
+	}
+
+}
+
+impl Default for Gate {
+	fn default() -> Self {
+		return Self::new(-40.0);
+	}
+}
+
+impl Effect for Gate {
+
+	fn process(&mut self, f: Frame) -> Frame {
+
+		let key = match &self.sidechain {
+			Some(s) => s.frame(),
+			None => f,
+		};
+
+		if utils::gain_to_db(peak(key)) >= self.threshold {
+			self.held = self.hold.max(1);
+		} else if self.held > 0 {
+			self.held -= 1;
+		}
+
+		let (target, coeff) = if self.held > 0 {
+			(1.0, self.attack)
+		} else {
+			(self.range, self.release)
+		};
+
+		self.gain = target + (self.gain - target) * coeff;
+
+		return f * self.gain;
+
+	}
+
+}
+
+#[test]
+fn compressor_hard_knee() {
+
+	let mut c = Compressor::new(-6.0, 4.0).knee(0.0);
+	let at = utils::db_to_gain(-6.0);
+
+	// under, exactly at and over the threshold
+	for g in &[at * 0.5, at, at, at * 2.0, at, at * 0.5] {
+		for _ in 0..100 {
+			let out = c.process(Frame::new(*g, *g));
+			assert!(out.left.is_finite() && out.right.is_finite());
+		}
+		assert!(c.reduction().is_finite() && c.reduction() <= 0.0);
+	}
+
+}
@@ -129,28 +129,220 @@ impl Effect for Delay {
 
 }
 
-// TODO
+// freeverb tunings, in frames at 44.1khz
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+const STEREO_SPREAD: usize = 23;
+const REVERB_GAIN: f32 = 0.015;
+const SCALE_ROOM: f32 = 0.28;
+const OFFSET_ROOM: f32 = 0.7;
+const SCALE_DAMP: f32 = 0.4;
+const REVERB_SILENCE: f32 = 0.0001;
+
+fn scale_tuning(n: usize) -> usize {
+	return (n as f32 * SPEC.sample_rate as f32 / 44100.0) as usize;
+}
+
+#[derive(Clone, Debug)]
+struct Comb {
+	buffer: Vec<f32>,
+	idx: usize,
+	store: f32,
+}
+
+impl Comb {
+
+	fn new(len: usize) -> Self {
+		return Self {
+			buffer: vec![0.0; len.max(1)],
+			idx: 0,
+			store: 0.0,
+		};
+	}
+
+	fn process(&mut self, input: f32, feedback: f32, damp: f32) -> f32 {
+
+		let out = self.buffer[self.idx];
+
+		self.store = out * (1.0 - damp) + self.store * damp;
+		self.buffer[self.idx] = input + self.store * feedback;
+		self.idx = (self.idx + 1) % self.buffer.len();
+
+		return out;
+
+	}
+
+}
+
+#[derive(Clone, Debug)]
+struct Allpass {
+	buffer: Vec<f32>,
+	idx: usize,
+}
+
+impl Allpass {
+
+	fn new(len: usize) -> Self {
+		return Self {
+			buffer: vec![0.0; len.max(1)],
+			idx: 0,
+		};
+	}
+
+	fn process(&mut self, input: f32) -> f32 {
+
+		let buf = self.buffer[self.idx];
+
+		self.buffer[self.idx] = input + buf * 0.5;
+		self.idx = (self.idx + 1) % self.buffer.len();
+
+		return buf - input;
+
+	}
+
+}
+
+/// Freeverb style algorithmic reverb
 #[derive(Clone, Debug)]
 pub struct Reverb {
+	combs_l: Vec<Comb>,
+	combs_r: Vec<Comb>,
+	allpasses_l: Vec<Allpass>,
+	allpasses_r: Vec<Allpass>,
+	room_size: f32,
+	damping: f32,
+	mix: f32,
+	width: f32,
+	// how many silent frames in a row has the tail produced
+	silence: usize,
 }
 
 impl Reverb {
-	pub fn new(d: f32) -> Self {
+
+	/// create with room size (0.0 - 1.0), with 0.5 damping, 0.3 mix and full stereo width
+	pub fn new(room_size: f32) -> Self {
 		return Self {
+			combs_l: COMB_TUNINGS.iter().map(|n| Comb::new(scale_tuning(*n))).collect(),
+			combs_r: COMB_TUNINGS.iter().map(|n| Comb::new(scale_tuning(*n + STEREO_SPREAD))).collect(),
+			allpasses_l: ALLPASS_TUNINGS.iter().map(|n| Allpass::new(scale_tuning(*n))).collect(),
+			allpasses_r: ALLPASS_TUNINGS.iter().map(|n| Allpass::new(scale_tuning(*n + STEREO_SPREAD))).collect(),
+			room_size: room_size.max(0.0).min(1.0),
+			damping: 0.5,
+			mix: 0.3,
+			width: 1.0,
+			silence: 0,
 		};
 	}
+
+	pub fn room_size(&self) -> f32 {
+		return self.room_size;
+	}
+
+	/// how long the tail rings (0.0 - 1.0)
+	pub fn set_room_size(&mut self, s: f32) {
+		self.room_size = s.max(0.0).min(1.0);
+	}
+
+	pub fn damping(&self) -> f32 {
+		return self.damping;
+	}
+
+	/// how fast high frequencies die out in the tail (0.0 - 1.0)
+	pub fn set_damping(&mut self, d: f32) {
+		self.damping = d.max(0.0).min(1.0);
+	}
+
+	pub fn mix(&self) -> f32 {
+		return self.mix;
+	}
+
+	/// wet / dry mix, 0.0 is fully dry, 1.0 is fully wet
+	pub fn set_mix(&mut self, m: f32) {
+		self.mix = m.max(0.0).min(1.0);
+	}
+
+	pub fn width(&self) -> f32 {
+		return self.width;
+	}
+
+	/// stereo width of the wet signal, 0.0 is mono
+	pub fn set_width(&mut self, w: f32) {
+		self.width = w.max(0.0).min(1.0);
+	}
+
+	fn tail_len(&self) -> usize {
+		return self.combs_r
+			.iter()
+			.map(|c| c.buffer.len())
+			.max()
+			.unwrap_or(0);
+	}
+
+	fn wet(&mut self, f: Frame) -> Frame {
+
+		let input = (f.left + f.right) * REVERB_GAIN;
+		let feedback = self.room_size * SCALE_ROOM + OFFSET_ROOM;
+		let damp = self.damping * SCALE_DAMP;
+
+		let mut l = self.combs_l
+			.iter_mut()
+			.fold(0.0, |acc, c| acc + c.process(input, feedback, damp));
+
+		let mut r = self.combs_r
+			.iter_mut()
+			.fold(0.0, |acc, c| acc + c.process(input, feedback, damp));
+
+		for a in &mut self.allpasses_l {
+			l = a.process(l);
+		}
+
+		for a in &mut self.allpasses_r {
+			r = a.process(r);
+		}
+
+		let wet1 = self.width / 2.0 + 0.5;
+		let wet2 = (1.0 - self.width) / 2.0;
+
+		return Frame::new(l * wet1 + r * wet2, r * wet1 + l * wet2);
+
+	}
+
 }
 
 impl Default for Reverb {
 	fn default() -> Self {
-		return Self::new(0.0);
+		return Self::new(0.5);
 	}
 }
 
 impl Effect for Reverb {
+
 	fn process(&mut self, f: Frame) -> Frame {
-		return f;
+
+		self.silence = 0;
+
+		return f * (1.0 - self.mix) + self.wet(f) * self.mix;
+
 	}
+
+	fn leftover(&mut self) -> Option<Frame> {
+
+		if self.silence >= self.tail_len() {
+			return None;
+		}
+
+		let out = self.wet(Frame::default()) * self.mix;
+
+		if out.left.abs() < REVERB_SILENCE && out.right.abs() < REVERB_SILENCE {
+			self.silence += 1;
+		} else {
+			self.silence = 0;
+		}
+
+		return Some(out);
+
+	}
+
 }
 
 pub struct Lowpass {
@@ -190,3 +382,313 @@ impl Effect for Lowpass {
 
 }
 
+
+/// Response Shape of a [`Biquad`](struct.Biquad.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterType {
+	Lowpass,
+	Highpass,
+	/// constant 0db peak gain
+	Bandpass,
+	Notch,
+	/// boost / cut around freq by gain
+	Peak,
+	/// boost / cut below freq by gain
+	LowShelf,
+	/// boost / cut above freq by gain
+	HighShelf,
+}
+
+// time for parameter changes to settle
+const BIQUAD_SMOOTH_TIME: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BiquadCoeffs {
+	b0: f32,
+	b1: f32,
+	b2: f32,
+	a1: f32,
+	a2: f32,
+}
+
+impl BiquadCoeffs {
+
+	// https://www.w3.org/2011/audio/audio-eq-cookbook.html
+	fn new(ty: FilterType, freq: f32, q: f32, gain: f32) -> Self {
+
+		let w0 = 2.0 * PI * freq / SPEC.sample_rate as f32;
+		let cos = w0.cos();
+		let alpha = w0.sin() / (2.0 * q);
+		let a = 10f32.powf(gain / 40.0);
+		let sqa = 2.0 * a.sqrt() * alpha;
+
+		let (b0, b1, b2, a0, a1, a2) = match ty {
+			FilterType::Lowpass => (
+				(1.0 - cos) / 2.0,
+				1.0 - cos,
+				(1.0 - cos) / 2.0,
+				1.0 + alpha,
+				-2.0 * cos,
+				1.0 - alpha,
+			),
+			FilterType::Highpass => (
+				(1.0 + cos) / 2.0,
+				-(1.0 + cos),
+				(1.0 + cos) / 2.0,
+				1.0 + alpha,
+				-2.0 * cos,
+				1.0 - alpha,
+			),
+			FilterType::Bandpass => (
+				alpha,
+				0.0,
+				-alpha,
+				1.0 + alpha,
+				-2.0 * cos,
+				1.0 - alpha,
+			),
+			FilterType::Notch => (
+				1.0,
+				-2.0 * cos,
+				1.0,
+				1.0 + alpha,
+				-2.0 * cos,
+				1.0 - alpha,
+			),
+			FilterType::Peak => (
+				1.0 + alpha * a,
+				-2.0 * cos,
+				1.0 - alpha * a,
+				1.0 + alpha / a,
+				-2.0 * cos,
+				1.0 - alpha / a,
+			),
+			FilterType::LowShelf => (
+				a * ((a + 1.0) - (a - 1.0) * cos + sqa),
+				2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+				a * ((a + 1.0) - (a - 1.0) * cos - sqa),
+				(a + 1.0) + (a - 1.0) * cos + sqa,
+				-2.0 * ((a - 1.0) + (a + 1.0) * cos),
+				(a + 1.0) + (a - 1.0) * cos - sqa,
+			),
+			FilterType::HighShelf => (
+				a * ((a + 1.0) + (a - 1.0) * cos + sqa),
+				-2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+				a * ((a + 1.0) + (a - 1.0) * cos - sqa),
+				(a + 1.0) - (a - 1.0) * cos + sqa,
+				2.0 * ((a - 1.0) - (a + 1.0) * cos),
+				(a + 1.0) - (a - 1.0) * cos - sqa,
+			),
+		};
+
+		return Self {
+			b0: b0 / a0,
+			b1: b1 / a0,
+			b2: b2 / a0,
+			a1: a1 / a0,
+			a2: a2 / a0,
+		};
+
+	}
+
+}
+
+// transposed direct form II state for 1 channel
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BiquadState {
+	z1: f32,
+	z2: f32,
+}
+
+impl BiquadState {
+	fn process(&mut self, c: &BiquadCoeffs, x: f32) -> f32 {
+		let y = c.b0 * x + self.z1;
+		self.z1 = c.b1 * x - c.a1 * y + self.z2;
+		self.z2 = c.b2 * x - c.a2 * y;
+		return y;
+	}
+}
+
+/// RBJ Biquad Filter
+///
+/// parameters can be changed while playing (e.g. through an `Arc<Mutex<Biquad>>` also passed to [`Track::add_effect`](struct.Track.html#method.add_effect)), they glide to the new value over a few milliseconds to avoid clicks
+#[derive(Clone, Debug)]
+pub struct Biquad {
+	ty: FilterType,
+	freq: f32,
+	q: f32,
+	gain: f32,
+	cur_freq: f32,
+	cur_q: f32,
+	cur_gain: f32,
+	coeffs: BiquadCoeffs,
+	left: BiquadState,
+	right: BiquadState,
+}
+
+impl Biquad {
+
+	/// create a filter, freq is in hz, gain is in db and only used by peak & shelf types
+	pub fn new(ty: FilterType, freq: f32, q: f32, gain: f32) -> Self {
+
+		let freq = clamp_freq(freq);
+		let q = q.max(0.01);
+
+		return Self {
+			ty: ty,
+			freq: freq,
+			q: q,
+			gain: gain,
+			cur_freq: freq,
+			cur_q: q,
+			cur_gain: gain,
+			coeffs: BiquadCoeffs::new(ty, freq, q, gain),
+			left: BiquadState::default(),
+			right: BiquadState::default(),
+		};
+
+	}
+
+	pub fn lowpass(freq: f32, q: f32) -> Self {
+		return Self::new(FilterType::Lowpass, freq, q, 0.0);
+	}
+
+	pub fn highpass(freq: f32, q: f32) -> Self {
+		return Self::new(FilterType::Highpass, freq, q, 0.0);
+	}
+
+	pub fn bandpass(freq: f32, q: f32) -> Self {
+		return Self::new(FilterType::Bandpass, freq, q, 0.0);
+	}
+
+	pub fn notch(freq: f32, q: f32) -> Self {
+		return Self::new(FilterType::Notch, freq, q, 0.0);
+	}
+
+	pub fn peak(freq: f32, q: f32, gain: f32) -> Self {
+		return Self::new(FilterType::Peak, freq, q, gain);
+	}
+
+	pub fn low_shelf(freq: f32, q: f32, gain: f32) -> Self {
+		return Self::new(FilterType::LowShelf, freq, q, gain);
+	}
+
+	pub fn high_shelf(freq: f32, q: f32, gain: f32) -> Self {
+		return Self::new(FilterType::HighShelf, freq, q, gain);
+	}
+
+	pub fn ty(&self) -> FilterType {
+		return self.ty;
+	}
+
+	pub fn set_ty(&mut self, ty: FilterType) {
+		self.ty = ty;
+		self.update_coeffs();
+	}
+
+	pub fn freq(&self) -> f32 {
+		return self.freq;
+	}
+
+	pub fn set_freq(&mut self, f: f32) {
+		self.freq = clamp_freq(f);
+	}
+
+	pub fn q(&self) -> f32 {
+		return self.q;
+	}
+
+	pub fn set_q(&mut self, q: f32) {
+		self.q = q.max(0.01);
+	}
+
+	pub fn gain(&self) -> f32 {
+		return self.gain;
+	}
+
+	pub fn set_gain(&mut self, g: f32) {
+		self.gain = g;
+	}
+
+	/// clear filter memory
+	pub fn reset(&mut self) {
+		self.left = BiquadState::default();
+		self.right = BiquadState::default();
+	}
+
+	// set freq without gliding, for modulation that's already smooth (envelopes / lfos)
+	pub(super) fn set_freq_now(&mut self, f: f32) {
+
+		let f = clamp_freq(f);
+
+		if f != self.cur_freq {
+			self.freq = f;
+			self.cur_freq = f;
+			self.update_coeffs();
+		}
+
+	}
+
+	// process 1 channel using only the left state
+	pub(super) fn process_mono(&mut self, x: f32) -> f32 {
+		self.smooth();
+		return self.left.process(&self.coeffs, x);
+	}
+
+	fn update_coeffs(&mut self) {
+		self.coeffs = BiquadCoeffs::new(self.ty, self.cur_freq, self.cur_q, self.cur_gain);
+	}
+
+	fn smooth(&mut self) {
+
+		if self.cur_freq == self.freq && self.cur_q == self.q && self.cur_gain == self.gain {
+			return;
+		}
+
+		let k = 1.0 - f32::exp(-1.0 / (BIQUAD_SMOOTH_TIME * SPEC.sample_rate as f32));
+
+		// glide freq in log space so sweeps sound even
+		self.cur_freq = f32::exp(smooth_to(self.cur_freq.ln(), self.freq.ln(), k));
+		self.cur_q = smooth_to(self.cur_q, self.q, k);
+		self.cur_gain = smooth_to(self.cur_gain, self.gain, k);
+
+		if (self.cur_freq - self.freq).abs() < 0.01 {
+			self.cur_freq = self.freq;
+		}
+
+		self.update_coeffs();
+
+	}
+
+}
+
+fn clamp_freq(f: f32) -> f32 {
+	return f.max(10.0).min(SPEC.sample_rate as f32 * 0.49);
+}
+
+fn smooth_to(cur: f32, target: f32, k: f32) -> f32 {
+
+	let next = cur + (target - cur) * k;
+
+	if (target - next).abs() < 0.0001 {
+		return target;
+	}
+
+	return next;
+
+}
+
+impl Effect for Biquad {
+
+	fn process(&mut self, f: Frame) -> Frame {
+
+		self.smooth();
+
+		return Frame::new(
+			self.left.process(&self.coeffs, f.left),
+			self.right.process(&self.coeffs, f.right),
+		);
+
+	}
+
+}
@@ -1,16 +1,143 @@
 // wengwengweng
 
+use std::collections::VecDeque;
 use super::*;
 
 pub(super) type SourceID = usize;
 
+// frames between spatial updates, gains ramp in between
+const SPATIAL_BLOCK: usize = 64;
+
 pub struct Control {
 	pub volume: f32,
 	pub pan: Pan,
+	/// playback rate, changes pitch along with speed unless `preserve_pitch` is on
+	pub speed: f32,
+	/// time-stretch instead of resample when speed isn't 1.0, keeps pitch but smears transients
+	pub preserve_pitch: bool,
 	pub paused: bool,
 	pub detach: bool,
 	pub looping: bool,
 	pub effects: Vec<Arc<Mutex<dyn Effect + Send>>>,
+	/// name of the [`Bus`](struct.Bus.html) this goes into, `None` goes straight to master
+	pub bus: Option<String>,
+	/// extra post-fader sends to other buses, with amount
+	pub sends: Vec<(String, f32)>,
+	/// position in 3d space, `None` plays it as is
+	pub emitter: Option<Arc<Mutex<Emitter>>>,
+	fade: Option<Fade>,
+	// drop resampling and stretching state before the next frame
+	seeked: bool,
+}
+
+// a linear volume ramp
+struct Fade {
+	from: f32,
+	to: f32,
+	time: f32,
+	duration: f32,
+	// pause and restore volume to this when it's done
+	pause: Option<f32>,
+	// detach when it's done
+	stop: bool,
+}
+
+impl Control {
+
+	/// ramp volume to a value
+	pub fn fade_to(&mut self, volume: f32, d: Duration) {
+		self.fade = Some(Fade {
+			from: self.volume,
+			to: volume,
+			time: 0.0,
+			duration: d.as_secs_f32(),
+			pause: None,
+			stop: false,
+		});
+	}
+
+	/// resume and ramp volume up from silence
+	pub fn fade_in(&mut self, d: Duration) {
+
+		// if it's fading out, go back to the volume before
+		let to = match &self.fade {
+			Some(Fade { pause: Some(v), .. }) => *v,
+			_ => self.volume,
+		};
+
+		self.volume = 0.0;
+		self.paused = false;
+		self.fade_to(to, d);
+
+	}
+
+	/// ramp volume down to silence then pause, volume is restored so it resumes at the same level
+	pub fn fade_out(&mut self, d: Duration) {
+
+		let restore = match &self.fade {
+			Some(Fade { pause: Some(v), .. }) => *v,
+			Some(f) => f.to,
+			None => self.volume,
+		};
+
+		self.fade_to(0.0, d);
+
+		if let Some(f) = &mut self.fade {
+			f.pause = Some(restore);
+		}
+
+	}
+
+	/// ramp volume down to silence then detach
+	pub fn fade_stop(&mut self, d: Duration) {
+
+		self.fade_to(0.0, d);
+
+		if let Some(f) = &mut self.fade {
+			f.stop = true;
+		}
+
+	}
+
+	/// if a fade is in progress
+	pub fn fading(&self) -> bool {
+		return self.fade.is_some();
+	}
+
+	// the source jumped, anything buffered from before is stale
+	pub(super) fn mark_seeked(&mut self) {
+		self.seeked = true;
+	}
+
+	fn update_fade(&mut self, dt: f32) {
+
+		let fade = match &mut self.fade {
+			Some(fade) => fade,
+			None => return,
+		};
+
+		fade.time += dt;
+
+		if fade.time < fade.duration {
+			self.volume = fade.from + (fade.to - fade.from) * fade.time / fade.duration;
+			return;
+		}
+
+		self.volume = fade.to;
+
+		if let Some(v) = fade.pause {
+			self.paused = true;
+			self.volume = v;
+		}
+
+		if fade.stop {
+			self.detach = true;
+		}
+
+		self.fade = None;
+
+	}
+
 }
 
 impl Default for Control {
@@ -18,10 +145,65 @@ impl Default for Control {
 		return Self {
 			volume: 1.0,
 			pan: Pan::new(1.0, 1.0),
+			speed: 1.0,
+			preserve_pitch: false,
 			paused: false,
 			detach: false,
 			looping: false,
 			effects: vec![],
+			bus: None,
+			sends: vec![],
+			emitter: None,
+			fade: None,
+			seeked: false,
+		};
+	}
+}
+
+/// A Named Sub Mix
+///
+/// sources routed into a bus are summed, run through the bus effects, then go to its parent bus or master and to any buses it sends to
+pub struct Bus {
+	pub volume: f32,
+	pub pan: Pan,
+	pub mute: bool,
+	pub effects: Vec<Arc<Mutex<dyn Effect + Send>>>,
+	sends: Vec<(String, f32)>,
+	// sends changed since the mixer last worked out the routing
+	dirty: bool,
+}
+
+impl Bus {
+
+	/// set the amount sent post-fader to another bus, 0.0 removes the send, a send that would feed back into itself is dropped
+	pub fn set_send(&mut self, name: &str, amount: f32) {
+
+		self.sends.retain(|(n, _)| n != name);
+
+		if amount != 0.0 {
+			self.sends.push((name.to_owned(), amount));
+		}
+
+		self.dirty = true;
+
+	}
+
+	/// get the sends to other buses, with amount
+	pub fn sends(&self) -> &[(String, f32)] {
+		return &self.sends;
+	}
+
+}
+
+impl Default for Bus {
+	fn default() -> Self {
+		return Self {
+			volume: 1.0,
+			pan: Pan::new(1.0, 1.0),
+			mute: false,
+			effects: vec![],
+			sends: vec![],
+			dirty: false,
 		};
 	}
 }
@@ -29,12 +211,180 @@ impl Default for Control {
 struct SourceCtx {
 	src: Arc<Mutex<dyn Source + Send>>,
 	control: Arc<Mutex<Control>>,
+	spatial: Option<SpatialState>,
+	varispeed: Varispeed,
+	stretch: Option<Stretch>,
+	// read ahead by a stretch that's no longer needed, played before the source
+	pending: VecDeque<Frame>,
+}
+
+// plays a source at a changing rate with 4 point hermite interpolation
+#[derive(Default)]
+struct Varispeed {
+	// p[1] is the current frame, interpolates towards p[2]
+	p: [Frame; 4],
+	phase: f32,
+	started: bool,
+	// frames padded after the source ended
+	pad: usize,
+}
+
+impl Varispeed {
+
+	fn active(&self) -> bool {
+		return self.started;
+	}
+
+	fn reset(&mut self) {
+		*self = Self::default();
+	}
+
+	fn advance(&mut self, pull: &mut dyn FnMut() -> Option<Frame>) {
+
+		let next = if self.pad > 0 {
+			None
+		} else {
+			pull()
+		};
+
+		let next = match next {
+			Some(f) => f,
+			None => {
+				self.pad += 1;
+				self.p[3]
+			},
+		};
+
+		self.p = [self.p[1], self.p[2], self.p[3], next];
+
+	}
+
+	fn next(&mut self, rate: f32, pull: &mut dyn FnMut() -> Option<Frame>) -> Option<Frame> {
+
+		if !self.started {
+			let first = pull()?;
+			self.p = [first; 4];
+			self.phase = 0.0;
+			self.pad = 0;
+			self.started = true;
+			self.advance(pull);
+			self.advance(pull);
+		} else {
+			self.phase += rate;
+			while self.phase >= 1.0 {
+				self.phase -= 1.0;
+				self.advance(pull);
+			}
+		}
+
+		// the current frame is padding, source is done
+		if self.pad >= 3 {
+			return None;
+		}
+
+		let [a, b, c, d] = self.p;
+		let t = self.phase;
+
+		return Some(Frame::new(
+			hermite(a.left, b.left, c.left, d.left, t),
+			hermite(a.right, b.right, c.right, d.right, t),
+		));
+
+	}
+
+}
+
+fn hermite(a: f32, b: f32, c: f32, d: f32, t: f32) -> f32 {
+
+	let c1 = 0.5 * (c - a);
+	let c2 = a - 2.5 * b + 2.0 * c - 0.5 * d;
+	let c3 = 0.5 * (d - a) + 1.5 * (b - c);
+
+	return ((c3 * t + c2) * t + c1) * t + b;
+
+}
+
+// emitter state of a source
+struct SpatialState {
+	countdown: usize,
+	pan: Pan,
+	target: Pan,
+	step: Pan,
+	pitch: f32,
+	filter: Biquad,
+}
+
+impl SpatialState {
+
+	fn new(e: &Emitter, l: &Listener) -> Self {
+
+		let s = spatialize(e, l);
+
+		return Self {
+			countdown: SPATIAL_BLOCK,
+			pan: s.pan,
+			target: s.pan,
+			step: Pan::new(0.0, 0.0),
+			pitch: s.pitch,
+			filter: Biquad::lowpass(s.cutoff, std::f32::consts::FRAC_1_SQRT_2),
+		};
+
+	}
+
+	fn update(&mut self, e: &Arc<Mutex<Emitter>>, l: &Listener) {
+
+		if self.countdown > 0 {
+			self.countdown -= 1;
+			return;
+		}
+
+		let e = match e.lock() {
+			Ok(e) => *e,
+			Err(_) => return,
+		};
+
+		let s = spatialize(&e, l);
+		let n = SPATIAL_BLOCK as f32;
+
+		self.countdown = SPATIAL_BLOCK - 1;
+		self.pan = self.target;
+		self.target = s.pan;
+		self.step = Pan::new((s.pan.left - self.pan.left) / n, (s.pan.right - self.pan.right) / n);
+		self.pitch = s.pitch;
+		self.filter.set_freq(s.cutoff);
+
+	}
+
+	fn process(&mut self, f: Frame) -> Frame {
+		self.pan.left += self.step.left;
+		self.pan.right += self.step.right;
+		return self.filter.process(f) * self.pan;
+	}
+
+}
+
+struct BusCtx {
+	name: String,
+	parent: Option<String>,
+	control: Arc<Mutex<Bus>>,
 }
 
 pub(super) struct Mixer {
 	sources: HashMap<SourceID, SourceCtx>,
 	last_id: SourceID,
 	spec: Spec,
+	limiter: Option<Limiter>,
+	buses: Vec<BusCtx>,
+	bus_ids: HashMap<String, usize>,
+	// parent index of each bus
+	bus_parents: Vec<Option<usize>>,
+	// where each bus goes, its parent and the sends that don't close a loop
+	bus_outputs: Vec<Vec<(usize, f32)>>,
+	// a bus is mixed after every bus that feeds into it
+	bus_order: Vec<usize>,
+	bus_acc: Vec<Frame>,
+	listener: Listener,
+	effects: Vec<Arc<Mutex<dyn Effect + Send>>>,
 }
 
 impl Mixer {
@@ -44,6 +394,15 @@ impl Mixer {
 			sources: hmap![],
 			last_id: 0,
 			spec: spec,
+			limiter: None,
+			buses: vec![],
+			bus_ids: hmap![],
+			bus_parents: vec![],
+			bus_outputs: vec![],
+			bus_order: vec![],
+			bus_acc: vec![],
+			listener: Listener::default(),
+			effects: vec![],
 		};
 	}
 
@@ -55,6 +414,10 @@ impl Mixer {
 		self.sources.insert(id, SourceCtx {
 			src: src,
 			control: ctrl.clone(),
+			spatial: None,
+			varispeed: Varispeed::default(),
+			stretch: None,
+			pending: VecDeque::new(),
 		});
 
 		self.last_id += 1;
@@ -63,10 +426,190 @@ impl Mixer {
 
 	}
 
-	pub fn count(&self) -> usize {
+	pub fn source_count(&self) -> usize {
 		return self.sources.len();
 	}
 
+	pub fn set_listener(&mut self, l: Listener) {
+		self.listener = l;
+	}
+
+	pub fn listener(&self) -> Listener {
+		return self.listener;
+	}
+
+	pub fn add_effect(&mut self, e: Arc<Mutex<dyn Effect + Send>>) {
+		self.effects.push(e);
+	}
+
+	pub fn set_limiter(&mut self, b: bool) {
+		if b {
+			if self.limiter.is_none() {
+				self.limiter = Some(Limiter::new(0.0));
+			}
+		} else {
+			self.limiter = None;
+		}
+	}
+
+	pub fn limiter(&self) -> bool {
+		return self.limiter.is_some();
+	}
+
+	pub fn add_bus(&mut self, name: &str, parent: Option<&str>) -> Result<Arc<Mutex<Bus>>> {
+
+		if self.bus_ids.contains_key(name) {
+			return Err(format!("bus '{}' already exists", name));
+		}
+
+		if let Some(parent) = parent {
+			if !self.bus_ids.contains_key(parent) {
+				return Err(format!("bus '{}' not found", parent));
+			}
+		}
+
+		let ctrl = Arc::new(Mutex::new(Bus::default()));
+
+		self.buses.push(BusCtx {
+			name: name.to_owned(),
+			parent: parent.map(|p| p.to_owned()),
+			control: ctrl.clone(),
+		});
+
+		self.rebuild_buses();
+
+		return Ok(ctrl);
+
+	}
+
+	pub fn bus(&self, name: &str) -> Option<Arc<Mutex<Bus>>> {
+		return self.bus_ids
+			.get(name)
+			.map(|i| self.buses[*i].control.clone());
+	}
+
+	pub fn set_bus_parent(&mut self, name: &str, parent: Option<&str>) -> Result<()> {
+
+		let idx = *self.bus_ids
+			.get(name)
+			.ok_or(format!("bus '{}' not found", name))?;
+
+		// walk up from the new parent to make sure we're not creating a loop
+		let mut cur = parent.map(|p| p.to_owned());
+
+		while let Some(p) = cur {
+
+			if p == name {
+				return Err(format!("bus '{}' can't be its own ancestor", name));
+			}
+
+			let pidx = *self.bus_ids
+				.get(&p)
+				.ok_or(format!("bus '{}' not found", p))?;
+
+			cur = self.buses[pidx].parent.clone();
+
+		}
+
+		self.buses[idx].parent = parent.map(|p| p.to_owned());
+		self.rebuild_buses();
+
+		return Ok(());
+
+	}
+
+	/// remove a bus, child buses go to its parent, sources routed into it go to master
+	pub fn remove_bus(&mut self, name: &str) {
+
+		let idx = match self.bus_ids.get(name) {
+			Some(i) => *i,
+			None => return,
+		};
+
+		let bus = self.buses.remove(idx);
+
+		for b in &mut self.buses {
+			if b.parent.as_deref() == Some(name) {
+				b.parent = bus.parent.clone();
+			}
+		}
+
+		self.rebuild_buses();
+
+	}
+
+	fn rebuild_buses(&mut self) {
+
+		self.bus_ids = self.buses
+			.iter()
+			.enumerate()
+			.map(|(i, b)| (b.name.clone(), i))
+			.collect();
+
+		let bus_ids = &self.bus_ids;
+
+		self.bus_parents = self.buses
+			.iter()
+			.map(|b| b.parent.as_ref().and_then(|p| bus_ids.get(p).cloned()))
+			.collect();
+
+		self.bus_acc = vec![Frame::default(); self.buses.len()];
+		self.route_buses();
+
+	}
+
+	// only redone when parents or sends change, not for every frame
+	fn route_buses(&mut self) {
+
+		let bus_ids = &self.bus_ids;
+
+		let mut outputs = self.bus_parents
+			.iter()
+			.map(|p| p.iter().map(|p| (*p, 1.0)).collect())
+			.collect::<Vec<Vec<(usize, f32)>>>();
+
+		for (i, b) in self.buses.iter().enumerate() {
+			if let Ok(mut bus) = b.control.lock() {
+				bus.dirty = false;
+				for (name, amount) in &bus.sends {
+					if let Some(t) = bus_ids.get(name) {
+						if !bus_reaches(&outputs, *t, i) {
+							outputs[i].push((*t, *amount));
+						}
+					}
+				}
+			}
+		}
+
+		let mut feeders = vec![0; outputs.len()];
+
+		for out in &outputs {
+			for (t, _) in out {
+				feeders[*t] += 1;
+			}
+		}
+
+		let mut ready = (0..outputs.len())
+			.filter(|i| feeders[*i] == 0)
+			.collect::<Vec<usize>>();
+
+		let mut order = Vec::with_capacity(outputs.len());
+
+		while let Some(i) = ready.pop() {
+			order.push(i);
+			for (t, _) in &outputs[i] {
+				feeders[*t] -= 1;
+				if feeders[*t] == 0 {
+					ready.push(*t);
+				}
+			}
+		}
+
+		self.bus_outputs = outputs;
+		self.bus_order = order;
+
+	}
+
 }
 
 impl Iterator for Mixer {
@@ -75,88 +618,249 @@ impl Iterator for Mixer {
 
 	fn next(&mut self) -> Option<Self::Item> {
 
-		if self.sources.is_empty() {
-			return None;
+		if self.sources.is_empty() && self.buses.is_empty() && self.effects.is_empty() {
+			// let the limiter flush its lookahead
+			return self.limiter
+				.as_mut()
+				.and_then(|l| l.leftover());
+		}
+
+		let rerouted = self.buses
+			.iter()
+			.any(|b| b.control.lock().map(|b| b.dirty).unwrap_or(false));
+
+		if rerouted {
+			self.route_buses();
 		}
 
 		let mut detached = hset![];
+		let mut master = Frame::new(0.0, 0.0);
+		let bus_ids = &self.bus_ids;
+		let bus_parents = &self.bus_parents;
+		let bus_acc = &mut self.bus_acc;
 
-		let sample = self.sources
-			.iter_mut()
-			.fold(Frame::new(0.0, 0.0), |frame_acc, (id, ctx)| {
+		for acc in bus_acc.iter_mut() {
+			*acc = Frame::default();
+		}
 
-				let ctrl = match ctx.control.lock() {
-					Ok(ctrl) => ctrl,
-					Err(_) => return frame_acc,
-				};
+		let dt = 1.0 / self.spec.sample_rate as f32;
+		let listener = &self.listener;
 
-				let mut src = match ctx.src.lock() {
-					Ok(src) => src,
-					Err(_) => return frame_acc,
-				};
+		for (id, ctx) in &mut self.sources {
 
-				if ctrl.detach {
-					detached.insert(*id);
-				}
+			let mut ctrl = match ctx.control.lock() {
+				Ok(ctrl) => ctrl,
+				Err(_) => continue,
+			};
 
-				if ctrl.paused {
+			let mut src = match ctx.src.lock() {
+				Ok(src) => src,
+				Err(_) => continue,
+			};
 
-					return frame_acc;
+			if ctrl.detach {
+				detached.insert(*id);
+			}
 
-				} else if let Some(mut frame) = src.next() {
+			if ctrl.paused {
+				continue;
+			}
 
-					for e in &ctrl.effects {
-						if let Ok(mut e) = e.lock() {
-							frame = e.process(frame);
+			ctrl.update_fade(dt);
+
+			if ctrl.seeked {
+				ctrl.seeked = false;
+				ctx.varispeed.reset();
+				ctx.stretch = None;
+				ctx.pending.clear();
+			}
+
+			match &ctrl.emitter {
+				Some(e) => match &mut ctx.spatial {
+					Some(s) => s.update(e, listener),
+					None => {
+						if let Ok(e) = e.lock() {
+							ctx.spatial = Some(SpatialState::new(&e, listener));
 						}
+					},
+				},
+				None => ctx.spatial = None,
+			}
+
+			let doppler = ctx.spatial
+				.as_ref()
+				.map(|s| s.pitch)
+				.unwrap_or(1.0);
+
+			let speed = ctrl.speed.max(0.0);
+
+			let (tempo, pitch) = if ctrl.preserve_pitch {
+				(speed, doppler)
+			} else {
+				(1.0, doppler * speed)
+			};
+
+			if tempo != 1.0 {
+				if ctx.stretch.is_none() {
+					ctx.stretch = Some(Stretch::new());
+				}
+			} else if let Some(s) = ctx.stretch.take() {
+				// back to normal speed, carry on from where the stretch is at
+				ctx.pending.extend(s.leftover());
+			}
+
+			let stretch = &mut ctx.stretch;
+			let pending = &mut ctx.pending;
+			let mut read = || pending.pop_front().or_else(|| src.next());
+
+			let mut pull = || {
+				return match stretch {
+					Some(s) => s.next(tempo, &mut read),
+					None => read(),
+				};
+			};
+
+			let next = if pitch != 1.0 || ctx.varispeed.active() {
+				ctx.varispeed.next(pitch, &mut pull)
+			} else {
+				pull()
+			};
+
+			let out = if let Some(mut frame) = next {
+
+				for e in &ctrl.effects {
+					if let Ok(mut e) = e.lock() {
+						frame = e.process(frame);
 					}
+				}
 
-					return frame_acc + frame * ctrl.pan * ctrl.volume;
+				frame * ctrl.pan * ctrl.volume
 
-				} else {
+			} else {
 
-					let mut has_leftover = false;
-					let mut leftover_acc = Frame::default();
+				let mut has_leftover = false;
+				let mut leftover_acc = Frame::default();
 
-					for i in 0..ctrl.effects.len() {
-						if let Ok(mut e) = ctrl.effects[i].lock() {
-							if let Some(mut leftover) = e.leftover() {
-								has_leftover = true;
-								for j in (i + 1)..ctrl.effects.len() {
-									if let Ok(mut e2) = ctrl.effects[j].lock() {
-										leftover = e2.process(leftover);
-									}
+				for i in 0..ctrl.effects.len() {
+					if let Ok(mut e) = ctrl.effects[i].lock() {
+						if let Some(mut leftover) = e.leftover() {
+							has_leftover = true;
+							for j in (i + 1)..ctrl.effects.len() {
+								if let Ok(mut e2) = ctrl.effects[j].lock() {
+									leftover = e2.process(leftover);
 								}
-								leftover_acc += leftover * ctrl.pan * ctrl.volume;
 							}
-						};
-					}
+							leftover_acc += leftover * ctrl.pan * ctrl.volume;
+						}
+					};
+				}
 
-					if has_leftover {
-						return frame_acc + leftover_acc;
-					} else {
-						if ctrl.looping {
-							if let Err(e) = src.seek_start() {
-								elog!("{}", e);
-							}
-						} else {
-							detached.insert(*id);
+				if !has_leftover {
+					if ctrl.looping {
+						if let Err(e) = src.seek_start() {
+							elog!("{}", e);
+						}
+						ctx.varispeed.reset();
+						if let Some(s) = &mut ctx.stretch {
+							s.reset();
 						}
+					} else {
+						detached.insert(*id);
 					}
+				}
+
+				leftover_acc
+
+			};
 
-					return frame_acc;
+			let out = match &mut ctx.spatial {
+				Some(s) => s.process(out),
+				None => out,
+			};
 
+			match ctrl.bus.as_ref().and_then(|b| bus_ids.get(b)) {
+				Some(i) => bus_acc[*i] += out,
+				None => master += out,
+			}
+
+			for (bus, amount) in &ctrl.sends {
+				if let Some(i) = bus_ids.get(bus) {
+					bus_acc[*i] += out * *amount;
 				}
+			}
+
+		}
+
+		for i in &self.bus_order {
+
+			let out = match self.buses[*i].control.lock() {
+				Ok(bus) if !bus.mute => {
+
+					let mut frame = bus_acc[*i];
+
+					for e in &bus.effects {
+						if let Ok(mut e) = e.lock() {
+							frame = e.process(frame);
+						}
+					}
+
+					frame * bus.pan * bus.volume
 
-			});
+				},
+				_ => Frame::default(),
+			};
+
+			if bus_parents[*i].is_none() {
+				master += out;
+			}
+
+			for (t, amount) in &self.bus_outputs[*i] {
+				bus_acc[*t] += out * *amount;
+			}
+
+		}
 
 		self.sources.retain(|id, ctx| {
 			return !detached.contains(id);
 		});
 
-		return Some(sample);
+		for e in &self.effects {
+			if let Ok(mut e) = e.lock() {
+				master = e.process(master);
+			}
+		}
+
+		if let Some(limiter) = &mut self.limiter {
+			return Some(limiter.process(master));
+		}
+
+		return Some(master);
 
 	}
 
 }
 
+// if bus a feeds into bus b through parents and sends
+fn bus_reaches(outputs: &[Vec<(usize, f32)>], a: usize, b: usize) -> bool {
+
+	let mut visited = vec![false; outputs.len()];
+	let mut stack = vec![a];
+
+	while let Some(i) = stack.pop() {
+
+		if i == b {
+			return true;
+		}
+
+		if visited[i] {
+			continue;
+		}
+
+		visited[i] = true;
+		stack.extend(outputs[i].iter().map(|(t, _)| *t));
+
+	}
+
+	return false;
+
+}
@@ -5,8 +5,52 @@
 //! This module provides 2 types of high-level types:
 //!  - [`Sound`](struct.Sound.html), buffered audio mainly for sound effects
 //!  - [`Track`](struct.Track.html), streamed audio mainly for music
-
-// TODO: sample rate conversion
+//!
+//! A [`SoundBank`](struct.SoundBank.html) groups variations of a [`Sound`](struct.Sound.html) with random pitch and volume, voice limiting and cooldown, for sounds triggered a lot like footsteps and gunshots
+//!
+//! Files that don't match the device sample rate are converted with a [`Resampler`](struct.Resampler.html), quality can be changed with [`Audio::set_resample_quality`](struct.Audio.html#method.set_resample_quality)
+//!
+//! Both can be routed into named [`Bus`](struct.Bus.html)es, which have their own volume, pan, mute, effects and sends, and can nest
+//! ```ignore
+//! ctx.add_bus("music", None)?;
+//! ctx.add_bus("sfx", None)?;
+//! ctx.add_bus("reverb", None)?.lock().unwrap().effects.push(Arc::new(Mutex::new(Reverb::new(0.8))));
+//!
+//! track.set_bus(Some("music"));
+//! sound.builder().bus("sfx").send("reverb", 0.3).play()?;
+//!
+//! ctx.bus("music").unwrap().lock().unwrap().volume = 0.5;
+//! ctx.bus("sfx").unwrap().lock().unwrap().set_send("reverb", 0.2);
+//! ```
+//!
+//! Sources can be attached to an [`Emitter`](struct.Emitter.html), they're then panned, attenuated, doppler shifted and muffled based on where they are relative to the [`Listener`](struct.Listener.html) (usually the camera)
+//! ```ignore
+//! let engine = Arc::new(Mutex::new(Emitter::new(car.pos)));
+//!
+//! engine_track.set_emitter(Some(engine.clone()));
+//! honk.builder().emitter(engine.clone()).play()?;
+//!
+//! // every frame
+//! ctx.set_listener(Listener::new(cam.pos, cam.front, vec3!(0, 1, 0)));
+//! engine.lock().unwrap().move_to(car.pos, dt);
+//! ```
+//!
+//! Where the mix goes is picked by [`Conf::audio_backend`](../conf/struct.Conf.html#structfield.audio_backend), by default it plays on the sound card and falls back to a silent backend if there's none. The silent backends still run the mixer in real time, `AudioBackend::File` also writes it to a wav file, useful for CI and testing
+//! ```ignore
+//! launcher()
+//! 	.audio_backend(conf::AudioBackend::File("out.wav".into()))
+//! 	.run::<Game>()
+//! ```
+//!
+//! [`Audio::record`](struct.Audio.html#method.record) captures from the microphone into a [`Recording`](struct.Recording.html), which can be read from or played back live as a `Source`. `InputDevice::File` plays a file in real time instead, for testing without a device
+//! ```ignore
+//! let mut mic = ctx.record_from(InputDevice::File("voice.wav".into()), Duration::from_secs(5))?;
+//!
+//! ctx.play(Arc::new(Mutex::new(mic.source())))?;
+//!
+//! // every frame
+//! let loud = mic.read().iter().any(|f| f.left.abs() > 0.3);
+//! ```
 
 use std::io::Cursor;
 use std::io::Read;
@@ -22,20 +66,30 @@ use math::*;
 mod utils;
 
 import!(mixer);
+pub use mixer::Control;
+pub use mixer::Bus;
 import!(vorbis);
 import!(wav);
 import!(mp3);
 import!(decoder);
-import!(buffer);
-import!(resample);
+import!(stretch);
 export!(source);
+export!(resample);
+export!(buffer);
+export!(render);
 export!(types);
 export!(effect);
+export!(dynamics);
 export!(spatial);
+export!(analysis);
 #[cfg(not(web))]
 export!(track);
 #[cfg(not(web))]
 export!(sound);
+#[cfg(not(web))]
+export!(bank);
+#[cfg(not(web))]
+export!(capture);
 
 #[cfg(all(not(ios), not(web)))]
 export!(native);
@@ -48,6 +102,7 @@ use crate::*;
 
 pub mod music;
 pub mod synth;
+pub mod sfx;
 
 pub const SPEC: Spec = Spec {
 	sample_rate: 44100,
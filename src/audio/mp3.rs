@@ -4,16 +4,109 @@ use super::*;
 
 // TODO: puremp3 cannot decode mp3 with id3 tags
 
+// frames decoded before a seek target, to fill the bit reservoir
+const SEEK_PREROLL: usize = 2;
+
+const BITRATES_V1: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+const BITRATES_V2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+// (byte offset, first sample) of each layer 3 frame, found by walking the frame headers
+fn frame_index(data: &[u8]) -> Vec<(u64, u64)> {
+
+	let mut frames = vec![];
+	let mut i = 0;
+	let mut samples = 0;
+
+	// skip id3v2 tag
+	if data.len() >= 10 && &data[0..3] == b"ID3" {
+		let size = data[6..10]
+			.iter()
+			.fold(0, |acc, b| (acc << 7) | (*b & 0x7f) as usize);
+		let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+		i = 10 + size + footer;
+	}
+
+	while i + 4 <= data.len() {
+
+		let h = &data[i..i + 4];
+
+		// sync word, then version (0b01 is reserved) and layer 3
+		if h[0] != 0xff || h[1] & 0xe0 != 0xe0 || (h[1] >> 3) & 0b11 == 0b01 || (h[1] >> 1) & 0b11 != 0b01 {
+			i += 1;
+			continue;
+		}
+
+		let mpeg1 = (h[1] >> 3) & 0b11 == 0b11;
+		let bitrate_idx = (h[2] >> 4) as usize;
+		let sample_rate_idx = ((h[2] >> 2) & 0b11) as usize;
+		let padding = ((h[2] >> 1) & 1) as usize;
+
+		// free format / bad bitrate, bad sample rate
+		if bitrate_idx == 0 || bitrate_idx == 15 || sample_rate_idx == 3 {
+			i += 1;
+			continue;
+		}
+
+		let sample_rate = match (h[1] >> 3) & 0b11 {
+			0b11 => SAMPLE_RATES[sample_rate_idx],
+			0b10 => SAMPLE_RATES[sample_rate_idx] / 2,
+			_ => SAMPLE_RATES[sample_rate_idx] / 4,
+		};
+
+		let (bitrate, coef, count) = if mpeg1 {
+			(BITRATES_V1[bitrate_idx], 144, 1152)
+		} else {
+			(BITRATES_V2[bitrate_idx], 72, 576)
+		};
+
+		let len = (coef * bitrate * 1000 / sample_rate) as usize + padding;
+
+		frames.push((i as u64, samples));
+		samples += count;
+		i += len;
+
+	}
+
+	frames.push((data.len() as u64, samples));
+
+	return frames;
+
+}
+
 pub struct Mp3Playback<R: Read + Seek> {
 	decoder: puremp3::Mp3Decoder<R>,
 	cur_frame: puremp3::Frame,
 	cur_frame_offset: usize,
 	sample_rate: u32,
+	// frame index, the last one marks the end
+	frames: Vec<(u64, u64)>,
+	// frames played
+	pos: u64,
 }
 
 impl<R: Read + Seek> Mp3Playback<R> {
 
-	pub fn new(reader: R) -> Result<Self> {
+	pub fn new(mut reader: R) -> Result<Self> {
+
+		let start = reader
+			.seek(SeekFrom::Current(0))
+			.map_err(|_| format!("failed to seek"))?;
+
+		let mut data = vec![];
+
+		reader
+			.read_to_end(&mut data)
+			.map_err(|_| format!("failed to read mp3"))?;
+
+		reader
+			.seek(SeekFrom::Start(start))
+			.map_err(|_| format!("failed to seek"))?;
+
+		let frames = frame_index(&data)
+			.into_iter()
+			.map(|(offset, samples)| (offset + start, samples))
+			.collect();
 
 		let mut decoder = puremp3::Mp3Decoder::new(reader);
 		let cur_frame = decoder
@@ -27,6 +120,8 @@ impl<R: Read + Seek> Mp3Playback<R> {
 			cur_frame,
 			cur_frame_offset: 0,
 			sample_rate,
+			frames,
+			pos: 0,
 		});
 
 	}
@@ -48,11 +143,65 @@ impl<R: Read + Seek> Source for Mp3Playback<R> {
 			.map_err(|_| format!("failed to seek mp3"))?;
 
 		self.cur_frame_offset = 0;
+		self.pos = 0;
 
 		return Ok(());
 
 	}
 
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+
+		let target = (pos.as_secs_f64() * self.sample_rate as f64) as u64;
+
+		let i = match self.frames.binary_search_by_key(&target, |(_, s)| *s) {
+			Ok(i) => i,
+			Err(i) => i.saturating_sub(1),
+		};
+
+		let i = i.saturating_sub(SEEK_PREROLL);
+		let (offset, start) = self.frames.get(i).cloned().unwrap_or((0, 0));
+
+		self.decoder
+			.get_mut()
+			.seek(SeekFrom::Start(offset))
+			.map_err(|_| format!("failed to seek mp3"))?;
+
+		self.pos = start;
+
+		loop {
+
+			match self.decoder.next_frame() {
+				Ok(frame) => self.cur_frame = frame,
+				Err(_) => {
+					// past the end
+					self.cur_frame_offset = self.cur_frame.samples[0].len();
+					return Ok(());
+				},
+			}
+
+			let len = self.cur_frame.samples[0].len() as u64;
+
+			if self.pos + len > target {
+				self.cur_frame_offset = (target - self.pos) as usize;
+				self.pos = target;
+				return Ok(());
+			}
+
+			self.pos += len;
+
+		}
+
+	}
+
+	fn position(&self) -> Option<Duration> {
+		return Some(Duration::from_secs_f64(self.pos as f64 / self.sample_rate as f64));
+	}
+
+	fn duration(&self) -> Option<Duration> {
+		let len = self.frames.last().map(|(_, s)| *s).unwrap_or(0);
+		return Some(Duration::from_secs_f64(len as f64 / self.sample_rate as f64));
+	}
+
 }
 
 impl<R: Read + Seek> Iterator for Mp3Playback<R> {
@@ -73,6 +222,7 @@ impl<R: Read + Seek> Iterator for Mp3Playback<R> {
 		let right = self.cur_frame.samples[1][self.cur_frame_offset];
 
 		self.cur_frame_offset += 1;
+		self.pos += 1;
 
 		return Some(Frame::new(left, right));
 
@@ -1,51 +1,226 @@
 // wengwengweng
 
 use std::thread;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 use cpal::traits::*;
 use super::*;
+use conf::AudioBackend;
+
+// how often the headless backends wake up to pull from the mixer
+const TICK: Duration = Duration::from_millis(10);
+// how much audio a recording keeps by default
+const RECORD_BUFFER: Duration = Duration::from_secs(10);
 
 /// The Audio Context. See [mod-level doc](index.html) for usage.
 pub struct Audio {
 	mixer: Arc<Mutex<Mixer>>,
+	resample_quality: ResampleQuality,
+	backend: AudioBackend,
+	stop: Arc<AtomicBool>,
+	thread: Option<thread::JoinHandle<()>>,
+	input: Option<Input>,
 }
 
 impl Audio {
 
-	pub(crate) fn new(_: &conf::Conf) -> Result<Self> {
+	pub(crate) fn new(conf: &conf::Conf) -> Result<Self> {
 
-		let host = cpal::default_host();
+		let mixer = Arc::new(Mutex::new(Mixer::new(SPEC)));
+		let stop = Arc::new(AtomicBool::new(false));
+		let mut thread = None;
 
-		let device = host
-			.default_output_device()
-			.ok_or(format!("failed to get default output device"))?;
+		let backend = match &conf.audio_backend {
+			AudioBackend::Auto => {
+				match start_cpal(&mixer) {
+					Ok(_) => AudioBackend::Cpal,
+					Err(err) => {
+						elog!("{}, falling back to null audio backend", err);
+						thread = Some(start_headless(&mixer, None, &stop)?);
+						AudioBackend::Null
+					},
+				}
+			},
+			AudioBackend::Cpal => {
+				start_cpal(&mixer)?;
+				AudioBackend::Cpal
+			},
+			AudioBackend::Null => {
+				thread = Some(start_headless(&mixer, None, &stop)?);
+				AudioBackend::Null
+			},
+			AudioBackend::File(path) => {
+				thread = Some(start_headless(&mixer, Some(path.as_path()), &stop)?);
+				AudioBackend::File(path.clone())
+			},
+		};
 
-		let format = device
-			.default_output_format()
-			.map_err(|_| format!("failed to get default audio output format"))?;
+		return Ok(Self {
+			mixer,
+			resample_quality: ResampleQuality::default(),
+			backend,
+			stop,
+			thread,
+			input: None,
+		});
+
+	}
+
+	/// the backend in use, `Auto` is resolved to what it picked
+	pub fn backend(&self) -> &AudioBackend {
+		return &self.backend;
+	}
+
+	pub(super) fn mixer(&self) -> &Arc<Mutex<Mixer>> {
+		return &self.mixer;
+	}
+
+	/// enable a brickwall limiter on the final mix so it never goes past 0 dBFS
+	pub fn set_limiter(&mut self, b: bool) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.set_limiter(b);
+		}
+	}
+
+	/// check if the master limiter is enabled
+	pub fn limiter(&self) -> bool {
+		return self.mixer
+			.lock()
+			.map(|m| m.limiter())
+			.unwrap_or(false);
+	}
+
+	/// add an effect on the final mix, before the limiter
+	pub fn add_master_effect(&mut self, e: Arc<Mutex<dyn Effect + Send>>) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.add_effect(e);
+		}
+	}
+
+	/// add a named bus, routed into `parent` or master if `None`
+	pub fn add_bus(&mut self, name: &str, parent: Option<&str>) -> Result<Arc<Mutex<Bus>>> {
+		return self.mixer
+			.lock()
+			.map_err(|_| format!("failed to get mixer"))?
+			.add_bus(name, parent);
+	}
+
+	/// get a bus by name
+	pub fn bus(&self, name: &str) -> Option<Arc<Mutex<Bus>>> {
+		return self.mixer
+			.lock()
+			.ok()
+			.and_then(|m| m.bus(name));
+	}
+
+	/// re-route a bus into another bus, fails if it'll create a loop
+	pub fn set_bus_parent(&mut self, name: &str, parent: Option<&str>) -> Result<()> {
+		return self.mixer
+			.lock()
+			.map_err(|_| format!("failed to get mixer"))?
+			.set_bus_parent(name, parent);
+	}
+
+	/// remove a bus, child buses go to its parent, sources routed into it go to master
+	pub fn remove_bus(&mut self, name: &str) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.remove_bus(name);
+		}
+	}
+
+	/// set where [`Emitter`](struct.Emitter.html)s are heard from, update it every frame with the camera
+	pub fn set_listener(&mut self, l: Listener) {
+		if let Ok(mut mixer) = self.mixer.lock() {
+			mixer.set_listener(l);
+		}
+	}
+
+	pub fn listener(&self) -> Listener {
+		return self.mixer
+			.lock()
+			.map(|m| m.listener())
+			.unwrap_or_default();
+	}
+
+	/// quality of sample rate conversion for [`Sound`](struct.Sound.html)s and [`Track`](struct.Track.html)s loaded after this
+	pub fn set_resample_quality(&mut self, q: ResampleQuality) {
+		self.resample_quality = q;
+	}
+
+	pub fn resample_quality(&self) -> ResampleQuality {
+		return self.resample_quality;
+	}
+
+	/// start capturing from the default input device, keeping the latest 10 seconds
+	pub fn record(&mut self) -> Result<Recording> {
+		return self.record_from(InputDevice::Default, RECORD_BUFFER);
+	}
 
-		let format = cpal::Format {
-			channels: SPEC.channel_count,
-			sample_rate: cpal::SampleRate(SPEC.sample_rate),
-			data_type: format.data_type,
+	/// start capturing from an input device, keeping the latest `buffer` of audio
+	pub fn record_from(&mut self, device: InputDevice, buffer: Duration) -> Result<Recording> {
+		return match device {
+			InputDevice::Default => {
+				if self.input.is_none() {
+					self.input = Some(Input::new()?);
+				}
+				match &self.input {
+					Some(input) => input.record(buffer),
+					None => Err(format!("failed to start audio input")),
+				}
+			},
+			InputDevice::File(path) => Recording::from_file(path, buffer),
 		};
+	}
 
-		let event_loop = host.event_loop();
-		let stream_id = event_loop
-			.build_output_stream(&device, &format)
-			.map_err(|_| format!("failed to build audio output stream"))?;
+	pub fn play<S: Source + Send + 'static>(&mut self, src: Arc<Mutex<S>>) -> Result<Arc<Mutex<Control>>> {
+		return Ok(self.mixer
+			.lock()
+			.map_err(|_| format!("failed to get mixer"))?
+			.add(src));
+	}
 
-		event_loop
-			.play_stream(stream_id)
-			.map_err(|_| format!("failed to start audio stream"))?;
+}
 
-		let mixer = Arc::new(Mutex::new(Mixer::new(SPEC)));
-		let t_mixer = Arc::clone(&mixer);
+impl Drop for Audio {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+		if let Some(thread) = self.thread.take() {
+			// let the file sink finish writing
+			if thread.join().is_err() {
+				elog!("audio thread panicked");
+			}
+		}
+	}
+}
+
+struct InputSink {
+	ring: Arc<Mutex<Ring>>,
+	channels: usize,
+}
+
+// one event loop shared by every input stream, since a cpal event loop never returns
+struct Input {
+	event_loop: Arc<cpal::EventLoop>,
+	sinks: Arc<Mutex<HashMap<cpal::StreamId, InputSink>>>,
+}
+
+impl Input {
+
+	fn new() -> Result<Self> {
+
+		let event_loop = Arc::new(cpal::default_host().event_loop());
+		let sinks: Arc<Mutex<HashMap<cpal::StreamId, InputSink>>> = Arc::new(Mutex::new(HashMap::new()));
+
+		let t_event_loop = Arc::clone(&event_loop);
+		let t_sinks = Arc::clone(&sinks);
 
 		thread::Builder::new()
-			.name(format!("dirty_audio"))
+			.name(format!("dirty_audio_input"))
 			.spawn(move || {
 
-			event_loop.run(move |id, data| {
+			t_event_loop.run(move |id, data| {
 
 				let data = match data {
 					Ok(data) => data,
@@ -55,64 +230,260 @@ impl Audio {
 					}
 				};
 
-				let mut mixer = match t_mixer.lock() {
-					Ok(mixer) => mixer,
-					Err(err) => {
-						elog!("failed to get mixer");
-						return;
-					}
+				let sinks = match t_sinks.lock() {
+					Ok(sinks) => sinks,
+					Err(_) => return,
 				};
 
-				match data {
+				let sink = match sinks.get(&id) {
+					Some(sink) => sink,
+					None => return,
+				};
 
-					cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::U16(mut output) } => {
-						for d in output.chunks_mut(2) {
-							let frame = mixer.next().unwrap_or_default();
-							d[0] = utils::f32_to_u16(frame.left);
-							d[1] = utils::f32_to_u16(frame.right);
-						}
-					},
+				let mut ring = match sink.ring.lock() {
+					Ok(ring) => ring,
+					Err(_) => return,
+				};
 
-					cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::I16(mut output) } => {
-						for d in output.chunks_mut(2) {
-							let frame = mixer.next().unwrap_or_default();
-							d[0] = utils::f32_to_i16(frame.left);
-							d[1] = utils::f32_to_i16(frame.right);
-						}
+				let samples = match data {
+					cpal::StreamData::Input { buffer: cpal::UnknownTypeInputBuffer::U16(input) } => {
+						input.iter().map(|s| utils::u16_to_f32(*s)).collect::<Vec<f32>>()
 					},
-
-					cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::F32(mut output) } => {
-						for d in output.chunks_mut(2) {
-							let frame = mixer.next().unwrap_or_default();
-							d[0] = frame.left;
-							d[1] = frame.right;
-						}
+					cpal::StreamData::Input { buffer: cpal::UnknownTypeInputBuffer::I16(input) } => {
+						input.iter().map(|s| utils::i16_to_f32(*s)).collect::<Vec<f32>>()
 					},
+					cpal::StreamData::Input { buffer: cpal::UnknownTypeInputBuffer::F32(input) } => {
+						input.to_vec()
+					},
+					_ => return,
+				};
 
-					_ => (),
-
+				// mono goes to both sides, anything past 2 channels is dropped
+				for d in samples.chunks(sink.channels) {
+					if d.len() >= 2 {
+						ring.push(Frame::new(d[0], d[1]));
+					} else {
+						ring.push(Frame::new(d[0], d[0]));
+					}
 				}
 
 			});
 
-		}).map_err(|_| format!("failed to spawn audio thread"))?;
+		}).map_err(|_| format!("failed to spawn audio input thread"))?;
 
 		return Ok(Self {
-			mixer,
+			event_loop: event_loop,
+			sinks: sinks,
 		});
 
 	}
 
-	pub(super) fn mixer(&self) -> &Arc<Mutex<Mixer>> {
-		return &self.mixer;
-	}
+	fn record(&self, buffer: Duration) -> Result<Recording> {
 
-	pub fn play<S: Source + Send + 'static>(&mut self, src: Arc<Mutex<S>>) -> Result<Arc<Mutex<Control>>> {
-		return Ok(self.mixer
+		let device = cpal::default_host()
+			.default_input_device()
+			.ok_or(format!("failed to get default input device"))?;
+
+		let format = device
+			.default_input_format()
+			.map_err(|_| format!("failed to get default audio input format"))?;
+
+		let stream_id = self.event_loop
+			.build_input_stream(&device, &format)
+			.map_err(|_| format!("failed to build audio input stream"))?;
+
+		let sample_rate = format.sample_rate.0;
+		let ring = Arc::new(Mutex::new(Ring::new((buffer.as_secs_f64() * sample_rate as f64) as usize)));
+
+		self.sinks
 			.lock()
-			.map_err(|_| format!("failed to get mixer"))?
-			.add(src));
+			.map_err(|_| format!("failed to start audio input"))?
+			.insert(stream_id.clone(), InputSink {
+				ring: Arc::clone(&ring),
+				channels: (format.channels as usize).max(1),
+			});
+
+		if self.event_loop.play_stream(stream_id.clone()).is_err() {
+			if let Ok(mut sinks) = self.sinks.lock() {
+				sinks.remove(&stream_id);
+			}
+			self.event_loop.destroy_stream(stream_id);
+			return Err(format!("failed to start audio input stream"));
+		}
+
+		let event_loop = Arc::clone(&self.event_loop);
+		let sinks = Arc::clone(&self.sinks);
+
+		return Ok(Recording::new(ring, sample_rate, Closer::new(move || {
+			if let Ok(mut sinks) = sinks.lock() {
+				sinks.remove(&stream_id);
+			}
+			event_loop.destroy_stream(stream_id.clone());
+		})));
+
 	}
 
 }
 
+fn start_cpal(mixer: &Arc<Mutex<Mixer>>) -> Result<()> {
+
+	let host = cpal::default_host();
+
+	let device = host
+		.default_output_device()
+		.ok_or(format!("failed to get default output device"))?;
+
+	let format = device
+		.default_output_format()
+		.map_err(|_| format!("failed to get default audio output format"))?;
+
+	let format = cpal::Format {
+		channels: SPEC.channel_count,
+		sample_rate: cpal::SampleRate(SPEC.sample_rate),
+		data_type: format.data_type,
+	};
+
+	let event_loop = host.event_loop();
+	let stream_id = event_loop
+		.build_output_stream(&device, &format)
+		.map_err(|_| format!("failed to build audio output stream"))?;
+
+	event_loop
+		.play_stream(stream_id)
+		.map_err(|_| format!("failed to start audio stream"))?;
+
+	let t_mixer = Arc::clone(mixer);
+
+	thread::Builder::new()
+		.name(format!("dirty_audio"))
+		.spawn(move || {
+
+		event_loop.run(move |id, data| {
+
+			let data = match data {
+				Ok(data) => data,
+				Err(err) => {
+					elog!("an error occurred on stream {:?}: {}", id, err);
+					return;
+				}
+			};
+
+			let mut mixer = match t_mixer.lock() {
+				Ok(mixer) => mixer,
+				Err(err) => {
+					elog!("failed to get mixer");
+					return;
+				}
+			};
+
+			match data {
+
+				cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::U16(mut output) } => {
+					for d in output.chunks_mut(2) {
+						let frame = mixer.next().unwrap_or_default();
+						d[0] = utils::f32_to_u16(frame.left);
+						d[1] = utils::f32_to_u16(frame.right);
+					}
+				},
+
+				cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::I16(mut output) } => {
+					for d in output.chunks_mut(2) {
+						let frame = mixer.next().unwrap_or_default();
+						d[0] = utils::f32_to_i16(frame.left);
+						d[1] = utils::f32_to_i16(frame.right);
+					}
+				},
+
+				cpal::StreamData::Output { buffer: cpal::UnknownTypeOutputBuffer::F32(mut output) } => {
+					for d in output.chunks_mut(2) {
+						let frame = mixer.next().unwrap_or_default();
+						d[0] = frame.left;
+						d[1] = frame.right;
+					}
+				},
+
+				_ => (),
+
+			}
+
+		});
+
+	}).map_err(|_| format!("failed to spawn audio thread"))?;
+
+	return Ok(());
+
+}
+
+// pulls from the mixer at real time rate on a timer instead of a device callback, optionally writing it to a wav file
+fn start_headless(mixer: &Arc<Mutex<Mixer>>, path: Option<&Path>, stop: &Arc<AtomicBool>) -> Result<thread::JoinHandle<()>> {
+
+	let mut writer = match path {
+		Some(path) => {
+			let spec = hound::WavSpec {
+				channels: SPEC.channel_count,
+				sample_rate: SPEC.sample_rate,
+				bits_per_sample: 16,
+				sample_format: hound::SampleFormat::Int,
+			};
+			Some(hound::WavWriter::create(path, spec)
+				.map_err(|_| format!("failed to create {}", path.display()))?)
+		},
+		None => None,
+	};
+
+	let t_mixer = Arc::clone(mixer);
+	let t_stop = Arc::clone(stop);
+
+	return thread::Builder::new()
+		.name(format!("dirty_audio"))
+		.spawn(move || {
+
+		let start = Instant::now();
+		let mut played = 0;
+
+		while !t_stop.load(Ordering::Relaxed) {
+
+			let due = (start.elapsed().as_secs_f64() * SPEC.sample_rate as f64) as u64;
+
+			if let Ok(mut mixer) = t_mixer.lock() {
+
+				while played < due {
+
+					let frame = mixer.next().unwrap_or_default();
+
+					if let Some(w) = &mut writer {
+						let res = w.write_sample(utils::f32_to_i16(frame.left))
+							.and_then(|_| w.write_sample(utils::f32_to_i16(frame.right)));
+						if res.is_err() {
+							elog!("failed to write audio file");
+							writer = None;
+						}
+					}
+
+					played += 1;
+
+				}
+
+			}
+
+			// keep the header valid in case the process exits without dropping us
+			if let Some(w) = &mut writer {
+				if w.flush().is_err() {
+					elog!("failed to write audio file");
+					writer = None;
+				}
+			}
+
+			thread::sleep(TICK);
+
+		}
+
+		if let Some(w) = writer {
+			if w.finalize().is_err() {
+				elog!("failed to write audio file");
+			}
+		}
+
+	}).map_err(|_| format!("failed to spawn audio thread"));
+
+}
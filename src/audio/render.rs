@@ -0,0 +1,124 @@
+// wengwengweng
+
+use super::*;
+
+/// Sample Format of a Written Wav File
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WavFormat {
+	Int16,
+	Int24,
+	Float32,
+}
+
+impl Default for WavFormat {
+	fn default() -> Self {
+		return WavFormat::Int16;
+	}
+}
+
+/// Offline Mixer
+///
+/// renders sources, buses and effects as fast as possible without an audio device
+/// ```ignore
+/// let mut r = Renderer::new();
+/// let synth = Arc::new(Mutex::new(BasicSynth::new()));
+///
+/// r.play(synth.clone());
+/// synth.lock().unwrap().play_oneshot(Voice::builder(Note::new(60)).build());
+/// r.render(Duration::from_secs(2));
+/// r.finish().save_wav("note.wav", WavFormat::Int24)?;
+/// ```
+pub struct Renderer {
+	mixer: Mixer,
+	frames: Vec<Frame>,
+}
+
+impl Renderer {
+
+	pub fn new() -> Self {
+		return Self {
+			mixer: Mixer::new(SPEC),
+			frames: vec![],
+		};
+	}
+
+	pub fn play<S: Source + Send + 'static>(&mut self, src: Arc<Mutex<S>>) -> Arc<Mutex<Control>> {
+		return self.mixer.add(src);
+	}
+
+	pub fn add_bus(&mut self, name: &str, parent: Option<&str>) -> Result<Arc<Mutex<Bus>>> {
+		return self.mixer.add_bus(name, parent);
+	}
+
+	pub fn bus(&self, name: &str) -> Option<Arc<Mutex<Bus>>> {
+		return self.mixer.bus(name);
+	}
+
+	pub fn set_limiter(&mut self, b: bool) {
+		self.mixer.set_limiter(b);
+	}
+
+	/// add an effect on the final mix, before the limiter
+	pub fn add_master_effect(&mut self, e: Arc<Mutex<dyn Effect + Send>>) {
+		self.mixer.add_effect(e);
+	}
+
+	pub fn set_listener(&mut self, l: Listener) {
+		self.mixer.set_listener(l);
+	}
+
+	/// run the mix for a duration, output is appended
+	pub fn render(&mut self, d: Duration) {
+
+		let count = (d.as_secs_f64() * SPEC.sample_rate as f64).round() as usize;
+
+		self.frames.reserve(count);
+
+		for _ in 0..count {
+			let f = self.mixer.next().unwrap_or_default();
+			self.frames.push(f);
+		}
+
+	}
+
+	/// run until every source has finished, or until max duration, bus effect tails are cut off
+	pub fn render_to_end(&mut self, max: Duration) {
+
+		let count = (max.as_secs_f64() * SPEC.sample_rate as f64).round() as usize;
+
+		for _ in 0..count {
+
+			if self.mixer.source_count() == 0 {
+				break;
+			}
+
+			let f = self.mixer.next().unwrap_or_default();
+
+			self.frames.push(f);
+
+		}
+
+	}
+
+	/// length of the output so far
+	pub fn position(&self) -> Duration {
+		return Duration::from_secs_f64(self.frames.len() as f64 / SPEC.sample_rate as f64);
+	}
+
+	pub fn frames(&self) -> &[Frame] {
+		return &self.frames;
+	}
+
+	/// take the output
+	pub fn finish(self) -> AudioBuffer {
+		return AudioBuffer::from_frames(self.frames, SPEC.sample_rate);
+	}
+
+}
+
+impl Default for Renderer {
+	fn default() -> Self {
+		return Self::new();
+	}
+}
+
@@ -1,87 +1,434 @@
 // wengwengweng
 
+use std::f64::consts::PI;
+use std::collections::VecDeque;
+
 use super::*;
 
-pub struct Resampler {
-	src: Arc<Mutex<dyn Source + Send>>,
-	target: Spec,
-	prev_frame: Option<Frame>,
-	next_frame: Option<Frame>,
-	pos: f32,
-	frame_pos: usize,
+// kernel table resolution between 2 input samples
+const PHASES: usize = 256;
+
+/// Quality / Speed Tradeoff of a [`Resampler`](struct.Resampler.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResampleQuality {
+	/// 8 taps
+	Low,
+	/// 32 taps
+	Medium,
+	/// 64 taps
+	High,
+}
+
+impl ResampleQuality {
+
+	// (taps on each side, kaiser beta, passband)
+	fn params(&self) -> (usize, f64, f64) {
+		return match self {
+			ResampleQuality::Low => (4, 5.0, 0.85),
+			ResampleQuality::Medium => (16, 7.5, 0.92),
+			ResampleQuality::High => (32, 9.5, 0.95),
+		};
+	}
+
 }
 
-impl Resampler {
-	pub fn new(src: Arc<Mutex<dyn Source + Send>>, target: Spec) -> Result<Self> {
-		return Ok(Self {
+impl Default for ResampleQuality {
+	fn default() -> Self {
+		return ResampleQuality::Medium;
+	}
+}
+
+// zeroth order modified bessel function of the first kind
+fn bessel_i0(x: f64) -> f64 {
+
+	let mut sum = 1.0;
+	let mut term = 1.0;
+	let half = x / 2.0;
+
+	for k in 1..32 {
+		term *= half / k as f64;
+		sum += term * term;
+	}
+
+	return sum;
+
+}
+
+fn sinc(x: f64) -> f64 {
+	if x.abs() < 1e-9 {
+		return 1.0;
+	}
+	return (PI * x).sin() / (PI * x);
+}
+
+// half of a symmetric kaiser windowed sinc lowpass, sampled at PHASES per input sample
+#[derive(Clone, Debug)]
+struct Kernel {
+	half: usize,
+	table: Vec<f32>,
+}
+
+impl Kernel {
+
+	fn new(quality: ResampleQuality, ratio: f64) -> Self {
+
+		let (half, beta, passband) = quality.params();
+
+		// cutoff relative to input nyquist, lower it when downsampling to avoid aliasing
+		let cutoff = passband * ratio.min(1.0);
+		// widen the kernel when downsampling so the filter keeps its quality
+		let half = (half as f64 / ratio.min(1.0)).ceil() as usize;
+		let len = half * PHASES + 1;
+		let norm = bessel_i0(beta);
+
+		let table = (0..len)
+			.map(|i| {
+				let x = i as f64 / PHASES as f64;
+				let r = x / half as f64;
+				let w = bessel_i0(beta * (1.0 - r * r).max(0.0).sqrt()) / norm;
+				return (cutoff * sinc(cutoff * x) * w) as f32;
+			})
+			.collect();
+
+		return Self {
+			half: half,
+			table: table,
+		};
+
+	}
+
+	fn at(&self, x: f64) -> f32 {
+
+		let p = x.abs() * PHASES as f64;
+		let i = p as usize;
+
+		if i + 1 >= self.table.len() {
+			return 0.0;
+		}
+
+		let t = (p - i as f64) as f32;
+
+		return self.table[i] + (self.table[i + 1] - self.table[i]) * t;
+
+	}
+
+}
+
+/// Streaming Sample Rate Converter
+///
+/// converts any [`Source`](trait.Source.html) to a target sample rate with a windowed sinc filter, passes through untouched if the rates already match
+pub struct Resampler<S: Source> {
+	src: S,
+	target: u32,
+	quality: ResampleQuality,
+	kernel: Kernel,
+	ratio: f64,
+	// input frames around the current position, buf[0] is input frame base
+	buf: VecDeque<Frame>,
+	base: i64,
+	// current output position in input frames
+	pos: f64,
+	// number of input frames, known after the source ends
+	len: Option<i64>,
+	read: i64,
+}
+
+impl<S: Source> Resampler<S> {
+
+	pub fn new(src: S, target: u32, quality: ResampleQuality) -> Self {
+
+		let ratio = target as f64 / src.sample_rate().max(1) as f64;
+		let kernel = Kernel::new(quality, ratio);
+
+		let mut r = Self {
 			src: src,
 			target: target,
-			prev_frame: None,
-			next_frame: None,
+			quality: quality,
+			kernel: kernel,
+			ratio: ratio,
+			buf: VecDeque::new(),
+			base: 0,
 			pos: 0.0,
-			frame_pos: 0,
-		})
+			len: None,
+			read: 0,
+		};
+
+		r.reset();
+
+		return r;
+
 	}
-	pub fn get_inner(&self) -> &Arc<Mutex<dyn Source + Send>> {
+
+	pub fn inner(&self) -> &S {
 		return &self.src;
 	}
+
+	pub fn inner_mut(&mut self) -> &mut S {
+		return &mut self.src;
+	}
+
+	pub fn quality(&self) -> ResampleQuality {
+		return self.quality;
+	}
+
+	/// clear filter memory, call after moving the inner source
+	pub fn reset(&mut self) {
+
+		let half = self.kernel.half;
+
+		self.buf.clear();
+
+		// zeros before the first frame so the first output is centered on it
+		for _ in 0..half {
+			self.buf.push_back(Frame::default());
+		}
+
+		self.base = -(half as i64);
+		self.pos = 0.0;
+		self.len = None;
+		self.read = 0;
+
+	}
+
+	fn update_rate(&mut self) {
+
+		let ratio = self.target as f64 / self.src.sample_rate().max(1) as f64;
+
+		if (ratio - self.ratio).abs() > f64::EPSILON {
+			self.ratio = ratio;
+			self.kernel = Kernel::new(self.quality, ratio);
+			self.reset();
+		}
+
+	}
+
 }
 
-impl Iterator for Resampler {
+impl<S: Source> Iterator for Resampler<S> {
 
 	type Item = Frame;
 
 	fn next(&mut self) -> Option<Self::Item> {
 
-		let mut src = match self.src.lock() {
-			Ok(src) => src,
-			Err(_) => return None,
-		};
+		self.update_rate();
 
-		let sample_rate = src.sample_rate();
+		if self.src.sample_rate() == self.target {
+			return self.src.next();
+		}
 
-		if self.target.sample_rate == sample_rate {
-			return src.next();
+		if let Some(len) = self.len {
+			if self.pos >= len as f64 {
+				return None;
+			}
 		}
 
-		// TODO: bugged yo
-		let speed = sample_rate as f32 / self.target.sample_rate as f32;
+		let half = self.kernel.half as i64;
+		let center = self.pos.floor() as i64;
+		let first = center - half + 1;
+		let last = center + half;
 
-		self.pos += speed;
+		// drop frames the kernel has passed
+		while self.base < first && !self.buf.is_empty() {
+			self.buf.pop_front();
+			self.base += 1;
+		}
 
-		if self.pos > self.frame_pos as f32 {
+		// read until the kernel is covered, pad with silence after the end
+		while self.base + (self.buf.len() as i64) <= last {
 
-			let skip = self.pos as usize - self.frame_pos;
+			let frame = if self.len.is_none() {
+				self.src.next()
+			} else {
+				None
+			};
 
-			for _ in 0..skip {
-				src.next();
+			match frame {
+				Some(f) => {
+					self.buf.push_back(f);
+					self.read += 1;
+				},
+				None => {
+					if self.len.is_none() {
+						self.len = Some(self.read);
+					}
+					self.buf.push_back(Frame::default());
+				},
 			}
 
-			self.prev_frame = self.next_frame;
-			self.next_frame = src.next();
+		}
 
-			if self.next_frame.is_none() {
+		if let Some(len) = self.len {
+			if self.pos >= len as f64 {
 				return None;
 			}
+		}
+
+		let mut out = Frame::default();
 
-			self.frame_pos += skip + 1;
-			let progress = self.pos - (self.frame_pos - 1) as f32;
-			let prev = self.prev_frame.unwrap_or_default();
-			let next = self.next_frame.unwrap_or_default();
+		for k in first..=last {
+			let f = self.buf[(k - self.base) as usize];
+			out += f * self.kernel.at(self.pos - k as f64);
+		}
 
-			return Some(prev + (next - prev) * progress);
+		self.pos += 1.0 / self.ratio;
 
-		} else {
+		return Some(out);
+
+	}
+
+}
+
+impl<S: Source> Source for Resampler<S> {
+
+	fn sample_rate(&self) -> u32 {
+		return self.target;
+	}
+
+	fn seek_start(&mut self) -> Result<()> {
+		self.src.seek_start()?;
+		self.reset();
+		return Ok(());
+	}
+
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+		self.src.seek(pos)?;
+		self.reset();
+		return Ok(());
+	}
 
-			let prev = self.prev_frame.unwrap_or_default();
-			let next = self.next_frame.unwrap_or_default();
-			let progress = self.pos - (self.frame_pos - 1) as f32;
+	fn position(&self) -> Option<Duration> {
 
-			return Some(prev + (next - prev) * progress);
+		let pos = self.src.position()?;
 
+		if self.src.sample_rate() == self.target {
+			return Some(pos);
 		}
 
+		// the inner source is ahead by what's buffered for the kernel
+		let ahead = (self.read as f64 - self.pos) / self.src.sample_rate().max(1) as f64;
+
+		return Some(Duration::from_secs_f64((pos.as_secs_f64() - ahead).max(0.0)));
+
+	}
+
+	fn duration(&self) -> Option<Duration> {
+		return self.src.duration();
+	}
+
+}
+
+// plays a slice of frames
+struct FrameSource<'a> {
+	frames: std::slice::Iter<'a, Frame>,
+	sample_rate: u32,
+}
+
+impl<'a> Iterator for FrameSource<'a> {
+	type Item = Frame;
+	fn next(&mut self) -> Option<Self::Item> {
+		return self.frames.next().cloned();
 	}
+}
+
+impl<'a> Source for FrameSource<'a> {
+	fn sample_rate(&self) -> u32 {
+		return self.sample_rate;
+	}
+}
+
+/// convert a whole buffer of frames from one sample rate to another
+pub fn resample(frames: &[Frame], from: u32, to: u32, quality: ResampleQuality) -> Vec<Frame> {
+
+	if from == to {
+		return frames.to_vec();
+	}
+
+	let src = FrameSource {
+		frames: frames.iter(),
+		sample_rate: from,
+	};
+
+	return Resampler::new(src, to, quality).collect();
+
+}
+
+#[cfg(test)]
+fn sine(freq: f32, sample_rate: u32, len: usize) -> Vec<Frame> {
+	return (0..len)
+		.map(|i| {
+			let v = (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin() * 0.5;
+			return Frame::new(v, v);
+		})
+		.collect();
+}
+
+// magnitude of a frequency in a signal
+#[cfg(test)]
+fn dft(frames: &[Frame], freq: f32, sample_rate: u32) -> f32 {
+
+	let mut re = 0.0;
+	let mut im = 0.0;
+
+	for (i, f) in frames.iter().enumerate() {
+		let a = 2.0 * PI * freq as f64 * i as f64 / sample_rate as f64;
+		re += f.left as f64 * a.cos();
+		im -= f.left as f64 * a.sin();
+	}
+
+	return ((re * re + im * im).sqrt() * 2.0 / frames.len() as f64) as f32;
+
+}
+
+#[test]
+fn resample_pitch_length() {
+
+	let pairs = [(48000, 44100), (22050, 44100), (44100, 48000), (32000, 44100)];
+	let qualities = [ResampleQuality::Low, ResampleQuality::Medium, ResampleQuality::High];
+
+	for (from, to) in &pairs {
+		for q in &qualities {
+
+			let input = sine(1000.0, *from, *from as usize);
+			let output = resample(&input, *from, *to, *q);
+
+			// 1 second in, 1 second out
+			let expected = (*to as usize) as i64;
+
+			assert!((output.len() as i64 - expected).abs() <= 1, "{} -> {} {:?}: length {}", from, to, q, output.len());
+
+			// same pitch, the energy stays at 1k instead of moving to where playing the input at the wrong rate would put it
+			let skip = *to as usize / 10;
+			let body = &output[skip..output.len() - skip];
+			let at = dft(body, 1000.0, *to);
+			let off = dft(body, 1000.0 * *to as f32 / *from as f32, *to);
+
+			assert!((at - 0.5).abs() < 0.02, "{} -> {} {:?}: level {}", from, to, q, at);
+			assert!(off < at * 0.1, "{} -> {} {:?}: pitch shifted", from, to, q);
+
+		}
+	}
+
+}
+
+#[test]
+fn resample_anti_alias() {
+
+	// 23k is above the 22.05k output nyquist and should be filtered out instead of folding down to 21.1k
+	let input = sine(23000.0, 48000, 48000);
+	let output = resample(&input, 48000, 44100, ResampleQuality::Medium);
+	let alias = dft(&output, 44100.0 - 23000.0, 44100);
+
+	assert!(alias < 0.5 * 0.01, "alias {}", alias);
+
+}
+
+#[test]
+fn resample_passthrough() {
+
+	let input = sine(440.0, 44100, 1000);
+	let output = resample(&input, 44100, 44100, ResampleQuality::High);
+
+	assert_eq!(input, output);
 
 }
 
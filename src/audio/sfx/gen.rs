@@ -0,0 +1,310 @@
+// wengwengweng
+
+use std::f32::consts::PI;
+use super::*;
+
+// sfxr renders each sample from 8 sub samples
+const OVERSAMPLE: usize = 8;
+const MASTER_VOLUME: f32 = 0.05;
+// sfxr's export gain, for a usable level
+const GAIN: f32 = 4.0;
+const PHASER_LEN: usize = 1024;
+const NOISE_LEN: usize = 32;
+// noise is the same every time for the same params
+const NOISE_SEED: u64 = 0x5F3;
+// pitches in sfxr are relative to this
+const SAMPLE_RATE: u32 = 44100;
+
+/// Plays [`Params`](struct.Params.html) as a [`Source`](../trait.Source.html), at 44100hz
+#[derive(Clone)]
+pub struct Generator {
+	params: Params,
+	rng: Rng,
+	done: bool,
+
+	phase: usize,
+	fperiod: f64,
+	fmaxperiod: f64,
+	fslide: f64,
+	fdslide: f64,
+	square_duty: f32,
+	square_slide: f32,
+	arp_mod: f64,
+	arp_time: usize,
+	arp_limit: usize,
+
+	vib_phase: f32,
+	vib_speed: f32,
+	vib_amp: f32,
+
+	env_vol: f32,
+	env_stage: usize,
+	env_time: usize,
+	env_length: [usize; 3],
+
+	fltp: f32,
+	fltdp: f32,
+	fltw: f32,
+	fltw_d: f32,
+	fltdmp: f32,
+	fltphp: f32,
+	flthp: f32,
+	flthp_d: f32,
+
+	fphase: f32,
+	fdphase: f32,
+	ipp: usize,
+	phaser: Vec<f32>,
+	noise: [f32; NOISE_LEN],
+
+	rep_time: usize,
+	rep_limit: usize,
+}
+
+impl Generator {
+
+	pub fn new(mut params: Params) -> Self {
+
+		params.clamp();
+
+		let p = &params;
+		let mut rng = Rng::new(NOISE_SEED);
+		let mut noise = [0.0; NOISE_LEN];
+
+		for n in &mut noise {
+			*n = rng.gen() * 2.0 - 1.0;
+		}
+
+		let fltw = p.lpf_freq.powi(3) * 0.1;
+
+		let mut g = Self {
+			rng: rng,
+			done: false,
+			phase: 0,
+			fperiod: 0.0,
+			fmaxperiod: 0.0,
+			fslide: 0.0,
+			fdslide: 0.0,
+			square_duty: 0.0,
+			square_slide: 0.0,
+			arp_mod: 0.0,
+			arp_time: 0,
+			arp_limit: 0,
+			vib_phase: 0.0,
+			vib_speed: p.vib_speed.powi(2) * 0.01,
+			vib_amp: p.vib_strength * 0.5,
+			env_vol: 0.0,
+			env_stage: 0,
+			env_time: 0,
+			env_length: [
+				(p.env_attack.powi(2) * 100000.0) as usize,
+				(p.env_sustain.powi(2) * 100000.0) as usize,
+				(p.env_decay.powi(2) * 100000.0) as usize,
+			],
+			fltp: 0.0,
+			fltdp: 0.0,
+			fltw: fltw,
+			fltw_d: 1.0 + p.lpf_ramp * 0.0001,
+			fltdmp: (5.0 / (1.0 + p.lpf_resonance.powi(2) * 20.0) * (0.01 + fltw)).min(0.8),
+			fltphp: 0.0,
+			flthp: p.hpf_freq.powi(2) * 0.1,
+			flthp_d: 1.0 + p.hpf_ramp * 0.0003,
+			fphase: p.pha_offset.powi(2) * 1020.0 * p.pha_offset.signum(),
+			fdphase: p.pha_ramp.powi(2) * p.pha_ramp.signum(),
+			ipp: 0,
+			phaser: vec![0.0; PHASER_LEN],
+			noise: noise,
+			rep_time: 0,
+			rep_limit: if p.repeat_speed == 0.0 {
+				0
+			} else {
+				((1.0 - p.repeat_speed).powi(2) * 20000.0 + 32.0) as usize
+			},
+			params: params,
+		};
+
+		g.restart();
+
+		return g;
+
+	}
+
+	pub fn params(&self) -> &Params {
+		return &self.params;
+	}
+
+	// reset the pitch, duty and arpeggio sweeps, also what repeat does
+	fn restart(&mut self) {
+
+		let p = &self.params;
+
+		self.fperiod = 100.0 / (p.base_freq as f64 * p.base_freq as f64 + 0.001);
+		self.fmaxperiod = 100.0 / (p.freq_limit as f64 * p.freq_limit as f64 + 0.001);
+		self.fslide = 1.0 - (p.freq_ramp as f64).powi(3) * 0.01;
+		self.fdslide = -(p.freq_dramp as f64).powi(3) * 0.000001;
+		self.square_duty = 0.5 - p.duty * 0.5;
+		self.square_slide = -p.duty_ramp * 0.00005;
+
+		self.arp_mod = if p.arp_mod >= 0.0 {
+			1.0 - (p.arp_mod as f64).powi(2) * 0.9
+		} else {
+			1.0 + (p.arp_mod as f64).powi(2) * 10.0
+		};
+
+		self.arp_time = 0;
+		self.arp_limit = if p.arp_speed == 1.0 {
+			0
+		} else {
+			((1.0 - p.arp_speed).powi(2) * 20000.0 + 32.0) as usize
+		};
+
+	}
+
+	fn sample(&mut self) -> Option<f32> {
+
+		if self.done {
+			return None;
+		}
+
+		self.rep_time += 1;
+
+		if self.rep_limit != 0 && self.rep_time >= self.rep_limit {
+			self.rep_time = 0;
+			self.restart();
+		}
+
+		self.arp_time += 1;
+
+		if self.arp_limit != 0 && self.arp_time >= self.arp_limit {
+			self.arp_limit = 0;
+			self.fperiod *= self.arp_mod;
+		}
+
+		self.fslide += self.fdslide;
+		self.fperiod *= self.fslide;
+
+		if self.fperiod > self.fmaxperiod {
+			self.fperiod = self.fmaxperiod;
+			if self.params.freq_limit > 0.0 {
+				self.done = true;
+			}
+		}
+
+		let mut rfperiod = self.fperiod as f32;
+
+		if self.vib_amp > 0.0 {
+			self.vib_phase += self.vib_speed;
+			rfperiod = self.fperiod as f32 * (1.0 + self.vib_phase.sin() * self.vib_amp);
+		}
+
+		let period = (rfperiod as usize).max(8);
+
+		self.square_duty = (self.square_duty + self.square_slide).max(0.0).min(0.5);
+
+		self.env_time += 1;
+
+		if self.env_time > self.env_length[self.env_stage] {
+			self.env_time = 0;
+			self.env_stage += 1;
+			if self.env_stage == 3 {
+				self.done = true;
+				return None;
+			}
+		}
+
+		let t = self.env_time as f32 / self.env_length[self.env_stage].max(1) as f32;
+
+		self.env_vol = match self.env_stage {
+			0 => t,
+			1 => 1.0 + (1.0 - t) * 2.0 * self.params.env_punch,
+			_ => 1.0 - t,
+		};
+
+		self.fphase += self.fdphase;
+
+		let iphase = (self.fphase.abs() as usize).min(PHASER_LEN - 1);
+
+		if self.flthp_d != 1.0 {
+			self.flthp = (self.flthp * self.flthp_d).max(0.00001).min(0.1);
+		}
+
+		let mut acc = 0.0;
+
+		for _ in 0..OVERSAMPLE {
+
+			self.phase += 1;
+
+			if self.phase >= period {
+				self.phase %= period;
+				if self.params.wave == Wave::Noise {
+					for n in &mut self.noise {
+						*n = self.rng.gen() * 2.0 - 1.0;
+					}
+				}
+			}
+
+			let fp = self.phase as f32 / period as f32;
+
+			let mut sample = match self.params.wave {
+				Wave::Square => if fp < self.square_duty { 0.5 } else { -0.5 },
+				Wave::Saw => 1.0 - fp * 2.0,
+				Wave::Sine => (fp * 2.0 * PI).sin(),
+				Wave::Noise => self.noise[self.phase * NOISE_LEN / period],
+			};
+
+			// lowpass
+			let pp = self.fltp;
+
+			self.fltw = (self.fltw * self.fltw_d).max(0.0).min(0.1);
+
+			if self.params.lpf_freq != 1.0 {
+				self.fltdp += (sample - self.fltp) * self.fltw;
+				self.fltdp -= self.fltdp * self.fltdmp;
+			} else {
+				self.fltp = sample;
+				self.fltdp = 0.0;
+			}
+
+			self.fltp += self.fltdp;
+
+			// highpass
+			self.fltphp += self.fltp - pp;
+			self.fltphp -= self.fltphp * self.flthp;
+			sample = self.fltphp;
+
+			// phaser
+			self.phaser[self.ipp % PHASER_LEN] = sample;
+			sample += self.phaser[(self.ipp + PHASER_LEN - iphase) % PHASER_LEN];
+			self.ipp = (self.ipp + 1) % PHASER_LEN;
+
+			acc += sample * self.env_vol;
+
+		}
+
+		let out = acc / OVERSAMPLE as f32 * MASTER_VOLUME * GAIN * 2.0 * self.params.volume;
+
+		return Some(out.max(-1.0).min(1.0));
+
+	}
+
+}
+
+impl Iterator for Generator {
+	type Item = Frame;
+	fn next(&mut self) -> Option<Self::Item> {
+		return self.sample().map(Frame::mono);
+	}
+}
+
+impl Source for Generator {
+
+	fn sample_rate(&self) -> u32 {
+		return SAMPLE_RATE;
+	}
+
+	fn seek_start(&mut self) -> Result<()> {
+		*self = Self::new(self.params.clone());
+		return Ok(());
+	}
+
+}
@@ -0,0 +1,22 @@
+// wengwengweng
+
+//! Procedural Sound Effects
+//!
+//! retro game sound effects described by a handful of [`Params`](struct.Params.html), start from a [`Preset`](enum.Preset.html) and tweak or [`mutate`](struct.Params.html#method.mutate) from there
+//!
+//! this is a port of [sfxr](http://www.drpetter.se/project_sfxr.html) rather than a wrapper around the sfxr crate, so params can serialize and presets & mutation can be seeded
+//! ```ignore
+//! let coin = sfx::Params::preset(sfx::Preset::Pickup, 7);
+//! let coin2 = coin.mutate(1);
+//!
+//! coin.to_sound(&d.audio).play()?;
+//!
+//! // params serialize, so tuned sounds can be saved and loaded
+//! let json = data::to_json(&coin2)?;
+//! let coin2: sfx::Params = data::from_json(&json)?;
+//! ```
+
+export!(params);
+export!(gen);
+
+use super::*;
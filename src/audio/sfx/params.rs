@@ -0,0 +1,363 @@
+// wengwengweng
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use super::*;
+
+/// Oscillator Shape
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Wave {
+	Square,
+	Saw,
+	Sine,
+	Noise,
+}
+
+/// Starting Points for [`Params`](struct.Params.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Preset {
+	Pickup,
+	Laser,
+	Explosion,
+	Jump,
+	Hit,
+	Blip,
+}
+
+impl Preset {
+	pub fn all() -> [Preset; 6] {
+		return [
+			Preset::Pickup,
+			Preset::Laser,
+			Preset::Explosion,
+			Preset::Jump,
+			Preset::Hit,
+			Preset::Blip,
+		];
+	}
+}
+
+/// Sound Effect Description
+///
+/// same parameters as sfxr, most are 0.0 - 1.0, ramps and `arp_mod` go -1.0 - 1.0
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Params {
+	pub wave: Wave,
+	/// start pitch
+	pub base_freq: f32,
+	/// stops when the pitch slides below this
+	pub freq_limit: f32,
+	/// pitch slide
+	pub freq_ramp: f32,
+	/// change of pitch slide
+	pub freq_dramp: f32,
+	/// square wave duty
+	pub duty: f32,
+	pub duty_ramp: f32,
+	pub vib_strength: f32,
+	pub vib_speed: f32,
+	pub env_attack: f32,
+	pub env_sustain: f32,
+	/// louder at the start of sustain
+	pub env_punch: f32,
+	pub env_decay: f32,
+	/// jump in pitch after a while
+	pub arp_mod: f32,
+	pub arp_speed: f32,
+	/// restart the pitch and filter sweeps at this rate
+	pub repeat_speed: f32,
+	pub pha_offset: f32,
+	pub pha_ramp: f32,
+	/// lowpass cutoff, 1.0 is off
+	pub lpf_freq: f32,
+	pub lpf_ramp: f32,
+	pub lpf_resonance: f32,
+	/// highpass cutoff, 0.0 is off
+	pub hpf_freq: f32,
+	pub hpf_ramp: f32,
+	pub volume: f32,
+}
+
+impl Default for Params {
+	fn default() -> Self {
+		return Self {
+			wave: Wave::Square,
+			base_freq: 0.3,
+			freq_limit: 0.0,
+			freq_ramp: 0.0,
+			freq_dramp: 0.0,
+			duty: 0.0,
+			duty_ramp: 0.0,
+			vib_strength: 0.0,
+			vib_speed: 0.0,
+			env_attack: 0.0,
+			env_sustain: 0.3,
+			env_punch: 0.0,
+			env_decay: 0.4,
+			arp_mod: 0.0,
+			arp_speed: 0.0,
+			repeat_speed: 0.0,
+			pha_offset: 0.0,
+			pha_ramp: 0.0,
+			lpf_freq: 1.0,
+			lpf_ramp: 0.0,
+			lpf_resonance: 0.0,
+			hpf_freq: 0.0,
+			hpf_ramp: 0.0,
+			volume: 0.5,
+		};
+	}
+}
+
+// sfxr's rnd(n), an integer 0 - n
+fn rnd(r: &mut Rng, n: usize) -> usize {
+	return (r.gen() * (n + 1) as f32) as usize % (n + 1);
+}
+
+// sfxr's frnd(n), a float 0.0 - n
+fn frnd(r: &mut Rng, n: f32) -> f32 {
+	return r.gen() * n;
+}
+
+fn chance(r: &mut Rng) -> bool {
+	return rnd(r, 1) == 1;
+}
+
+impl Params {
+
+	/// a random variation of a preset, the same seed always gives the same sound
+	pub fn preset(p: Preset, seed: u64) -> Self {
+
+		let r = &mut Rng::new(seed);
+		let mut s = Self::default();
+
+		match p {
+
+			Preset::Pickup => {
+				s.base_freq = 0.4 + frnd(r, 0.5);
+				s.env_attack = 0.0;
+				s.env_sustain = frnd(r, 0.1);
+				s.env_decay = 0.1 + frnd(r, 0.4);
+				s.env_punch = 0.3 + frnd(r, 0.3);
+				if chance(r) {
+					s.arp_speed = 0.5 + frnd(r, 0.2);
+					s.arp_mod = 0.2 + frnd(r, 0.4);
+				}
+			},
+
+			Preset::Laser => {
+				s.wave = match rnd(r, 2) {
+					0 => Wave::Square,
+					1 => Wave::Saw,
+					_ => if chance(r) { Wave::Saw } else { Wave::Sine },
+				};
+				s.base_freq = 0.5 + frnd(r, 0.5);
+				s.freq_limit = (s.base_freq - 0.2 - frnd(r, 0.6)).max(0.2);
+				s.freq_ramp = -0.15 - frnd(r, 0.2);
+				if rnd(r, 2) == 0 {
+					s.base_freq = 0.3 + frnd(r, 0.6);
+					s.freq_limit = frnd(r, 0.1);
+					s.freq_ramp = -0.35 - frnd(r, 0.3);
+				}
+				if chance(r) {
+					s.duty = frnd(r, 0.5);
+					s.duty_ramp = frnd(r, 0.2);
+				} else {
+					s.duty = 0.4 + frnd(r, 0.5);
+					s.duty_ramp = -frnd(r, 0.7);
+				}
+				s.env_attack = 0.0;
+				s.env_sustain = 0.1 + frnd(r, 0.2);
+				s.env_decay = frnd(r, 0.4);
+				if chance(r) {
+					s.env_punch = frnd(r, 0.3);
+				}
+				if rnd(r, 2) == 0 {
+					s.pha_offset = frnd(r, 0.2);
+					s.pha_ramp = -frnd(r, 0.2);
+				}
+				if chance(r) {
+					s.hpf_freq = frnd(r, 0.3);
+				}
+			},
+
+			Preset::Explosion => {
+				s.wave = Wave::Noise;
+				if chance(r) {
+					s.base_freq = 0.1 + frnd(r, 0.4);
+					s.freq_ramp = -0.1 + frnd(r, 0.4);
+				} else {
+					s.base_freq = 0.2 + frnd(r, 0.7);
+					s.freq_ramp = -0.2 - frnd(r, 0.2);
+				}
+				s.base_freq *= s.base_freq;
+				if rnd(r, 4) == 0 {
+					s.freq_ramp = 0.0;
+				}
+				if rnd(r, 2) == 0 {
+					s.repeat_speed = 0.3 + frnd(r, 0.5);
+				}
+				s.env_attack = 0.0;
+				s.env_sustain = 0.1 + frnd(r, 0.3);
+				s.env_decay = frnd(r, 0.5);
+				if !chance(r) {
+					s.pha_offset = -0.3 + frnd(r, 0.9);
+					s.pha_ramp = -frnd(r, 0.3);
+				}
+				s.env_punch = 0.2 + frnd(r, 0.6);
+				if chance(r) {
+					s.vib_strength = frnd(r, 0.7);
+					s.vib_speed = frnd(r, 0.6);
+				}
+				if rnd(r, 2) == 0 {
+					s.arp_speed = 0.6 + frnd(r, 0.3);
+					s.arp_mod = 0.8 - frnd(r, 1.6);
+				}
+			},
+
+			Preset::Jump => {
+				s.wave = Wave::Square;
+				s.duty = frnd(r, 0.6);
+				s.base_freq = 0.3 + frnd(r, 0.3);
+				s.freq_ramp = 0.1 + frnd(r, 0.2);
+				s.env_attack = 0.0;
+				s.env_sustain = 0.1 + frnd(r, 0.3);
+				s.env_decay = 0.1 + frnd(r, 0.2);
+				if chance(r) {
+					s.hpf_freq = frnd(r, 0.3);
+				}
+				if chance(r) {
+					s.lpf_freq = 1.0 - frnd(r, 0.6);
+				}
+			},
+
+			Preset::Hit => {
+				s.wave = match rnd(r, 2) {
+					0 => Wave::Square,
+					1 => Wave::Saw,
+					_ => Wave::Noise,
+				};
+				if s.wave == Wave::Square {
+					s.duty = frnd(r, 0.6);
+				}
+				s.base_freq = 0.2 + frnd(r, 0.6);
+				s.freq_ramp = -0.3 - frnd(r, 0.4);
+				s.env_attack = 0.0;
+				s.env_sustain = frnd(r, 0.1);
+				s.env_decay = 0.1 + frnd(r, 0.2);
+				if chance(r) {
+					s.hpf_freq = frnd(r, 0.3);
+				}
+			},
+
+			Preset::Blip => {
+				s.wave = if chance(r) {
+					Wave::Saw
+				} else {
+					s.duty = frnd(r, 0.6);
+					Wave::Square
+				};
+				s.base_freq = 0.2 + frnd(r, 0.4);
+				s.env_attack = 0.0;
+				s.env_sustain = 0.1 + frnd(r, 0.1);
+				s.env_decay = frnd(r, 0.2);
+				s.hpf_freq = 0.1;
+			},
+
+		}
+
+		return s;
+
+	}
+
+	/// a slightly different copy, roughly half the params move by up to ±0.05, the same seed always gives the same result
+	pub fn mutate(&self, seed: u64) -> Self {
+
+		let r = &mut Rng::new(seed);
+		let mut s = self.clone();
+
+		for v in vec![
+			&mut s.base_freq,
+			&mut s.freq_ramp,
+			&mut s.freq_dramp,
+			&mut s.duty,
+			&mut s.duty_ramp,
+			&mut s.vib_strength,
+			&mut s.vib_speed,
+			&mut s.env_attack,
+			&mut s.env_sustain,
+			&mut s.env_decay,
+			&mut s.env_punch,
+			&mut s.lpf_resonance,
+			&mut s.lpf_freq,
+			&mut s.lpf_ramp,
+			&mut s.hpf_freq,
+			&mut s.hpf_ramp,
+			&mut s.pha_offset,
+			&mut s.pha_ramp,
+			&mut s.repeat_speed,
+			&mut s.arp_speed,
+			&mut s.arp_mod,
+		] {
+			if chance(r) {
+				*v += frnd(r, 0.1) - 0.05;
+			}
+		}
+
+		s.clamp();
+
+		return s;
+
+	}
+
+	/// keep every param in its range
+	pub fn clamp(&mut self) {
+
+		let unit = |v: &mut f32| *v = v.max(0.0).min(1.0);
+		let signed = |v: &mut f32| *v = v.max(-1.0).min(1.0);
+
+		unit(&mut self.base_freq);
+		unit(&mut self.freq_limit);
+		signed(&mut self.freq_ramp);
+		signed(&mut self.freq_dramp);
+		unit(&mut self.duty);
+		signed(&mut self.duty_ramp);
+		unit(&mut self.vib_strength);
+		unit(&mut self.vib_speed);
+		unit(&mut self.env_attack);
+		unit(&mut self.env_sustain);
+		unit(&mut self.env_punch);
+		unit(&mut self.env_decay);
+		signed(&mut self.arp_mod);
+		unit(&mut self.arp_speed);
+		unit(&mut self.repeat_speed);
+		signed(&mut self.pha_offset);
+		signed(&mut self.pha_ramp);
+		unit(&mut self.lpf_freq);
+		signed(&mut self.lpf_ramp);
+		unit(&mut self.lpf_resonance);
+		unit(&mut self.hpf_freq);
+		signed(&mut self.hpf_ramp);
+		unit(&mut self.volume);
+
+	}
+
+	/// a [`Source`](../trait.Source.html) that plays this
+	pub fn generator(&self) -> Generator {
+		return Generator::new(self.clone());
+	}
+
+	/// render the whole sound
+	pub fn render(&self) -> AudioBuffer {
+		return AudioBuffer::from_source(self.generator());
+	}
+
+	/// render to a [`Sound`](../struct.Sound.html)
+	#[cfg(not(web))]
+	pub fn to_sound(&self, ctx: &Audio) -> Sound {
+		return Sound::from_buffer(ctx, self.render());
+	}
+
+}
@@ -13,14 +13,22 @@ impl Sound {
 
 	/// create sound from bytes of an audio file
 	pub fn from_bytes(ctx: &Audio, data: &[u8]) -> Result<Self> {
+		return Ok(Self::from_buffer(ctx, AudioBuffer::from_bytes(data)?));
+	}
+
+	/// create sound from decoded or generated audio
+	pub fn from_buffer(ctx: &Audio, mut buffer: AudioBuffer) -> Self {
+
+		if buffer.sample_rate() != SPEC.sample_rate {
+			buffer = buffer.resample(SPEC.sample_rate, ctx.resample_quality());
+		}
 
-		let buffer = AudioBuffer::from_bytes(data)?;
 		let playback = AudioBufferPlayback::new(buffer);
 
-		return Ok(Self {
+		return Self {
 			playback: playback,
 			mixer: Arc::clone(ctx.mixer()),
-		});
+		};
 
 	}
 
@@ -45,6 +53,12 @@ impl Sound {
 			effects: vec![],
 			volume: 1.0,
 			pan: Pan::new(1.0, 1.0),
+			bus: None,
+			sends: vec![],
+			emitter: None,
+			speed: 1.0,
+			preserve_pitch: false,
+			limit: None,
 		};
 	}
 
@@ -57,6 +71,12 @@ pub struct SoundBuilder<'a> {
 	mixer: &'a Arc<Mutex<Mixer>>,
 	volume: f32,
 	pan: Pan,
+	bus: Option<String>,
+	sends: Vec<(String, f32)>,
+	emitter: Option<Arc<Mutex<Emitter>>>,
+	speed: f32,
+	preserve_pitch: bool,
+	pub(super) limit: Option<&'a mut VoiceLimit>,
 }
 
 impl<'a> SoundBuilder<'a> {
@@ -76,14 +96,57 @@ impl<'a> SoundBuilder<'a> {
 		return self;
 	}
 
+	/// playback rate, 2.0 plays twice as fast and an octave higher
+	pub fn speed(mut self, s: f32) -> Self {
+		self.speed = s;
+		return self;
+	}
+
+	/// keep pitch when changing speed by time-stretching instead
+	pub fn preserve_pitch(mut self, b: bool) -> Self {
+		self.preserve_pitch = b;
+		return self;
+	}
+
+	/// route into a [`Bus`](struct.Bus.html) instead of master
+	pub fn bus(mut self, name: &str) -> Self {
+		self.bus = Some(name.to_owned());
+		return self;
+	}
+
+	/// also send to a [`Bus`](struct.Bus.html) with amount, e.g. a shared reverb
+	pub fn send(mut self, name: &str, amount: f32) -> Self {
+		self.sends.push((name.to_owned(), amount));
+		return self;
+	}
+
+	/// play from an [`Emitter`](struct.Emitter.html), it can keep moving while the sound plays
+	pub fn emitter(mut self, e: Arc<Mutex<Emitter>>) -> Self {
+		self.emitter = Some(e);
+		return self;
+	}
+
 	pub fn play(self) -> Result<()> {
 
+		let mut limit = self.limit;
+
+		if let Some(limit) = &mut limit {
+			if !limit.admit() {
+				return Ok(());
+			}
+		}
+
 		let mut mixer = self.mixer
 			.lock()
 			.map_err(|_| format!("failed to get mixer"))?;
 
-		let ctrl = mixer.add(Arc::new(Mutex::new(self.playback)));
-		let mut ctrl = ctrl.lock().unwrap();
+		let handle = mixer.add(Arc::new(Mutex::new(self.playback)));
+
+		if let Some(limit) = limit {
+			limit.track(handle.clone());
+		}
+
+		let mut ctrl = handle.lock().unwrap();
 
 		for e in self.effects {
 			ctrl.effects.push(e);
@@ -91,6 +154,11 @@ impl<'a> SoundBuilder<'a> {
 
 		ctrl.pan = self.pan;
 		ctrl.volume = self.volume;
+		ctrl.bus = self.bus;
+		ctrl.sends = self.sends;
+		ctrl.emitter = self.emitter;
+		ctrl.speed = self.speed;
+		ctrl.preserve_pitch = self.preserve_pitch;
 
 		return Ok(());
 
@@ -7,5 +7,17 @@ pub trait Source: Iterator<Item = Frame> {
 	fn seek_start(&mut self) -> Result<()> {
 		return Ok(());
 	}
+	/// jump to a position, not every source can
+	fn seek(&mut self, _: Duration) -> Result<()> {
+		return Err(format!("seeking not supported"));
+	}
+	/// current playback position, if known
+	fn position(&self) -> Option<Duration> {
+		return None;
+	}
+	/// total length, if known
+	fn duration(&self) -> Option<Duration> {
+		return None;
+	}
 }
 
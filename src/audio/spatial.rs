@@ -3,9 +3,210 @@
 use std::f32::consts::PI;
 use super::*;
 
-const MIN: f32 = 0.3;
-const MAX: f32 = 1.0;
+// gain of the ear facing away from a source
+const FAR_EAR: f32 = 0.3;
+// cutoff of a fully occluded source
+const OCCLUDED_CUTOFF: f32 = 600.0;
+const OPEN_CUTOFF: f32 = 20000.0;
+// doppler pitch range
+const MIN_PITCH: f32 = 0.25;
+const MAX_PITCH: f32 = 4.0;
 
+/// Where Audio is Heard From
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Listener {
+	pub pos: Vec3,
+	pub front: Vec3,
+	pub up: Vec3,
+	/// units per second, only used for doppler
+	pub velocity: Vec3,
+	/// in world units per second, 343 if a unit is a meter
+	pub speed_of_sound: f32,
+}
+
+impl Listener {
+
+	pub fn new(pos: Vec3, front: Vec3, up: Vec3) -> Self {
+		return Self {
+			pos: pos,
+			front: front,
+			up: up,
+			..Default::default()
+		};
+	}
+
+	/// move and derive velocity from how far it moved in `dt` seconds
+	pub fn move_to(&mut self, pos: Vec3, dt: f32) {
+		if dt > 0.0 {
+			self.velocity = (pos - self.pos) / dt;
+		}
+		self.pos = pos;
+	}
+
+}
+
+impl Default for Listener {
+	fn default() -> Self {
+		return Self {
+			pos: vec3!(0),
+			front: vec3!(0, 0, -1),
+			up: vec3!(0, 1, 0),
+			velocity: vec3!(0),
+			speed_of_sound: 343.3,
+		};
+	}
+}
+
+/// How Volume Drops With Distance
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rolloff {
+	/// full volume until `min`, silent after `max`
+	Linear {
+		min: f32,
+		max: f32,
+	},
+	/// physically based falloff, halves for every doubled distance past `min` with a factor of 1, stops dropping after `max`
+	Inverse {
+		min: f32,
+		max: f32,
+		factor: f32,
+	},
+	/// `(dist / min) ^ -factor`, stops dropping after `max`
+	Exponential {
+		min: f32,
+		max: f32,
+		factor: f32,
+	},
+}
+
+impl Rolloff {
+
+	/// gain at a distance
+	pub fn gain(&self, dist: f32) -> f32 {
+		return match *self {
+			Rolloff::Linear { min, max } => {
+				if dist <= min {
+					1.0
+				} else if dist >= max {
+					0.0
+				} else {
+					1.0 - (dist - min) / (max - min)
+				}
+			},
+			Rolloff::Inverse { min, max, factor } => {
+				let min = min.max(0.0001);
+				let d = dist.max(min).min(max.max(min));
+				min / (min + factor * (d - min))
+			},
+			Rolloff::Exponential { min, max, factor } => {
+				let min = min.max(0.0001);
+				let d = dist.max(min).min(max.max(min));
+				(d / min).powf(-factor)
+			},
+		};
+	}
+
+}
+
+impl Default for Rolloff {
+	fn default() -> Self {
+		return Rolloff::Inverse {
+			min: 1.0,
+			max: 1000.0,
+			factor: 1.0,
+		};
+	}
+}
+
+/// Directional Emitter Cone
+///
+/// angles are the full width in radians, full volume inside `inner`, `outer_gain` outside `outer`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cone {
+	pub inner: f32,
+	pub outer: f32,
+	pub outer_gain: f32,
+}
+
+impl Cone {
+
+	pub fn new(inner: f32, outer: f32, outer_gain: f32) -> Self {
+		return Self {
+			inner: inner,
+			outer: outer,
+			outer_gain: outer_gain,
+		};
+	}
+
+	/// gain at an angle off the cone direction
+	pub fn gain(&self, angle: f32) -> f32 {
+
+		let inner = self.inner / 2.0;
+		let outer = (self.outer / 2.0).max(inner);
+
+		if angle <= inner {
+			return 1.0;
+		} else if angle >= outer {
+			return self.outer_gain;
+		}
+
+		return 1.0 + (self.outer_gain - 1.0) * (angle - inner) / (outer - inner);
+
+	}
+
+}
+
+/// A Point in Space Sounds Come From
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Emitter {
+	pub pos: Vec3,
+	/// units per second, only used for doppler
+	pub velocity: Vec3,
+	/// facing, only used with a cone
+	pub dir: Vec3,
+	pub rolloff: Rolloff,
+	pub cone: Option<Cone>,
+	/// doppler strength, 0.0 to disable
+	pub doppler: f32,
+	/// 0.0 - 1.0, muffles and quiets the sound, e.g. from walls between it and the listener
+	pub occlusion: f32,
+}
+
+impl Emitter {
+
+	pub fn new(pos: Vec3) -> Self {
+		return Self {
+			pos: pos,
+			..Default::default()
+		};
+	}
+
+	/// move and derive velocity from how far it moved in `dt` seconds
+	pub fn move_to(&mut self, pos: Vec3, dt: f32) {
+		if dt > 0.0 {
+			self.velocity = (pos - self.pos) / dt;
+		}
+		self.pos = pos;
+	}
+
+}
+
+impl Default for Emitter {
+	fn default() -> Self {
+		return Self {
+			pos: vec3!(0),
+			velocity: vec3!(0),
+			dir: vec3!(0, 0, -1),
+			rolloff: Rolloff::default(),
+			cone: None,
+			doppler: 1.0,
+			occlusion: 0.0,
+		};
+	}
+}
+
+/// pan of a source heard from an ear facing `dir`, silent past `strength` units away
+#[deprecated(note = "use an Emitter with a Listener")]
 pub fn spatial_pan(src: Vec3, ear: Vec3, dir: Vec3, strength: f32) -> Pan {
 
 	let dist = Vec3::dist(src, ear);
@@ -14,16 +215,76 @@ pub fn spatial_pan(src: Vec3, ear: Vec3, dir: Vec3, strength: f32) -> Pan {
 	let a = dir.xz().unit();
 	let b = (src - ear).xz().unit();
 
-	// TODO: better logic?
 	let angle = (a.y.atan2(a.x) - b.y.atan2(b.x) + 3.0 * PI) % (2.0 * PI) - PI;
 
 	let pan = if angle > 0.0 {
-		Pan::new(1.0, (angle - PI / 2.0).abs().map(0.0, PI / 2.0, MIN, MAX))
+		Pan::new(1.0, (angle - PI / 2.0).abs().map(0.0, PI / 2.0, FAR_EAR, 1.0))
 	} else {
-		Pan::new((-angle - PI / 2.0).abs().map(0.0, PI / 2.0, MIN, MAX), 1.0)
+		Pan::new((-angle - PI / 2.0).abs().map(0.0, PI / 2.0, FAR_EAR, 1.0), 1.0)
 	};
 
 	return pan * volume;
 
 }
 
+// what an emitter sounds like from a listener
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Spatial {
+	pub pan: Pan,
+	pub pitch: f32,
+	pub cutoff: f32,
+}
+
+pub(super) fn spatialize(e: &Emitter, l: &Listener) -> Spatial {
+
+	let to_src = e.pos - l.pos;
+	let dist = to_src.len();
+
+	let mut gain = e.rolloff.gain(dist);
+
+	if let Some(cone) = &e.cone {
+		if dist > 0.0 && e.dir.len() > 0.0 {
+			let angle = Vec3::dot(e.dir.unit(), -to_src / dist).max(-1.0).min(1.0).acos();
+			gain *= cone.gain(angle);
+		}
+	}
+
+	let occlusion = e.occlusion.max(0.0).min(1.0);
+
+	gain *= 1.0 - occlusion * 0.5;
+
+	// -1.0 is fully left, 1.0 is fully right
+	let right = Vec3::cross(l.front, l.up);
+
+	let side = if dist > 0.0 && right.len() > 0.0 {
+		Vec3::dot(to_src / dist, right.unit())
+	} else {
+		0.0
+	};
+
+	let pan = Pan::new(
+		1.0 - side.max(0.0) * (1.0 - FAR_EAR),
+		1.0 + side.min(0.0) * (1.0 - FAR_EAR),
+	) * gain;
+
+	// velocities towards each other along the line between them raise the pitch
+	let pitch = if e.doppler > 0.0 && dist > 0.0 && l.speed_of_sound > 0.0 {
+		let dir = -to_src / dist;
+		let c = l.speed_of_sound;
+		let vl = (Vec3::dot(l.velocity, dir) * e.doppler).min(c * 0.99);
+		let vs = (Vec3::dot(e.velocity, dir) * e.doppler).min(c * 0.99);
+		((c - vl) / (c - vs)).max(MIN_PITCH).min(MAX_PITCH)
+	} else {
+		1.0
+	};
+
+	// exponential so it sounds even across the range
+	let cutoff = OPEN_CUTOFF * (OCCLUDED_CUTOFF / OPEN_CUTOFF).powf(occlusion);
+
+	return Spatial {
+		pan: pan,
+		pitch: pitch,
+		cutoff: cutoff,
+	};
+
+}
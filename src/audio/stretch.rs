@@ -0,0 +1,199 @@
+// wengwengweng
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use super::*;
+
+// ~46ms grains with 4x overlap
+const GRAIN: usize = 2048;
+const HOP: usize = GRAIN / 4;
+// how far a grain can move to line up with the last one
+const SEEK: usize = 192;
+// length and step of the similarity check
+const MATCH_LEN: usize = 512;
+const MATCH_STEP: usize = 4;
+
+// changes tempo without changing pitch by overlap-adding windowed grains read at a different rate than they're written (wsola), each grain is nudged to where it best continues the last one so they don't cancel out
+pub(super) struct Stretch {
+	window: Vec<f32>,
+	input: VecDeque<Frame>,
+	// where the next grain is read from, relative to the front of input
+	in_pos: f64,
+	// where the last grain was actually read from
+	last: Option<usize>,
+	acc: Vec<Frame>,
+	norm: Vec<f32>,
+	// frames of acc ready to output
+	ready: usize,
+	out_pos: usize,
+	// grains left to flush after the source ends
+	flush: Option<usize>,
+}
+
+impl Stretch {
+
+	pub fn new() -> Self {
+		return Self {
+			// offset by half a sample so no point is 0, a lone grain then normalizes back exactly
+			window: (0..GRAIN)
+				.map(|i| 0.5 - 0.5 * (2.0 * PI * (i as f32 + 0.5) / GRAIN as f32).cos())
+				.collect(),
+			input: VecDeque::with_capacity(GRAIN * 2),
+			in_pos: 0.0,
+			last: None,
+			acc: vec![Frame::default(); GRAIN],
+			norm: vec![0.0; GRAIN],
+			ready: 0,
+			out_pos: 0,
+			flush: None,
+		};
+	}
+
+	pub fn reset(&mut self) {
+		*self = Self::new();
+	}
+
+	// input that's read but not played yet, to continue at normal speed without a jump
+	pub fn leftover(self) -> VecDeque<Frame> {
+
+		let from = self.last
+			.map(|l| l + self.out_pos)
+			.unwrap_or(0)
+			.min(self.input.len());
+		let mut input = self.input;
+
+		input.drain(..from);
+
+		return input;
+
+	}
+
+	pub fn next(&mut self, tempo: f32, pull: &mut dyn FnMut() -> Option<Frame>) -> Option<Frame> {
+
+		if self.out_pos >= self.ready {
+			if !self.step(tempo, pull) {
+				return None;
+			}
+		}
+
+		let i = self.out_pos;
+		let n = self.norm[i];
+
+		self.out_pos += 1;
+
+		if n > 0.000001 {
+			return Some(self.acc[i] * (1.0 / n));
+		} else {
+			return Some(Frame::default());
+		}
+
+	}
+
+	// shift out the last hop and add the next grain, false if there's nothing left
+	fn step(&mut self, tempo: f32, pull: &mut dyn FnMut() -> Option<Frame>) -> bool {
+
+		if self.ready > 0 {
+			self.acc.drain(..HOP);
+			self.acc.resize(GRAIN, Frame::default());
+			self.norm.drain(..HOP);
+			self.norm.resize(GRAIN, 0.0);
+		}
+
+		let nominal = self.in_pos.round() as usize;
+
+		if self.flush.is_none() {
+			while self.input.len() < nominal + SEEK + GRAIN {
+				match pull() {
+					Some(f) => self.input.push_back(f),
+					None => {
+						self.flush = Some(GRAIN / HOP);
+						break;
+					},
+				}
+			}
+		}
+
+		let start = match self.last {
+			Some(last) => self.best_match(nominal, last + HOP),
+			None => nominal,
+		};
+
+		let mut added = false;
+
+		for i in 0..GRAIN {
+			if let Some(f) = self.input.get(start + i) {
+				let w = self.window[i];
+				self.acc[i] += *f * w;
+				self.norm[i] += w;
+				added = true;
+			}
+		}
+
+		if !added {
+			match &mut self.flush {
+				Some(0) => return false,
+				Some(n) => *n -= 1,
+				None => {},
+			}
+		}
+
+		self.in_pos += (HOP as f32 * tempo.max(0.0)) as f64;
+
+		// drop input no grain will read again
+		let used = (self.in_pos.floor() as usize)
+			.saturating_sub(SEEK)
+			.min(start)
+			.min(self.input.len());
+
+		self.input.drain(..used);
+		self.in_pos -= used as f64;
+		self.last = Some(start - used);
+
+		self.ready = HOP;
+		self.out_pos = 0;
+
+		return true;
+
+	}
+
+	// start within SEEK of nominal that looks the most like the natural continuation of the last grain
+	fn best_match(&self, nominal: usize, target: usize) -> usize {
+
+		let mono = |i: usize| {
+			return self.input
+				.get(i)
+				.map(|f| f.left + f.right)
+				.unwrap_or(0.0);
+		};
+
+		let from = nominal.saturating_sub(SEEK);
+		let to = nominal + SEEK;
+		let mut best = nominal;
+		let mut best_score = std::f32::MIN;
+
+		for start in from..=to {
+
+			let mut dot = 0.0;
+			let mut energy = 0.0;
+
+			for i in (0..MATCH_LEN).step_by(MATCH_STEP) {
+				let a = mono(start + i);
+				dot += a * mono(target + i);
+				energy += a * a;
+			}
+
+			// normalized so loud spots don't win just for being loud
+			let score = dot / energy.max(0.000001).sqrt();
+
+			if score > best_score {
+				best_score = score;
+				best = start;
+			}
+
+		}
+
+		return best;
+
+	}
+
+}
@@ -5,12 +5,14 @@ use super::*;
 use std::collections::VecDeque;
 use std::collections::HashMap;
 
+const DEFAULT_MAX_VOICES: usize = 16;
+
 pub struct BasicSynth {
-	notes: HashMap<Note, Voice>,
+	voices: Vec<Voice>,
+	max_voices: usize,
+	patches: HashMap<u8, Patch>,
 	volume: f32,
-	last_time: f32,
 	buf: VecDeque<f32>,
-	clock: f32,
 	sample_rate: u32,
 }
 
@@ -19,10 +21,10 @@ impl BasicSynth {
 	pub fn new() -> Self {
 		return BasicSynth {
 			volume: 1.0,
-			notes: hmap![],
-			last_time: 0.0,
+			voices: vec![],
+			max_voices: DEFAULT_MAX_VOICES,
+			patches: hmap![],
 			buf: VecDeque::with_capacity(120),
-			clock: 0.0,
 			sample_rate: SPEC.sample_rate,
 		};
 	}
@@ -39,27 +41,126 @@ impl BasicSynth {
 		self.volume = v.max(0.0).min(1.0);
 	}
 
-	pub fn play(&mut self, v: Voice) {
-		self.notes.insert(v.note, v);
+	/// max number of voices sounding at once, oldest voices get stolen past this
+	pub fn set_max_voices(&mut self, n: usize) {
+		self.max_voices = n.max(1);
+	}
+
+	pub fn max_voices(&self) -> usize {
+		return self.max_voices;
 	}
 
-	pub fn play_oneshot(&mut self, v: Voice) {
+	/// number of voices currently sounding
+	pub fn voice_count(&self) -> usize {
+		return self.voices.len();
+	}
+
+	/// play a voice, retriggering the same note releases the old one
+	pub fn play(&mut self, v: Voice) {
 
-		let n = v.note;
+		let (note, channel) = (v.note, v.channel);
 
-		self.notes.insert(n, v);
-		self.release(n);
+		self.release_on(channel, note);
+
+		let active = self.voices
+			.iter()
+			.filter(|v| v.fade.is_none())
+			.count();
+
+		if active >= self.max_voices {
+
+			// steal the oldest released voice, or just the oldest
+			let victim = self.voices
+				.iter()
+				.position(|v| v.fade.is_none() && v.released())
+				.or_else(|| self.voices.iter().position(|v| v.fade.is_none()));
+
+			if let Some(i) = victim {
+				self.voices[i].steal();
+			}
+
+		}
 
+		self.voices.push(v);
+
+	}
+
+	/// play a voice that releases by itself after its attack and decay
+	pub fn play_oneshot(&mut self, mut v: Voice) {
+		v.oneshot();
+		self.play(v);
 	}
 
+	/// release a note on all channels
 	pub fn release(&mut self, n: Note) {
 
-		if let Some(n) = self.notes.get_mut(&n) {
-			n.release();
+		for v in &mut self.voices {
+			if v.note == n && !v.released() {
+				v.release();
+			}
+		}
+
+	}
+
+	fn release_on(&mut self, channel: u8, n: Note) {
+
+		for v in &mut self.voices {
+			if v.note == n && v.channel == channel && !v.released() {
+				v.release();
+			}
+		}
+
+	}
+
+	/// set the patch used when notes on a channel are played through [`Instrument`](trait.Instrument.html) (e.g. by a [`Sequencer`](struct.Sequencer.html))
+	pub fn set_patch(&mut self, channel: u8, p: Patch) {
+		self.patches.insert(channel, p);
+	}
+
+	pub fn patch(&self, channel: u8) -> Option<&Patch> {
+		return self.patches.get(&channel);
+	}
+
+	/// release all voices
+	pub fn release_all(&mut self) {
+		for v in &mut self.voices {
+			v.release();
 		}
+	}
+
+}
+
+impl Instrument for BasicSynth {
+
+	fn note_on(&mut self, channel: u8, note: Note, velocity: f32) {
+
+		let patch = self.patches
+			.get(&channel)
+			.cloned()
+			.unwrap_or_default();
+
+		let volume = patch.volume * velocity;
+
+		self.play(Voice::builder(note)
+			.patch(patch)
+			.channel(channel)
+			.volume(volume)
+			.build());
 
 	}
 
+	fn note_off(&mut self, channel: u8, note: Note) {
+		self.release_on(channel, note);
+	}
+
+	fn all_notes_off(&mut self) {
+		self.release_all();
+	}
+
+	fn active(&self) -> bool {
+		return !self.voices.is_empty();
+	}
+
 }
 
 impl Source for BasicSynth {
@@ -74,31 +175,15 @@ impl Iterator for BasicSynth {
 
 	fn next(&mut self) -> Option<Self::Item> {
 
-		self.clock = (self.clock + 1.0) % self.sample_rate as f32;
-
-		let time = self.clock / self.sample_rate as f32;
-
-		let dt = if time >= self.last_time {
-			time - self.last_time
-		} else {
-			(1.0 + time) - self.last_time
-		};
-
-		self.last_time = time;
-
 		let mut sound = 0.0;
 
-		for n in self.notes.values_mut() {
-			sound += n.voice(time);
+		for v in &mut self.voices {
+			sound += v.next(self.sample_rate);
 		}
 
 		sound *= self.volume;
 
-		for n in self.notes.values_mut() {
-			n.tick(dt);
-		}
-
-		self.notes.retain(|_, n| !n.dead());
+		self.voices.retain(|v| !v.dead());
 
 		if self.buf.len() >= self.buf.capacity() {
 			self.buf.pop_front();
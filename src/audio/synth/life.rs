@@ -9,6 +9,10 @@ pub struct Life {
 	released: bool,
 	dead: bool,
 	volume: f32,
+	// level when released
+	release_from: f32,
+	// release by itself after attack and decay
+	oneshot: bool,
 	envelope: Envelope,
 }
 
@@ -21,12 +25,18 @@ impl Life {
 			released: false,
 			dead: false,
 			volume: 0.0,
+			release_from: 0.0,
+			oneshot: false,
 			envelope: e,
 		};
 	}
 
 	pub fn update(&mut self, dt: f32) {
 
+		if self.oneshot && !self.released && self.life >= self.envelope.attack + self.envelope.decay {
+			self.release();
+		}
+
 		if !self.released {
 			self.life += dt;
 		} else {
@@ -47,7 +57,7 @@ impl Life {
 			if e.release == 0.0 {
 				self.volume = 0.0;
 			} else {
-				self.volume = e.sustain - (self.afterlife / e.release) * e.sustain;
+				self.volume = self.release_from - (self.afterlife / e.release) * self.release_from;
 			}
 		}
 
@@ -61,7 +71,19 @@ impl Life {
 	}
 
 	pub fn release(&mut self) {
-		self.released = true;
+		if !self.released {
+			self.released = true;
+			self.release_from = self.volume;
+		}
+	}
+
+	/// release automatically once attack and decay are done
+	pub fn oneshot(&mut self) {
+		self.oneshot = true;
+	}
+
+	pub fn released(&self) -> bool {
+		return self.released;
 	}
 
 	pub fn dead(&self) -> bool {
@@ -1,12 +1,19 @@
 // wengwengweng
 
 //! Simple Software Synthesizer
+//!
+//! a polyphonic subtractive synth, each [`Voice`](struct.Voice.html) stacks detuned oscillators, runs through an optional filter with its own envelope, and can be modulated by an LFO, described by a [`Patch`](struct.Patch.html)
+//!
+//! standard midi files can be played on it (or any [`Instrument`](trait.Instrument.html)) with a [`Sequencer`](struct.Sequencer.html)
 
 export!(envelope);
 export!(life);
 export!(wave);
+export!(patch);
 export!(voice);
 export!(basic);
+export!(smf);
+export!(sequencer);
 
 use super::*;
 use music::*;
@@ -0,0 +1,173 @@
+// wengwengweng
+
+use super::*;
+
+/// One Oscillator Layer of a [`Patch`](struct.Patch.html)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Oscillator {
+	pub waveform: Waveform,
+	pub volume: f32,
+	/// offset in semitones
+	pub transpose: i32,
+	/// offset in cents
+	pub detune: f32,
+	/// number of stacked copies
+	pub unison: usize,
+	/// total detune spread in cents of the unison copies
+	pub spread: f32,
+	/// high portion of a square cycle, 0.5 is a square wave
+	pub pulse_width: f32,
+}
+
+impl Oscillator {
+
+	pub fn new(w: Waveform) -> Self {
+		return Self {
+			waveform: w,
+			volume: 1.0,
+			transpose: 0,
+			detune: 0.0,
+			unison: 1,
+			spread: 0.0,
+			pulse_width: 0.5,
+		};
+	}
+
+	pub fn volume(mut self, v: f32) -> Self {
+		self.volume = v;
+		return self;
+	}
+
+	pub fn transpose(mut self, t: i32) -> Self {
+		self.transpose = t;
+		return self;
+	}
+
+	pub fn detune(mut self, d: f32) -> Self {
+		self.detune = d;
+		return self;
+	}
+
+	pub fn unison(mut self, n: usize, spread: f32) -> Self {
+		self.unison = n.max(1);
+		self.spread = spread;
+		return self;
+	}
+
+	pub fn pulse_width(mut self, pw: f32) -> Self {
+		self.pulse_width = pw;
+		return self;
+	}
+
+}
+
+impl Default for Oscillator {
+	fn default() -> Self {
+		return Self::new(Waveform::Sine);
+	}
+}
+
+/// Low Frequency Oscillator, restarts on each note
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Lfo {
+	pub waveform: Waveform,
+	pub freq: f32,
+	/// pitch depth in semitones
+	pub pitch: f32,
+	/// amplitude depth, 0.0 - 1.0
+	pub amp: f32,
+	/// filter cutoff depth in octaves
+	pub cutoff: f32,
+	/// pulse width depth
+	pub pwm: f32,
+}
+
+impl Default for Lfo {
+	fn default() -> Self {
+		return Self {
+			waveform: Waveform::Sine,
+			freq: 5.0,
+			pitch: 0.0,
+			amp: 0.0,
+			cutoff: 0.0,
+			pwm: 0.0,
+		};
+	}
+}
+
+/// Per Voice Filter with its Own Envelope
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VoiceFilter {
+	pub ty: FilterType,
+	pub cutoff: f32,
+	pub q: f32,
+	pub envelope: Envelope,
+	/// how many octaves the envelope opens the cutoff
+	pub amount: f32,
+}
+
+impl VoiceFilter {
+
+	pub fn new(ty: FilterType, cutoff: f32, q: f32) -> Self {
+		return Self {
+			ty: ty,
+			cutoff: cutoff,
+			q: q,
+			envelope: Envelope {
+				attack: 0.0,
+				decay: 0.0,
+				sustain: 1.0,
+				release: 0.0,
+			},
+			amount: 0.0,
+		};
+	}
+
+	pub fn envelope(mut self, e: Envelope, amount: f32) -> Self {
+		self.envelope = e;
+		self.amount = amount;
+		return self;
+	}
+
+}
+
+/// A Reusable Description of a [`Voice`](struct.Voice.html)
+///
+/// ```ignore
+/// let pad = Patch {
+/// 	oscs: vec![
+/// 		Oscillator::new(Waveform::Saw).unison(5, 30.0),
+/// 		Oscillator::new(Waveform::Square).transpose(-12).volume(0.5),
+/// 	],
+/// 	filter: Some(VoiceFilter::new(FilterType::Lowpass, 800.0, 1.0)),
+/// 	..Patch::default()
+/// };
+///
+/// synth.play(Voice::builder(note).patch(pad.clone()).build());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Patch {
+	pub oscs: Vec<Oscillator>,
+	pub envelope: Envelope,
+	pub lfo: Lfo,
+	pub filter: Option<VoiceFilter>,
+	pub volume: f32,
+}
+
+impl Default for Patch {
+	fn default() -> Self {
+		return Self {
+			oscs: vec![Oscillator::default()],
+			envelope: Envelope {
+				attack: 0.01,
+				decay: 0.01,
+				sustain: 1.0,
+				release: 1.0,
+			},
+			lfo: Lfo::default(),
+			filter: None,
+			volume: 1.0,
+		};
+	}
+}
+
@@ -0,0 +1,260 @@
+// wengwengweng
+
+use std::time::Duration;
+
+use super::*;
+
+/// Something a [`Sequencer`](struct.Sequencer.html) Can Play Notes On
+pub trait Instrument: Source {
+	/// velocity is 0.0 - 1.0
+	fn note_on(&mut self, channel: u8, note: Note, velocity: f32);
+	fn note_off(&mut self, channel: u8, note: Note);
+	/// release every sounding note, used when looping / seeking
+	fn all_notes_off(&mut self);
+	/// if it's still making sound, a finished non-looping sequencer stops after this returns false
+	fn active(&self) -> bool {
+		return true;
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SeqEvent {
+	NoteOn(u8, u8, u8),
+	NoteOff(u8, u8),
+}
+
+/// Plays a [`MidiFile`](struct.MidiFile.html) on an [`Instrument`](trait.Instrument.html)
+///
+/// ```ignore
+/// let song = MidiFile::from_bytes(include_bytes!("res/song.mid"))?;
+/// let mut synth = BasicSynth::new();
+///
+/// synth.set_patch(0, lead);
+/// synth.set_patch(1, bass);
+///
+/// let seq = Arc::new(Mutex::new(Sequencer::new(&song, synth)));
+///
+/// seq.lock().unwrap().set_looping(true);
+/// d.audio.play(seq.clone())?;
+/// ```
+pub struct Sequencer<I: Instrument> {
+	instrument: I,
+	// (time in secs, event), sorted by time
+	events: Vec<(f64, SeqEvent)>,
+	cur: usize,
+	time: f64,
+	duration: f64,
+	tempo_scale: f64,
+	looping: bool,
+	loop_start: f64,
+	loop_end: Option<f64>,
+	paused: bool,
+}
+
+impl<I: Instrument> Sequencer<I> {
+
+	pub fn new(file: &MidiFile, instrument: I) -> Self {
+
+		let mut events = vec![];
+
+		for track in file.tracks() {
+			for e in &track.events {
+
+				let ev = match e.kind {
+					MidiEventKind::NoteOn { channel, note, velocity } => SeqEvent::NoteOn(channel, note, velocity),
+					MidiEventKind::NoteOff { channel, note, .. } => SeqEvent::NoteOff(channel, note),
+					_ => continue,
+				};
+
+				events.push((file.tick_to_secs(e.tick), ev));
+
+			}
+		}
+
+		// note offs first so a note ending and restarting at the same time retriggers
+		events.sort_by(|(t1, e1), (t2, e2)| {
+			let is_on = |e: &SeqEvent| match e {
+				SeqEvent::NoteOn(..) => 1,
+				SeqEvent::NoteOff(..) => 0,
+			};
+			return t1
+				.partial_cmp(t2)
+				.unwrap_or(std::cmp::Ordering::Equal)
+				.then(is_on(e1).cmp(&is_on(e2)));
+		});
+
+		return Self {
+			instrument: instrument,
+			events: events,
+			cur: 0,
+			time: 0.0,
+			duration: file.duration(),
+			tempo_scale: 1.0,
+			looping: false,
+			loop_start: 0.0,
+			loop_end: None,
+			paused: false,
+		};
+
+	}
+
+	pub fn instrument(&self) -> &I {
+		return &self.instrument;
+	}
+
+	pub fn instrument_mut(&mut self) -> &mut I {
+		return &mut self.instrument;
+	}
+
+	/// playback speed, 2.0 plays twice as fast
+	pub fn set_tempo_scale(&mut self, s: f32) {
+		self.tempo_scale = s.max(0.0) as f64;
+	}
+
+	pub fn tempo_scale(&self) -> f32 {
+		return self.tempo_scale as f32;
+	}
+
+	/// loop between the loop points, the whole song by default
+	pub fn set_looping(&mut self, l: bool) {
+		self.looping = l;
+	}
+
+	pub fn looping(&self) -> bool {
+		return self.looping;
+	}
+
+	/// set loop range in song time, `None` end loops at the end of the song, errors if the range is empty
+	pub fn set_loop_points(&mut self, start: Duration, end: Option<Duration>) -> Result<()> {
+
+		let start = start.as_secs_f64();
+		let end = end.map(|e| e.as_secs_f64());
+
+		if end.unwrap_or(self.duration) <= start {
+			return Err(format!("loop end must be after loop start ({:.3}s)", start));
+		}
+
+		self.loop_start = start;
+		self.loop_end = end;
+
+		return Ok(());
+
+	}
+
+	pub fn set_paused(&mut self, p: bool) {
+		if p {
+			self.instrument.all_notes_off();
+		}
+		self.paused = p;
+	}
+
+	pub fn paused(&self) -> bool {
+		return self.paused;
+	}
+
+	/// current position in song time
+	pub fn position(&self) -> Duration {
+		return Duration::from_secs_f64(self.time);
+	}
+
+	pub fn duration(&self) -> Duration {
+		return Duration::from_secs_f64(self.duration);
+	}
+
+	/// jump to a position in song time, notes held over the position won't sound
+	pub fn seek(&mut self, pos: Duration) {
+
+		self.instrument.all_notes_off();
+		self.time = pos.as_secs_f64();
+		self.cur = self.events
+			.iter()
+			.position(|(t, _)| *t >= self.time)
+			.unwrap_or(self.events.len());
+
+	}
+
+	pub fn finished(&self) -> bool {
+		return !self.looping && self.cur >= self.events.len();
+	}
+
+	fn loop_end_secs(&self) -> f64 {
+		return self.loop_end.unwrap_or(self.duration);
+	}
+
+}
+
+impl<I: Instrument> Iterator for Sequencer<I> {
+
+	type Item = Frame;
+
+	fn next(&mut self) -> Option<Self::Item> {
+
+		if !self.paused {
+
+			// dispatch everything due before this sample
+			while let Some((t, e)) = self.events.get(self.cur) {
+
+				if *t > self.time || (self.looping && *t >= self.loop_end_secs()) {
+					break;
+				}
+
+				match *e {
+					SeqEvent::NoteOn(ch, note, vel) => {
+						self.instrument.note_on(ch, Note::new(note as i32), vel as f32 / 127.0);
+					},
+					SeqEvent::NoteOff(ch, note) => {
+						self.instrument.note_off(ch, Note::new(note as i32));
+					},
+				}
+
+				self.cur += 1;
+
+			}
+
+			self.time += self.tempo_scale / self.instrument.sample_rate() as f64;
+
+			let end = self.loop_end_secs();
+
+			if self.looping && self.time >= end && end > self.loop_start {
+				let start = self.loop_start;
+				self.seek(Duration::from_secs_f64(start));
+			}
+
+		}
+
+		if self.finished() && !self.instrument.active() {
+			return None;
+		}
+
+		return self.instrument.next();
+
+	}
+
+}
+
+impl<I: Instrument> Source for Sequencer<I> {
+
+	fn sample_rate(&self) -> u32 {
+		return self.instrument.sample_rate();
+	}
+
+	fn seek_start(&mut self) -> Result<()> {
+		self.seek(Duration::from_secs(0));
+		return Ok(());
+	}
+
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+		Sequencer::seek(self, pos);
+		return Ok(());
+	}
+
+	fn position(&self) -> Option<Duration> {
+		return Some(Sequencer::position(self));
+	}
+
+	fn duration(&self) -> Option<Duration> {
+		return Some(Sequencer::duration(self));
+	}
+
+}
+
@@ -0,0 +1,421 @@
+// wengwengweng
+
+// https://www.music.mcgill.ca/~ich/classes/mumt306/StandardMIDIfileformat.html
+
+use super::*;
+
+// 120 bpm
+const DEFAULT_TEMPO: u32 = 500_000;
+
+/// Time Unit of a [`MidiFile`](struct.MidiFile.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Division {
+	/// ticks per quarter note
+	Metrical(u16),
+	/// frames per second and ticks per frame
+	Timecode(u8, u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MidiEventKind {
+	NoteOn {
+		channel: u8,
+		note: u8,
+		velocity: u8,
+	},
+	NoteOff {
+		channel: u8,
+		note: u8,
+		velocity: u8,
+	},
+	Control {
+		channel: u8,
+		id: u8,
+		value: u8,
+	},
+	Program {
+		channel: u8,
+		program: u8,
+	},
+	/// 14-bit, 8192 is center
+	PitchBend {
+		channel: u8,
+		value: u16,
+	},
+	/// microseconds per quarter note
+	Tempo(u32),
+	EndOfTrack,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiEvent {
+	/// absolute time in ticks from start of track
+	pub tick: u64,
+	pub kind: MidiEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiTrack {
+	pub name: Option<String>,
+	pub events: Vec<MidiEvent>,
+}
+
+/// Parsed Standard MIDI File (type 0 / 1)
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiFile {
+	format: u16,
+	division: Division,
+	tracks: Vec<MidiTrack>,
+	// (tick, microseconds per quarter note)
+	tempo_map: Vec<(u64, u32)>,
+}
+
+struct Reader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+
+	fn new(data: &'a [u8]) -> Self {
+		return Self {
+			data: data,
+			pos: 0,
+		};
+	}
+
+	fn eof(&self) -> bool {
+		return self.pos >= self.data.len();
+	}
+
+	fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+
+		let data = self.data
+			.get(self.pos..self.pos + n)
+			.ok_or(format!("unexpected end of midi data"))?;
+
+		self.pos += n;
+
+		return Ok(data);
+
+	}
+
+	fn u8(&mut self) -> Result<u8> {
+		return Ok(self.bytes(1)?[0]);
+	}
+
+	fn peek(&self) -> Result<u8> {
+		return self.data
+			.get(self.pos)
+			.cloned()
+			.ok_or(format!("unexpected end of midi data"));
+	}
+
+	fn u16(&mut self) -> Result<u16> {
+		let b = self.bytes(2)?;
+		return Ok(u16::from_be_bytes([b[0], b[1]]));
+	}
+
+	fn u32(&mut self) -> Result<u32> {
+		let b = self.bytes(4)?;
+		return Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+	}
+
+	// variable length quantity, max 4 bytes
+	fn vlq(&mut self) -> Result<u32> {
+
+		let mut v = 0;
+
+		for _ in 0..4 {
+
+			let b = self.u8()?;
+
+			v = (v << 7) | (b & 0x7f) as u32;
+
+			if b & 0x80 == 0 {
+				return Ok(v);
+			}
+
+		}
+
+		return Err(format!("invalid midi variable length number"));
+
+	}
+
+}
+
+fn parse_track(data: &[u8]) -> Result<MidiTrack> {
+
+	let mut r = Reader::new(data);
+	let mut tick = 0;
+	let mut status = None;
+	let mut name = None;
+	let mut events = vec![];
+
+	while !r.eof() {
+
+		tick += r.vlq()? as u64;
+
+		// running status reuses the last channel status byte
+		let s = if r.peek()? & 0x80 != 0 {
+			r.u8()?
+		} else {
+			status.ok_or(format!("midi running status without previous status"))?
+		};
+
+		let kind = match s {
+
+			0xff => {
+
+				let ty = r.u8()?;
+				let len = r.vlq()? as usize;
+				let data = r.bytes(len)?;
+
+				match ty {
+					0x03 => {
+						name = Some(String::from_utf8_lossy(data).into_owned());
+						None
+					},
+					0x51 if len == 3 => {
+						Some(MidiEventKind::Tempo(u32::from_be_bytes([0, data[0], data[1], data[2]])))
+					},
+					0x2f => {
+						events.push(MidiEvent {
+							tick: tick,
+							kind: MidiEventKind::EndOfTrack,
+						});
+						break;
+					},
+					_ => None,
+				}
+
+			},
+
+			// sysex
+			0xf0 | 0xf7 => {
+				let len = r.vlq()? as usize;
+				r.bytes(len)?;
+				None
+			},
+
+			0x80..=0xef => {
+
+				status = Some(s);
+
+				let channel = s & 0x0f;
+
+				match s & 0xf0 {
+					0x80 => {
+						let note = r.u8()?;
+						let velocity = r.u8()?;
+						Some(MidiEventKind::NoteOff {
+							channel: channel,
+							note: note,
+							velocity: velocity,
+						})
+					},
+					0x90 => {
+						let note = r.u8()?;
+						let velocity = r.u8()?;
+						// note on with 0 velocity is note off
+						if velocity == 0 {
+							Some(MidiEventKind::NoteOff {
+								channel: channel,
+								note: note,
+								velocity: 0,
+							})
+						} else {
+							Some(MidiEventKind::NoteOn {
+								channel: channel,
+								note: note,
+								velocity: velocity,
+							})
+						}
+					},
+					0xb0 => {
+						let id = r.u8()?;
+						let value = r.u8()?;
+						Some(MidiEventKind::Control {
+							channel: channel,
+							id: id,
+							value: value,
+						})
+					},
+					0xc0 => {
+						let program = r.u8()?;
+						Some(MidiEventKind::Program {
+							channel: channel,
+							program: program,
+						})
+					},
+					0xe0 => {
+						let lsb = r.u8()? as u16;
+						let msb = r.u8()? as u16;
+						Some(MidiEventKind::PitchBend {
+							channel: channel,
+							value: (msb << 7) | lsb,
+						})
+					},
+					// polyphonic / channel pressure
+					0xa0 => {
+						r.bytes(2)?;
+						None
+					},
+					_ => {
+						r.bytes(1)?;
+						None
+					},
+				}
+
+			},
+
+			_ => return Err(format!("unknown midi status byte 0x{:x}", s)),
+
+		};
+
+		if let Some(kind) = kind {
+			events.push(MidiEvent {
+				tick: tick,
+				kind: kind,
+			});
+		}
+
+	}
+
+	return Ok(MidiTrack {
+		name: name,
+		events: events,
+	});
+
+}
+
+impl MidiFile {
+
+	pub fn from_bytes(data: &[u8]) -> Result<Self> {
+
+		let mut r = Reader::new(data);
+
+		if r.bytes(4)? != b"MThd" {
+			return Err(format!("not a midi file"));
+		}
+
+		let len = r.u32()? as usize;
+		let format = r.u16()?;
+		let ntracks = r.u16()?;
+		let division = r.u16()?;
+
+		// future versions may have a longer header
+		r.bytes(len.saturating_sub(6))?;
+
+		if format > 1 {
+			return Err(format!("midi format {} not supported", format));
+		}
+
+		let division = if division & 0x8000 != 0 {
+
+			// frames per second is stored negated
+			let fps = ((division >> 8) as i8).wrapping_neg() as u8;
+
+			if ![24, 25, 29, 30].contains(&fps) {
+				return Err(format!("invalid smpte frame rate {}", fps));
+			}
+
+			Division::Timecode(fps, (division & 0xff) as u8)
+
+		} else {
+			Division::Metrical(division.max(1))
+		};
+
+		let mut tracks = Vec::with_capacity(ntracks as usize);
+
+		while tracks.len() < ntracks as usize && !r.eof() {
+
+			let id = r.bytes(4)?;
+			let len = r.u32()? as usize;
+			let data = r.bytes(len)?;
+
+			// unknown chunks should be skipped
+			if id == b"MTrk" {
+				tracks.push(parse_track(data)?);
+			}
+
+		}
+
+		let mut tempo_map = tracks
+			.iter()
+			.flat_map(|t| t.events.iter())
+			.filter_map(|e| match e.kind {
+				MidiEventKind::Tempo(t) => Some((e.tick, t)),
+				_ => None,
+			})
+			.collect::<Vec<(u64, u32)>>();
+
+		tempo_map.sort_by_key(|(tick, _)| *tick);
+
+		return Ok(Self {
+			format: format,
+			division: division,
+			tracks: tracks,
+			tempo_map: tempo_map,
+		});
+
+	}
+
+	pub fn format(&self) -> u16 {
+		return self.format;
+	}
+
+	pub fn division(&self) -> Division {
+		return self.division;
+	}
+
+	pub fn tracks(&self) -> &[MidiTrack] {
+		return &self.tracks;
+	}
+
+	/// convert an absolute tick to seconds, following tempo changes
+	pub fn tick_to_secs(&self, tick: u64) -> f64 {
+
+		let tpq = match self.division {
+			Division::Metrical(tpq) => tpq as f64,
+			Division::Timecode(fps, tpf) => {
+				return tick as f64 / (fps.max(1) as f64 * tpf.max(1) as f64);
+			},
+		};
+
+		let mut secs = 0.0;
+		let mut last_tick = 0;
+		let mut tempo = DEFAULT_TEMPO;
+
+		for (t, new_tempo) in &self.tempo_map {
+
+			if *t >= tick {
+				break;
+			}
+
+			secs += (*t - last_tick) as f64 * tempo as f64 / 1_000_000.0 / tpq;
+			last_tick = *t;
+			tempo = *new_tempo;
+
+		}
+
+		return secs + (tick - last_tick) as f64 * tempo as f64 / 1_000_000.0 / tpq;
+
+	}
+
+	/// length in seconds, until the last event of the longest track
+	pub fn duration(&self) -> f64 {
+
+		let last = self.tracks
+			.iter()
+			.filter_map(|t| t.events.last())
+			.map(|e| e.tick)
+			.max()
+			.unwrap_or(0);
+
+		return self.tick_to_secs(last);
+
+	}
+
+}
+
@@ -2,105 +2,256 @@
 
 use super::*;
 
+// time for a stolen voice to fade out
+const STEAL_TIME: f32 = 0.005;
+
+// a single running copy of an oscillator
+#[derive(Clone, Debug)]
+struct Partial {
+	osc: Osc,
+	gain: f32,
+	ratio: f32,
+}
+
 #[derive(Clone, Debug)]
 pub struct Voice {
 	pub(super) life: Life,
-	pub(super) waveform: Waveform,
 	pub(super) note: Note,
+	pub(super) channel: u8,
 	pub(super) volume: f32,
+	partials: Vec<Partial>,
+	lfo: Lfo,
+	lfo_phase: f32,
+	filter: Option<(VoiceFilter, Life, Biquad)>,
+	pub(super) fade: Option<f32>,
 }
 
 impl Voice {
 
 	pub fn builder(note: Note) -> VoiceBuilder {
 		return VoiceBuilder {
-			volume: 1.0,
 			note: note,
-			waveform: Waveform::Sine,
-			envelope: Envelope {
-				attack: 0.01,
-				decay: 0.01,
-				sustain: 1.0,
-				release: 1.0,
-			},
+			channel: 0,
+			patch: Patch::default(),
 		};
 	}
 
-	pub(super) fn tick(&mut self, dt: f32) {
-		self.life.update(dt);
+	pub fn note(&self) -> Note {
+		return self.note;
+	}
+
+	pub fn channel(&self) -> u8 {
+		return self.channel;
 	}
 
-	pub(super) fn voice(&self, time: f32) -> f32 {
+	pub(super) fn next(&mut self, sample_rate: u32) -> f32 {
+
+		let dt = 1.0 / sample_rate as f32;
+		let lfo = self.lfo.waveform.at(self.lfo_phase, 0.0, 0.5);
+		let freq = self.note.freq() * f32::powf(2.0, lfo * self.lfo.pitch / 12.0);
+		let mut sound = 0.0;
+
+		self.lfo_phase = (self.lfo_phase + self.lfo.freq * dt).fract();
+
+		for p in &mut self.partials {
+
+			let pw = p.osc.pulse_width + lfo * self.lfo.pwm;
+
+			sound += p.osc.next_pw(freq * p.ratio, sample_rate, pw) * p.gain;
+
+		}
+
+		if let Some((f, life, biquad)) = &mut self.filter {
+
+			let octaves = f.amount * life.volume() + self.lfo.cutoff * lfo;
+
+			biquad.set_freq_now(f.cutoff * f32::powf(2.0, octaves));
+			sound = biquad.process_mono(sound);
+			life.update(dt);
 
-		let volume = self.life.volume() * self.volume;
-		let wav = self.waveform.osc(self.note.freq().floor(), time);
+		}
 
-		return volume * wav;
+		let amp = 1.0 - self.lfo.amp * (0.5 - 0.5 * lfo);
+		let fade = self.fade.unwrap_or(1.0);
+
+		sound *= self.life.volume() * self.volume * amp * fade;
+
+		self.life.update(dt);
+
+		if let Some(fade) = &mut self.fade {
+			*fade = (*fade - dt / STEAL_TIME).max(0.0);
+		}
+
+		return sound;
 
 	}
 
 	pub(super) fn dead(&self) -> bool {
-		return self.life.dead();
+		return self.life.dead() || self.fade == Some(0.0);
+	}
+
+	pub(super) fn released(&self) -> bool {
+		return self.life.released() || self.fade.is_some();
 	}
 
 	pub(super) fn release(&mut self) {
+
 		self.life.release();
+
+		if let Some((_, life, _)) = &mut self.filter {
+			life.release();
+		}
+
+	}
+
+	// go through attack and decay then release, without waiting for a note off
+	pub(super) fn oneshot(&mut self) {
+
+		self.life.oneshot();
+
+		if let Some((_, life, _)) = &mut self.filter {
+			life.oneshot();
+		}
+
+	}
+
+	// quickly fade out to make room for a new voice
+	pub(super) fn steal(&mut self) {
+		if self.fade.is_none() {
+			self.fade = Some(1.0);
+		}
 	}
 
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct VoiceBuilder {
 	note: Note,
-	envelope: Envelope,
-	waveform: Waveform,
-	volume: f32,
+	channel: u8,
+	patch: Patch,
 }
 
 impl VoiceBuilder {
 
+	/// use all settings from a patch
+	pub fn patch(mut self, p: Patch) -> Self {
+		self.patch = p;
+		return self;
+	}
+
+	/// notes only retrigger / release voices on the same channel
+	pub fn channel(mut self, c: u8) -> Self {
+		self.channel = c;
+		return self;
+	}
+
 	pub fn envelope(mut self, e: Envelope) -> Self {
-		self.envelope = e;
+		self.patch.envelope = e;
 		return self;
 	}
 
 	pub fn attack(mut self, a: f32) -> Self {
-		self.envelope.attack = a;
+		self.patch.envelope.attack = a;
 		return self;
 	}
 
 	pub fn decay(mut self, d: f32) -> Self {
-		self.envelope.decay = d;
+		self.patch.envelope.decay = d;
 		return self;
 	}
 
 	pub fn sustain(mut self, s: f32) -> Self {
-		self.envelope.sustain = s;
+		self.patch.envelope.sustain = s;
 		return self;
 	}
 
 	pub fn release(mut self, r: f32) -> Self {
-		self.envelope.release = r;
+		self.patch.envelope.release = r;
 		return self;
 	}
 
+	/// set the waveform of the first oscillator
 	pub fn waveform(mut self, w: Waveform) -> Self {
-		self.waveform = w;
+		match self.patch.oscs.first_mut() {
+			Some(o) => o.waveform = w,
+			None => self.patch.oscs.push(Oscillator::new(w)),
+		}
+		return self;
+	}
+
+	/// add an oscillator layer
+	pub fn osc(mut self, o: Oscillator) -> Self {
+		self.patch.oscs.push(o);
+		return self;
+	}
+
+	pub fn lfo(mut self, l: Lfo) -> Self {
+		self.patch.lfo = l;
+		return self;
+	}
+
+	pub fn filter(mut self, f: VoiceFilter) -> Self {
+		self.patch.filter = Some(f);
 		return self;
 	}
 
 	pub fn volume(mut self, v: f32) -> Self {
-		self.volume = v;
+		self.patch.volume = v;
 		return self;
 	}
 
 	pub fn build(self) -> Voice {
 
+		let mut partials = vec![];
+
+		for o in &self.patch.oscs {
+
+			let n = o.unison.max(1);
+			let gain = o.volume / (n as f32).sqrt();
+
+			for i in 0..n {
+
+				let offset = if n > 1 {
+					o.spread * (i as f32 / (n - 1) as f32 - 0.5)
+				} else {
+					0.0
+				};
+
+				let cents = o.transpose as f32 * 100.0 + o.detune + offset;
+
+				let mut osc = Osc::new(o.waveform);
+
+				osc.pulse_width = o.pulse_width;
+
+				// random start phase so unison copies don't cancel out
+				if n > 1 {
+					osc = osc.with_phase(math::rand(0.0, 1.0));
+				}
+
+				partials.push(Partial {
+					osc: osc,
+					gain: gain,
+					ratio: f32::powf(2.0, cents / 1200.0),
+				});
+
+			}
+
+		}
+
+		let filter = self.patch.filter.map(|f| {
+			return (f, Life::new(f.envelope), Biquad::new(f.ty, f.cutoff, f.q, 0.0));
+		});
+
 		return Voice {
-			volume: self.volume,
+			volume: self.patch.volume,
 			note: self.note,
-			waveform: self.waveform,
-			life: Life::new(self.envelope),
+			channel: self.channel,
+			life: Life::new(self.patch.envelope),
+			partials: partials,
+			lfo: self.patch.lfo,
+			lfo_phase: 0.0,
+			filter: filter,
+			fade: None,
 		};
 
 	}
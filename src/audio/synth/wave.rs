@@ -16,31 +16,216 @@ fn w(f: f32) -> f32 {
 	return f * 2.0 * PI;
 }
 
+// polynomial band limited step residual, t is phase since the step, dt is phase increment per sample
+fn poly_blep(t: f32, dt: f32) -> f32 {
+
+	if t < dt {
+		let x = t / dt;
+		return x + x - x * x - 1.0;
+	} else if t > 1.0 - dt {
+		let x = (t - 1.0) / dt;
+		return x * x + x + x + 1.0;
+	}
+
+	return 0.0;
+
+}
+
+// polynomial band limited ramp residual, for slope changes
+fn poly_blamp(t: f32, dt: f32) -> f32 {
+
+	if t < dt {
+		let x = t / dt - 1.0;
+		return -x * x * x / 3.0;
+	} else if t > 1.0 - dt {
+		let x = (t - 1.0) / dt + 1.0;
+		return x * x * x / 3.0;
+	}
+
+	return 0.0;
+
+}
+
 impl Waveform {
+
+	/// value at time t, t should keep growing (not wrap) for a continuous phase
 	pub fn osc(&self, freq: f32, t: f32) -> f32 {
+		return self.at((freq as f64 * t as f64).fract() as f32, 0.0, 0.5);
+	}
+
+	/// value at phase (0.0 - 1.0), dt is phase increment per sample for band limiting (0.0 for none), pw is the high portion of a square cycle
+	pub fn at(&self, phase: f32, dt: f32, pw: f32) -> f32 {
+
+		let dt = dt.abs().min(0.5);
+
 		return match self {
-			Waveform::Sine => f32::sin(w(freq) * t),
+			Waveform::Sine => f32::sin(w(phase)),
 			Waveform::Square => {
-				if Waveform::Sine.osc(freq, t) > 0.0 {
+
+				let naive = if phase < pw {
 					1.0
 				} else {
 					-1.0
+				};
+
+				if dt == 0.0 {
+					naive
+				} else {
+					naive + poly_blep(phase, dt) - poly_blep((phase - pw + 1.0).fract(), dt)
 				}
+
+			},
+			Waveform::Triangle => {
+
+				let naive = 4.0 * ((phase + 0.75).fract() - 0.5).abs() - 1.0;
+
+				if dt == 0.0 {
+					naive
+				} else {
+					// corners at 0.25 (slope -8) and 0.75 (slope +8)
+					naive
+						- 4.0 * dt * poly_blamp((phase + 0.75).fract(), dt)
+						+ 4.0 * dt * poly_blamp((phase + 0.25).fract(), dt)
+				}
+
 			},
-			Waveform::Triangle => f32::asin(Waveform::Sine.osc(freq, t)) * 2.0 / PI,
-			Waveform::Saw => (2.0 / PI) * (freq * PI * (t % (1.0 / freq)) - PI / 2.0),
-			Waveform::Noise => {
-				if freq == 0.0 {
-					0.0
+			Waveform::Saw => {
+				if dt == 0.0 {
+					2.0 * phase - 1.0
 				} else {
-					math::rand(-1.0, 1.0)
+					2.0 * phase - 1.0 - poly_blep(phase, dt)
 				}
 			},
+			Waveform::Noise => math::rand(-1.0, 1.0),
 		};
+
 	}
+
 }
 
 pub fn osc(wav: Waveform, freq: f32, t: f32) -> f32 {
 	return wav.osc(freq, t);
 }
 
+/// Band Limited Phase Accumulator Oscillator
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Osc {
+	pub waveform: Waveform,
+	pub pulse_width: f32,
+	phase: f32,
+}
+
+impl Osc {
+
+	pub fn new(w: Waveform) -> Self {
+		return Self {
+			waveform: w,
+			pulse_width: 0.5,
+			phase: 0.0,
+		};
+	}
+
+	/// start from phase (0.0 - 1.0)
+	pub fn with_phase(mut self, p: f32) -> Self {
+		self.phase = p.fract();
+		return self;
+	}
+
+	pub fn phase(&self) -> f32 {
+		return self.phase;
+	}
+
+	/// get current value and advance by 1 sample
+	pub fn next(&mut self, freq: f32, sample_rate: u32) -> f32 {
+		return self.next_pw(freq, sample_rate, self.pulse_width);
+	}
+
+	/// like next(), but with a modulated pulse width
+	pub fn next_pw(&mut self, freq: f32, sample_rate: u32, pw: f32) -> f32 {
+
+		let dt = freq / sample_rate as f32;
+		let v = self.waveform.at(self.phase, dt, pw.max(0.01).min(0.99));
+
+		self.phase = (self.phase + dt).rem_euclid(1.0);
+
+		return v;
+
+	}
+
+}
+
+#[test]
+fn band_limited() {
+
+	// magnitude of a frequency in a signal
+	let dft = |buf: &[f32], freq: f32| {
+
+		let mut re = 0.0;
+		let mut im = 0.0;
+
+		for (i, v) in buf.iter().enumerate() {
+			let a = w(freq) * i as f32 / SPEC.sample_rate as f32;
+			re += *v as f64 * a.cos() as f64;
+			im -= *v as f64 * a.sin() as f64;
+		}
+
+		return (re * re + im * im).sqrt() as f32 * 2.0 / buf.len() as f32;
+
+	};
+
+	// 1 second of a high note, ~3.1k has lots of harmonics above nyquist
+	let freq = Note::new(103).freq();
+	let len = SPEC.sample_rate as usize;
+
+	for wav in &[Waveform::Saw, Waveform::Square, Waveform::Triangle] {
+
+		let mut o = Osc::new(*wav);
+		let buf = (0..len)
+			.map(|_| o.next(freq, SPEC.sample_rate))
+			.collect::<Vec<f32>>();
+
+		let fundamental = dft(&buf, freq);
+
+		assert!(fundamental > 0.5, "{:?}: fundamental too quiet: {}", wav, fundamental);
+
+		// harmonics above nyquist fold back to non harmonic frequencies, check the worst of them that land in the most audible range, naive waveforms are around -20db here
+		let nyquist = SPEC.sample_rate as f32 / 2.0;
+		let mut worst: f32 = 0.0;
+
+		for k in 7..=40 {
+
+			let f = freq * k as f32;
+			let folded = (f % SPEC.sample_rate as f32 - nyquist).abs();
+			let folded = nyquist - folded;
+
+			// skip aliases landing near real harmonics
+			let near = (folded / freq).fract();
+
+			if near < 0.05 || near > 0.95 || folded > 10000.0 {
+				continue;
+			}
+
+			worst = worst.max(dft(&buf, folded));
+
+		}
+
+		let db = 20.0 * (worst / fundamental).log10();
+
+		assert!(db < -40.0, "{:?}: alias at {}db", wav, db);
+
+	}
+
+	// phase doesn't jump when the old 1 second clock would wrap
+	let mut o = Osc::new(Waveform::Sine);
+	let freq = 440.5;
+	let mut last = o.next(freq, SPEC.sample_rate);
+	let max_step = w(freq) / SPEC.sample_rate as f32 * 1.01;
+
+	for _ in 0..len * 2 {
+		let v = o.next(freq, SPEC.sample_rate);
+		assert!((v - last).abs() <= max_step);
+		last = v;
+	}
+
+}
+
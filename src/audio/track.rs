@@ -6,7 +6,7 @@ use super::*;
 #[derive(Clone)]
 pub struct Track {
 // 	id: SourceID,
-	src: Arc<Mutex<Decoder<Cursor<Vec<u8>>>>>,
+	src: Arc<Mutex<Resampler<Decoder<Cursor<Vec<u8>>>>>>,
 	ctrl: Arc<Mutex<Control>>,
 	mixer: Arc<Mutex<Mixer>>,
 }
@@ -17,6 +17,7 @@ impl Track {
 	pub fn from_bytes(ctx: &Audio, data: &[u8]) -> Result<Self> {
 
 		let src = Decoder::new(Cursor::new(data.to_owned()))?;
+		let src = Resampler::new(src, SPEC.sample_rate, ctx.resample_quality());
 		let src = Arc::new(Mutex::new(src));
 
 		let mut mixer = ctx.mixer()
@@ -70,16 +71,125 @@ impl Track {
 		self.ctrl.lock().unwrap().looping = l;
 	}
 
+	/// set playback rate, 2.0 plays twice as fast and an octave higher
+	pub fn set_speed(&self, s: f32) {
+		self.ctrl.lock().unwrap().speed = s;
+	}
+
+	/// get playback rate
+	pub fn speed(&self) -> f32 {
+		return self.ctrl.lock().unwrap().speed;
+	}
+
+	/// keep pitch when changing speed by time-stretching instead, e.g. for slow motion that shouldn't sound like it
+	pub fn set_preserve_pitch(&self, b: bool) {
+		self.ctrl.lock().unwrap().preserve_pitch = b;
+	}
+
 	/// check if is paused
 	pub fn paused(&self) -> bool {
 		return self.ctrl.lock().unwrap().paused;
 	}
 
+	/// jump to a position
+	pub fn seek(&self, pos: Duration) -> Result<()> {
+
+		// locked first so the mixer can't play anything between the seek and the reset
+		let mut ctrl = self.ctrl
+			.lock()
+			.map_err(|_| format!("failed to get track"))?;
+
+		self.src
+			.lock()
+			.map_err(|_| format!("failed to get track"))?
+			.seek(pos)?;
+
+		ctrl.mark_seeked();
+
+		return Ok(());
+
+	}
+
+	/// get current playback position
+	pub fn position(&self) -> Duration {
+		return self.src
+			.lock()
+			.ok()
+			.and_then(|s| s.position())
+			.unwrap_or_default();
+	}
+
+	/// get total length, `None` if the file doesn't tell
+	pub fn duration(&self) -> Option<Duration> {
+		return self.src
+			.lock()
+			.ok()
+			.and_then(|s| s.duration());
+	}
+
+	/// resume and ramp up from silence to the current volume
+	pub fn fade_in(&self, d: Duration) {
+		self.ctrl.lock().unwrap().fade_in(d);
+	}
+
+	/// ramp down to silence then pause
+	pub fn fade_out(&self, d: Duration) {
+		self.ctrl.lock().unwrap().fade_out(d);
+	}
+
+	/// ramp volume to a value
+	pub fn fade_to(&self, v: f32, d: Duration) {
+		self.ctrl.lock().unwrap().fade_to(v, d);
+	}
+
+	/// fade this out while fading another track in
+	/// ```ignore
+	/// self.battle_music.seek(Duration::from_secs(0))?;
+	/// self.field_music.crossfade_to(&self.battle_music, Duration::from_secs(2));
+	/// ```
+	pub fn crossfade_to(&self, other: &Track, d: Duration) {
+		self.fade_out(d);
+		other.fade_in(d);
+	}
+
 	/// remove audio from mixer
 	pub fn detach(&self) {
 		self.ctrl.lock().unwrap().detach = true;
 	}
 
+	/// route into a [`Bus`](struct.Bus.html), `None` for master
+	pub fn set_bus(&self, name: Option<&str>) {
+		self.ctrl.lock().unwrap().bus = name.map(|n| n.to_owned());
+	}
+
+	/// get the bus this is routed into
+	pub fn bus(&self) -> Option<String> {
+		return self.ctrl.lock().unwrap().bus.clone();
+	}
+
+	/// set the amount sent to a [`Bus`](struct.Bus.html), 0.0 removes the send
+	pub fn set_send(&self, name: &str, amount: f32) {
+
+		let mut ctrl = self.ctrl.lock().unwrap();
+
+		ctrl.sends.retain(|(n, _)| n != name);
+
+		if amount != 0.0 {
+			ctrl.sends.push((name.to_owned(), amount));
+		}
+
+	}
+
+	/// attach to an [`Emitter`](struct.Emitter.html), `None` to play it as is
+	pub fn set_emitter(&self, e: Option<Arc<Mutex<Emitter>>>) {
+		self.ctrl.lock().unwrap().emitter = e;
+	}
+
+	/// get the attached emitter
+	pub fn emitter(&self) -> Option<Arc<Mutex<Emitter>>> {
+		return self.ctrl.lock().unwrap().emitter.clone();
+	}
+
 	pub fn add_effect(&self, e: Arc<Mutex<dyn Effect + Send>>) {
 		self.ctrl.lock().unwrap().effects.push(e);
 	}
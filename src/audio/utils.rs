@@ -8,7 +8,30 @@ pub fn f32_to_i16(n: f32) -> i16 {
 	return (n * i16::MAX as f32) as i16;
 }
 
+pub fn f32_to_i24(n: f32) -> i32 {
+	return (n * 8_388_607.0) as i32;
+}
+
 pub fn f32_to_u16(n: f32) -> u16 {
 	return ((n * 0.5 + 0.5) * u16::MAX as f32) as u16;
 }
 
+pub fn u16_to_f32(n: u16) -> f32 {
+	return n as f32 / u16::MAX as f32 * 2.0 - 1.0;
+}
+
+pub fn db_to_gain(db: f32) -> f32 {
+	return 10f32.powf(db / 20.0);
+}
+
+pub fn gain_to_db(g: f32) -> f32 {
+	return 20.0 * g.max(0.000_001).log10();
+}
+
+// one pole smoothing coefficient that settles in roughly the given time
+pub fn time_coeff(t: f32, sample_rate: u32) -> f32 {
+	if t <= 0.0 {
+		return 0.0;
+	}
+	return f32::exp(-1.0 / (t * sample_rate as f32));
+}
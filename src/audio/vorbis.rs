@@ -7,18 +7,25 @@ use lewton::samples::InterleavedSamples;
 
 use super::*;
 
+// frames, a bit more than the max vorbis block size
+const SEEK_MARGIN: u64 = 8192;
+
 pub struct VorbisPlayback<R: Read + Seek> {
 	// TODO: have to do this for seek_start cuz seek_absgp_pg won't work
 	decoder: Option<OggStreamReader<R>>,
 	cur_packet: Option<vec::IntoIter<f32>>,
 	channel_count: ChannelCount,
 	sample_rate: u32,
+	// frames played
+	pos: u64,
+	duration: Option<Duration>,
 }
 
 impl<R: Read + Seek> VorbisPlayback<R> {
 
-	pub fn new(reader: R) -> Result<Self> {
+	pub fn new(mut reader: R) -> Result<Self> {
 
+		let len = last_granule(&mut reader)?;
 		let mut decoder = OggStreamReader::new(reader)
 			.map_err(|_| format!("failed to parse vorbis"))?;
 
@@ -31,6 +38,7 @@ impl<R: Read + Seek> VorbisPlayback<R> {
 		};
 
 		let sample_rate = header.audio_sample_rate;
+		let duration = len.map(|l| Duration::from_secs_f64(l as f64 / sample_rate as f64));
 
 		let data = match decoder.read_dec_packet_generic::<InterleavedSamples<f32>>() {
 			Ok(data) => data,
@@ -42,6 +50,8 @@ impl<R: Read + Seek> VorbisPlayback<R> {
 			cur_packet: data.map(|d| d.samples.into_iter()),
 			channel_count,
 			sample_rate,
+			pos: 0,
+			duration,
 		});
 
 	}
@@ -71,6 +81,63 @@ impl<R: Read + Seek> VorbisPlayback<R> {
 
 	}
 
+	fn channels(&self) -> usize {
+		return match self.channel_count {
+			ChannelCount::One => 1,
+			ChannelCount::Two => 2,
+		};
+	}
+
+	// after a page seek, decode until the granule position is known and continue from the exact frame, false if it landed past the target
+	fn skip_to(&mut self, target: u64) -> bool {
+
+		let channels = self.channels();
+		// packets since the seek, positions are unknown until a page ends
+		let mut packets = vec![];
+
+		let decoder = match &mut self.decoder {
+			Some(decoder) => decoder,
+			None => return false,
+		};
+
+		loop {
+
+			match decoder.read_dec_packet_generic::<InterleavedSamples<f32>>() {
+				Ok(Some(packet)) => packets.push(packet.samples),
+				Ok(None) => {
+					self.cur_packet = None;
+					self.pos = target;
+					return true;
+				},
+				Err(_) => return false,
+			};
+
+			let end = match decoder.get_last_absgp() {
+				Some(end) => end,
+				None => continue,
+			};
+
+			let len = packets.iter().map(|p| p.len()).sum::<usize>() / channels;
+			let start = end.saturating_sub(len as u64);
+
+			if start > target {
+				return false;
+			}
+
+			if end > target {
+				let skip = (target - start) as usize * channels;
+				let samples = packets.concat();
+				self.cur_packet = Some(samples[skip..].to_vec().into_iter());
+				self.pos = target;
+				return true;
+			}
+
+			packets.clear();
+
+		}
+
+	}
+
 }
 
 impl<R: Read + Seek> Source for VorbisPlayback<R> {
@@ -102,11 +169,51 @@ impl<R: Read + Seek> Source for VorbisPlayback<R> {
 
 		self.decoder = Some(decoder);
 		self.cur_packet = data.map(|d| d.samples.into_iter());
+		self.pos = 0;
+
+		return Ok(());
+
+	}
+
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+
+		let mut target = (pos.as_secs_f64() * self.sample_rate as f64) as u64;
+
+		if let Some(d) = self.duration {
+			target = target.min((d.as_secs_f64() * self.sample_rate as f64) as u64);
+		}
+
+		let paged = match &mut self.decoder {
+			// land a bit early, the first packet after a page seek only primes the decoder
+			Some(decoder) => decoder.seek_absgp_pg(target.saturating_sub(SEEK_MARGIN)).is_ok(),
+			None => false,
+		};
+
+		if paged && self.skip_to(target) {
+			return Ok(());
+		}
+
+		// page seeking failed, decode from the start
+		self.seek_start()?;
+
+		for _ in 0..target {
+			if self.next().is_none() {
+				break;
+			}
+		}
 
 		return Ok(());
 
 	}
 
+	fn position(&self) -> Option<Duration> {
+		return Some(Duration::from_secs_f64(self.pos as f64 / self.sample_rate as f64));
+	}
+
+	fn duration(&self) -> Option<Duration> {
+		return self.duration;
+	}
+
 }
 
 impl<R: Read + Seek> Iterator for VorbisPlayback<R> {
@@ -120,6 +227,8 @@ impl<R: Read + Seek> Iterator for VorbisPlayback<R> {
 			None => return None,
 		};
 
+		self.pos += 1;
+
 		return Some(match self.channel_count {
 			ChannelCount::One => Frame::new(sample, sample),
 			ChannelCount::Two => Frame::new(sample, self.next_sample().unwrap_or(0.0)),
@@ -145,3 +254,46 @@ pub fn is_vorbis<R: Read + Seek>(mut reader: R) -> Result<bool> {
 
 }
 
+// granule position of the last ogg page, which is the length in frames
+fn last_granule<R: Read + Seek>(reader: &mut R) -> Result<Option<u64>> {
+
+	let pos = reader
+		.seek(SeekFrom::Current(0))
+		.map_err(|_| format!("failed to seek"))?;
+
+	let len = reader
+		.seek(SeekFrom::End(0))
+		.map_err(|_| format!("failed to seek"))?;
+
+	// max ogg page size
+	let tail = len.min(65307);
+	let mut buf = vec![0; tail as usize];
+
+	reader
+		.seek(SeekFrom::End(-(tail as i64)))
+		.map_err(|_| format!("failed to seek"))?;
+
+	reader
+		.read_exact(&mut buf)
+		.map_err(|_| format!("failed to read vorbis"))?;
+
+	reader
+		.seek(SeekFrom::Start(pos))
+		.map_err(|_| format!("failed to seek"))?;
+
+	for i in (0..buf.len().saturating_sub(14)).rev() {
+		if &buf[i..i + 4] == b"OggS" {
+			let mut g = [0; 8];
+			g.copy_from_slice(&buf[i + 6..i + 14]);
+			let g = u64::from_le_bytes(g);
+			// -1 means no packet ends on this page
+			if g != std::u64::MAX {
+				return Ok(Some(g));
+			}
+		}
+	}
+
+	return Ok(None);
+
+}
+
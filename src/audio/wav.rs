@@ -12,6 +12,8 @@ pub struct WavPlayback<R: Read + Seek> {
 	spec: hound::WavSpec,
 	duration: Duration,
 	channel_count: ChannelCount,
+	// frames played
+	pos: u32,
 }
 
 impl<R: Read + Seek> WavPlayback<R> {
@@ -35,6 +37,7 @@ impl<R: Read + Seek> WavPlayback<R> {
 			decoder: wav,
 			duration,
 			channel_count,
+			pos: 0,
 		});
 
 	}
@@ -67,9 +70,32 @@ impl<R: Read + Seek> Source for WavPlayback<R> {
 		self.decoder
 			.seek(0)
 			.map_err(|_| format!("failed to seek wav"))?;
+		self.pos = 0;
 		return Ok(());
 	}
 
+	fn seek(&mut self, pos: Duration) -> Result<()> {
+
+		let frame = ((pos.as_secs_f64() * self.spec.sample_rate as f64) as u32).min(self.decoder.duration());
+
+		self.decoder
+			.seek(frame)
+			.map_err(|_| format!("failed to seek wav"))?;
+
+		self.pos = frame;
+
+		return Ok(());
+
+	}
+
+	fn position(&self) -> Option<Duration> {
+		return Some(Duration::from_secs_f64(self.pos as f64 / self.spec.sample_rate as f64));
+	}
+
+	fn duration(&self) -> Option<Duration> {
+		return Some(self.duration);
+	}
+
 }
 
 impl<R: Read + Seek> Iterator for WavPlayback<R> {
@@ -83,6 +109,8 @@ impl<R: Read + Seek> Iterator for WavPlayback<R> {
 			None => return None,
 		};
 
+		self.pos += 1;
+
 		return Some(match self.channel_count {
 			ChannelCount::One => Frame::new(sample, sample),
 			ChannelCount::Two => Frame::new(sample, self.next_sample().unwrap_or(0.0)),
@@ -2,6 +2,8 @@
 
 //! Start Up Configs
 
+use std::path::PathBuf;
+
 use crate::*;
 use math::*;
 
@@ -11,6 +13,19 @@ pub enum CanvasRoot {
 	Element(&'static str),
 }
 
+/// Where Audio Goes
+#[derive(Clone, PartialEq, Debug)]
+pub enum AudioBackend {
+	/// use the sound card, fall back to `Null` if there's none
+	Auto,
+	/// use the sound card, fail if there's none
+	Cpal,
+	/// run the mixer in real time without playing anything
+	Null,
+	/// like `Null`, but also write everything played to a wav file
+	File(PathBuf),
+}
+
 #[derive(Clone, Debug)]
 pub struct Conf {
 	pub width: i32,
@@ -28,6 +43,7 @@ pub struct Conf {
 	pub cull_face: bool,
 	pub canvas_root: CanvasRoot,
 	pub default_font: Option<gfx::BitmapFontData>,
+	pub audio_backend: AudioBackend,
 }
 
 impl Conf {
@@ -62,6 +78,7 @@ impl Default for Conf {
 			cull_face: false,
 			canvas_root: CanvasRoot::Body,
 			default_font: None,
+			audio_backend: AudioBackend::Auto,
 		};
 	}
 
@@ -145,5 +162,10 @@ impl Launcher {
 		return self;
 	}
 
+	pub fn audio_backend(mut self, b: AudioBackend) -> Self {
+		self.conf.audio_backend = b;
+		return self;
+	}
+
 }
 
@@ -259,13 +259,18 @@ pub fn sat2d(p1: &[Vec2], p2: &[Vec2]) -> (bool, Vec2) {
 			return (false, vec2!());
 		}
 
-		let o = s2max - s1min;
+		// mtv always pushes p1 out of p2
+		let o1 = s2max - s1min;
+		let o2 = s1max - s2min;
 
-		if o < overlap {
-
-			overlap = o;
-			mtv = a * o;
+		if o1 < overlap {
+			overlap = o1;
+			mtv = a * o1;
+		}
 
+		if o2 < overlap {
+			overlap = o2;
+			mtv = a * -o2;
 		}
 
 	}
@@ -439,7 +444,13 @@ pub fn sphere_plane(s: Sphere, p: Plane) -> bool {
 
 pub fn ray_plane(r: Ray3, p: Plane) -> Option<Vec3> {
 
-	let t = -(Vec3::dot(r.origin, p.normal) - p.dist) / Vec3::dot(r.dir, p.normal);
+	let denom = Vec3::dot(r.dir, p.normal);
+
+	if denom == 0.0 {
+		return None;
+	}
+
+	let t = -(Vec3::dot(r.origin, p.normal) + p.dist) / denom;
 
 	if t >= 0.0 {
 		return Some(r.at(t));
@@ -456,8 +467,17 @@ pub fn ray_sphere(r: Ray3, s: Sphere) -> Option<Vec3> {
 	let c = Vec3::dot(s.center - r.origin, s.center - r.origin) - s.radius * s.radius;
 	let d = b * b - 4.0 * a * c;
 
-	if d >= 0.0 {
-		return Some(r.at(d));
+	if d < 0.0 {
+		return None;
+	}
+
+	let t1 = (-b - d.sqrt()) / (2.0 * a);
+	let t2 = (-b + d.sqrt()) / (2.0 * a);
+
+	if t1 >= 0.0 {
+		return Some(r.at(t1));
+	} else if t2 >= 0.0 {
+		return Some(r.at(t2));
 	} else {
 		return None;
 	}
@@ -0,0 +1,606 @@
+// wengwengweng
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use super::*;
+
+type NodeID = usize;
+
+/// How a Layer Combines With the Layers Below
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LayerMode {
+	/// replace the transforms of the nodes it covers
+	Override,
+	/// add the clip's movement relative to its first frame on top
+	Additive,
+}
+
+/// Condition for a [`Transition`](struct.Transition.html)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cond {
+	/// a param is greater than the value
+	Above(String, f32),
+	/// a param is less than the value
+	Below(String, f32),
+	/// a trigger is set, it's consumed when the transition is taken
+	Trigger(String),
+	/// the current clip reached its end, or finished a loop if looping
+	End,
+}
+
+/// Transition Between 2 States
+#[derive(Clone, Debug)]
+pub struct Transition {
+	to: String,
+	cond: Cond,
+	fade: f32,
+}
+
+/// State in the Animation State Machine
+#[derive(Clone, Debug)]
+pub struct AnimState {
+	clip: String,
+	speed: f32,
+	looping: bool,
+	transitions: Vec<Transition>,
+}
+
+impl AnimState {
+
+	pub fn new(clip: &str) -> Self {
+		return Self {
+			clip: clip.to_owned(),
+			speed: 1.0,
+			looping: true,
+			transitions: vec![],
+		};
+	}
+
+	pub fn speed(mut self, s: f32) -> Self {
+		self.speed = s;
+		return self;
+	}
+
+	pub fn looping(mut self, l: bool) -> Self {
+		self.looping = l;
+		return self;
+	}
+
+	/// go to another state when cond is met, crossfading for fade seconds, checked in the order they're added
+	pub fn transition(mut self, to: &str, cond: Cond, fade: f32) -> Self {
+		self.transitions.push(Transition {
+			to: to.to_owned(),
+			cond: cond,
+			fade: fade,
+		});
+		return self;
+	}
+
+}
+
+/// Event Fired When a Clip Passes a Keyframe
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimEvent {
+	pub name: String,
+	pub clip: String,
+	pub layer: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Playing {
+	clip: usize,
+	time: f32,
+	speed: f32,
+	looping: bool,
+	loops: usize,
+	finished: bool,
+}
+
+impl Playing {
+
+	fn new(clip: usize, speed: f32, looping: bool) -> Self {
+		return Self {
+			clip: clip,
+			time: 0.0,
+			speed: speed.max(0.0),
+			looping: looping,
+			loops: 0,
+			finished: false,
+		};
+	}
+
+	// move forward, returns names of the events passed
+	fn advance(&mut self, dt: f32, len: f32, events: &[(f32, String)]) -> Vec<String> {
+
+		let mut fired = vec![];
+
+		if self.finished {
+			return fired;
+		}
+
+		let mut from = self.time;
+		let mut to = self.time + dt * self.speed;
+
+		let mut fire = |from: f32, to: f32, inclusive: bool| {
+			for (t, name) in events {
+				if *t >= from && (*t < to || (inclusive && *t <= to)) {
+					fired.push(name.clone());
+				}
+			}
+		};
+
+		if len <= 0.0 {
+			fire(from, to, false);
+			self.time = to;
+			return fired;
+		}
+
+		if !self.looping {
+			if to >= len {
+				fire(from, len, true);
+				self.time = len;
+				self.finished = true;
+			} else {
+				fire(from, to, false);
+				self.time = to;
+			}
+			return fired;
+		}
+
+		while to >= len {
+			fire(from, len, false);
+			to -= len;
+			from = 0.0;
+			self.loops += 1;
+			// don't fire the same events many times on a huge dt
+			to %= len;
+		}
+
+		fire(from, to, false);
+		self.time = to;
+
+		return fired;
+
+	}
+
+	fn ended(&self) -> bool {
+		return self.finished || self.loops > 0;
+	}
+
+}
+
+#[derive(Clone, Debug)]
+struct Layer {
+	mode: LayerMode,
+	weight: f32,
+	mask: Option<HashSet<NodeID>>,
+	cur: Option<Playing>,
+	prev: Option<Playing>,
+	// (elapsed, duration)
+	fade: (f32, f32),
+}
+
+impl Layer {
+
+	fn new(mode: LayerMode, mask: Option<HashSet<NodeID>>) -> Self {
+		return Self {
+			mode: mode,
+			weight: 1.0,
+			mask: mask,
+			cur: None,
+			prev: None,
+			fade: (0.0, 0.0),
+		};
+	}
+
+	// how much cur is blended in over prev
+	fn fade_t(&self) -> f32 {
+		let (elapsed, dur) = self.fade;
+		if dur <= 0.0 {
+			return 1.0;
+		}
+		return (elapsed / dur).min(1.0);
+	}
+
+	fn play(&mut self, p: Option<Playing>, fade: f32) {
+		self.prev = if fade > 0.0 {
+			self.cur.take()
+		} else {
+			None
+		};
+		self.cur = p;
+		self.fade = (0.0, fade);
+	}
+
+	fn covers(&self, id: NodeID) -> bool {
+		return self.mask
+			.as_ref()
+			.map(|m| m.contains(&id))
+			.unwrap_or(true);
+	}
+
+}
+
+/// Animation Player
+///
+/// plays the clips of a [`Model`](struct.Model.html) on layers, crossfades between them, and optionally drives the base layer with a state machine
+#[derive(Clone, Debug)]
+pub struct Animator {
+	// (name, len) of each clip in the model
+	clips: Vec<(Option<String>, f32)>,
+	layers: Vec<Layer>,
+	states: HashMap<String, AnimState>,
+	cur_state: Option<String>,
+	params: HashMap<String, f32>,
+	triggers: HashSet<String>,
+	events: HashMap<usize, Vec<(f32, String)>>,
+	fired: Vec<AnimEvent>,
+}
+
+impl Animator {
+
+	/// create an animator for a model, with an empty override base layer
+	pub fn new(model: &Model) -> Self {
+		return Self {
+			clips: model
+				.anims()
+				.iter()
+				.map(|a| (a.name().map(String::from), a.len()))
+				.collect(),
+			layers: vec![Layer::new(LayerMode::Override, None)],
+			states: hmap![],
+			cur_state: None,
+			params: hmap![],
+			triggers: hset![],
+			events: hmap![],
+			fired: vec![],
+		};
+	}
+
+	fn find_clip(&self, name: &str) -> Result<usize> {
+		return self.clips
+			.iter()
+			.position(|(n, _)| n.as_deref() == Some(name))
+			.ok_or_else(|| format!("anim clip not found: {}", name));
+	}
+
+	/// add a layer on top, only affecting nodes in mask if provided (see [`Model::subtree`](struct.Model.html#method.subtree)), returns the layer index
+	pub fn add_layer(&mut self, mode: LayerMode, mask: Option<HashSet<NodeID>>) -> usize {
+		self.layers.push(Layer::new(mode, mask));
+		return self.layers.len() - 1;
+	}
+
+	/// set how much a layer affects the final pose
+	pub fn set_layer_weight(&mut self, layer: usize, w: f32) {
+		if let Some(l) = self.layers.get_mut(layer) {
+			l.weight = w.max(0.0).min(1.0);
+		}
+	}
+
+	/// play a clip on the base layer, crossfading from the current clip for fade seconds
+	pub fn play(&mut self, clip: &str, fade: f32) -> Result<()> {
+		return self.play_on(0, clip, fade);
+	}
+
+	/// play a clip on a layer, crossfading from the current clip for fade seconds
+	pub fn play_on(&mut self, layer: usize, clip: &str, fade: f32) -> Result<()> {
+		return self.play_ex(layer, clip, 1.0, true, fade);
+	}
+
+	/// play a clip on a layer with speed & looping
+	pub fn play_ex(&mut self, layer: usize, clip: &str, speed: f32, looping: bool, fade: f32) -> Result<()> {
+
+		let clip = self.find_clip(clip)?;
+		let layer = self.layers
+			.get_mut(layer)
+			.ok_or_else(|| format!("anim layer not found: {}", layer))?;
+
+		layer.play(Some(Playing::new(clip, speed, looping)), fade);
+
+		return Ok(());
+
+	}
+
+	/// fade out whatever is playing on a layer
+	pub fn stop(&mut self, layer: usize, fade: f32) {
+		if let Some(l) = self.layers.get_mut(layer) {
+			l.play(None, fade);
+		}
+	}
+
+	/// add a state to the state machine
+	pub fn add_state(&mut self, name: &str, state: AnimState) -> Result<()> {
+		self.find_clip(&state.clip)?;
+		self.states.insert(name.to_owned(), state);
+		return Ok(());
+	}
+
+	/// jump to a state, crossfading for fade seconds
+	pub fn set_state(&mut self, name: &str, fade: f32) -> Result<()> {
+
+		let state = self.states
+			.get(name)
+			.ok_or_else(|| format!("anim state not found: {}", name))?;
+
+		let (clip, speed, looping) = (state.clip.clone(), state.speed, state.looping);
+
+		self.play_ex(0, &clip, speed, looping, fade)?;
+		self.cur_state = Some(name.to_owned());
+
+		return Ok(());
+
+	}
+
+	/// current state of the state machine
+	pub fn state(&self) -> Option<&str> {
+		return self.cur_state.as_deref();
+	}
+
+	pub fn set_param(&mut self, name: &str, v: f32) {
+		self.params.insert(name.to_owned(), v);
+	}
+
+	pub fn param(&self, name: &str) -> f32 {
+		return self.params
+			.get(name)
+			.cloned()
+			.unwrap_or(0.0);
+	}
+
+	/// set a one-shot trigger, kept until a transition consumes it
+	pub fn trigger(&mut self, name: &str) {
+		self.triggers.insert(name.to_owned());
+	}
+
+	/// fire an event when a clip passes a time
+	pub fn add_event(&mut self, clip: &str, time: f32, name: &str) -> Result<()> {
+		let clip = self.find_clip(clip)?;
+		self.events
+			.entry(clip)
+			.or_insert_with(|| vec![])
+			.push((time, name.to_owned()));
+		return Ok(());
+	}
+
+	/// get events fired since last call
+	pub fn drain_events(&mut self) -> Vec<AnimEvent> {
+		return self.fired.drain(..).collect();
+	}
+
+	/// advance time, usually called every frame with `app.dt()`
+	pub fn update(&mut self, dt: Duration) {
+
+		let dt = dt.as_secs_f32();
+		let no_events = vec![];
+
+		for (i, layer) in self.layers.iter_mut().enumerate() {
+
+			for p in layer.prev.iter_mut().chain(layer.cur.iter_mut()) {
+
+				let len = self.clips
+					.get(p.clip)
+					.map(|(_, len)| *len)
+					.unwrap_or(0.0);
+
+				let events = self.events
+					.get(&p.clip)
+					.unwrap_or(&no_events);
+
+				for name in p.advance(dt, len, events) {
+					self.fired.push(AnimEvent {
+						name: name,
+						clip: self.clips
+							.get(p.clip)
+							.and_then(|(n, _)| n.clone())
+							.unwrap_or_default(),
+						layer: i,
+					});
+				}
+
+			}
+
+			layer.fade.0 += dt;
+
+			if layer.fade_t() >= 1.0 {
+				layer.prev = None;
+			}
+
+		}
+
+		self.update_state();
+
+	}
+
+	fn update_state(&mut self) {
+
+		let state = match self.cur_state.as_ref().and_then(|s| self.states.get(s)) {
+			Some(s) => s,
+			None => return,
+		};
+
+		let ended = self.layers
+			.get(0)
+			.and_then(|l| l.cur.as_ref())
+			.map(|p| p.ended())
+			.unwrap_or(false);
+
+		let next = state.transitions
+			.iter()
+			.find(|t| {
+				return match &t.cond {
+					Cond::Above(p, v) => self.param(p) > *v,
+					Cond::Below(p, v) => self.param(p) < *v,
+					Cond::Trigger(name) => self.triggers.contains(name),
+					Cond::End => ended,
+				};
+			})
+			.cloned();
+
+		if let Some(t) = next {
+
+			if let Cond::Trigger(name) = &t.cond {
+				self.triggers.remove(name);
+			}
+
+			if let Err(err) = self.set_state(&t.to, t.fade) {
+				elog!("{}", err);
+				self.cur_state = None;
+			}
+
+		}
+
+	}
+
+	/// get the blended pose, pass to [`shapes::Model::pose`](shapes/struct.Model.html#method.pose) to draw
+	pub fn pose(&self, model: &Model) -> Pose {
+
+		let mut pose = model.rest_pose();
+
+		for layer in &self.layers {
+
+			if layer.weight <= 0.0 {
+				continue;
+			}
+
+			let sample = |p: &Option<Playing>, base: &Pose| {
+				let mut out = base.clone();
+				if let Some(p) = p {
+					if let Some(clip) = model.anims().get(p.clip) {
+						clip.sample(p.time, &mut out);
+					}
+				}
+				return out;
+			};
+
+			let t = layer.fade_t();
+
+			match layer.mode {
+
+				LayerMode::Override => {
+
+					let a = sample(&layer.prev, &pose);
+					let b = sample(&layer.cur, &pose);
+
+					for (id, tr) in pose.iter_mut() {
+						if layer.covers(*id) {
+							if let (Some(a), Some(b)) = (a.get(id), b.get(id)) {
+								*tr = blend(tr, &blend(a, b, t), layer.weight);
+							}
+						}
+					}
+
+				},
+
+				LayerMode::Additive => {
+
+					let rest = model.rest_pose();
+
+					let delta = |p: &Option<Playing>| {
+						let mut reference = rest.clone();
+						if let Some(p) = p {
+							if let Some(clip) = model.anims().get(p.clip) {
+								clip.sample(0.0, &mut reference);
+							}
+						}
+						let cur = sample(p, &rest);
+						return (reference, cur);
+					};
+
+					let (ra, ca) = delta(&layer.prev);
+					let (rb, cb) = delta(&layer.cur);
+
+					for (id, tr) in pose.iter_mut() {
+						if layer.covers(*id) {
+							if let (Some(ra), Some(ca), Some(rb), Some(cb)) = (ra.get(id), ca.get(id), rb.get(id), cb.get(id)) {
+								let d = blend(&diff(ra, ca), &diff(rb, cb), t);
+								*tr = add(tr, &blend(&Transform::new(), &d, layer.weight));
+							}
+						}
+					}
+
+				},
+
+			}
+
+		}
+
+		return pose;
+
+	}
+
+}
+
+fn quat_mul(a: Vec4, b: Vec4) -> Vec4 {
+	return vec4!(
+		a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+		a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+		a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+		a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+	);
+}
+
+fn quat_conj(q: Vec4) -> Vec4 {
+	return vec4!(-q.x, -q.y, -q.z, q.w);
+}
+
+fn blend(a: &Transform, b: &Transform, t: f32) -> Transform {
+	return Transform {
+		pos: a.pos.lerp(b.pos, t),
+		rot: a.rot.slerp(b.rot, t),
+		scale: a.scale.lerp(b.scale, t),
+	};
+}
+
+// the change from a to b
+fn diff(a: &Transform, b: &Transform) -> Transform {
+	return Transform {
+		pos: b.pos - a.pos,
+		rot: quat_mul(b.rot, quat_conj(a.rot)),
+		scale: b.scale / a.scale,
+	};
+}
+
+fn add(tr: &Transform, d: &Transform) -> Transform {
+	return Transform {
+		pos: tr.pos + d.pos,
+		rot: quat_mul(d.rot, tr.rot),
+		scale: tr.scale * d.scale,
+	};
+}
+
+#[test]
+fn playing_advance() {
+
+	let events = vec![
+		(0.0, "start".to_owned()),
+		(0.5, "half".to_owned()),
+		(1.0, "end".to_owned()),
+	];
+
+	// the end of a clip that doesn't loop is inclusive
+	let mut p = Playing::new(0, 1.0, false);
+
+	assert_eq!(p.advance(0.4, 1.0, &events), vec!["start"]);
+	assert_eq!(p.advance(0.4, 1.0, &events), vec!["half"]);
+	assert!(!p.ended());
+	assert_eq!(p.advance(0.5, 1.0, &events), vec!["end"]);
+	assert!(p.ended());
+	assert_eq!(p.time, 1.0);
+	assert!(p.advance(0.5, 1.0, &events).is_empty());
+
+	// an event at 0 fires again on every loop
+	let mut p = Playing::new(0, 1.0, true);
+
+	assert_eq!(p.advance(0.6, 1.0, &events), vec!["start", "half"]);
+	assert!(!p.ended());
+	assert_eq!(p.advance(0.6, 1.0, &events), vec!["start"]);
+	assert!(p.ended());
+	assert_eq!(p.loops, 1);
+
+	// a huge step only wraps once
+	assert_eq!(p.advance(10.5, 1.0, &events), vec!["half", "start", "half"]);
+	assert_eq!(p.loops, 2);
+	assert!((p.time - 0.7).abs() < 0.001);
+
+}
@@ -23,13 +23,61 @@ impl VertexLayout for Vertex {
 	}
 }
 
+/// Vertex Type for Skinned Meshes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SkinnedVertex {
+	pub pos: Vec3,
+	pub uv: Vec2,
+	pub normal: Vec3,
+	pub color: Color,
+	/// indices into the skin joints
+	pub joints: Vec4,
+	/// how much each joint affects this vertex, sums to 1
+	pub weights: Vec4,
+}
+
+impl VertexLayout for SkinnedVertex {
+	fn attrs() -> VertexAttrGroup {
+		return &[
+			("a_pos", 3),
+			("a_uv", 2),
+			("a_normal", 3),
+			("a_color", 4),
+			("a_joints", 4),
+			("a_weights", 4),
+		];
+	}
+}
+
+// inverse transpose of the model matrix, keeps normals perpendicular to surfaces under non-uniform scale
+pub(super) fn normal_mat(model: Mat4) -> Mat4 {
+
+	let m = model.inverse().transpose();
+
+	// a model matrix with 0 scale has no inverse
+	if m.as_arr().iter().all(|f| f.is_finite()) {
+		return m;
+	} else {
+		return mat4!();
+	}
+
+}
+
+// uniform values are borrowed, so sampler units point into here
+static TEX_UNITS: [i32; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
 #[derive(Clone, PartialEq)]
 pub(super) struct Uniform {
 	pub proj: Mat4,
 	pub view: Mat4,
 	pub model: Mat4,
+	pub normal_mat: Mat4,
 	pub color: Color,
 	pub tex: Texture,
+	pub joints: Option<Vec<Mat4>>,
+	pub material: MaterialUniform,
+	pub lights: LightUniform,
 	pub custom: Option<UniformData>,
 }
 
@@ -41,9 +89,33 @@ impl UniformLayout for Uniform {
 			("u_proj", &self.proj),
 			("u_view", &self.view),
 			("u_model", &self.model),
+			("u_normal_mat", &self.normal_mat),
 			("u_color", &self.color),
 		];
 
+		match &self.joints {
+			Some(joints) => {
+				values.push(("u_skinned", &1.0f32));
+				values.push(("u_joints", joints));
+			},
+			None => values.push(("u_skinned", &0.0f32)),
+		}
+
+		// custom textures are bound right after u_tex, then the material textures & the shadow map
+		let custom_texs = self.custom
+			.as_ref()
+			.map(|c| c.textures.len())
+			.unwrap_or(0)
+			.min(TEX_UNITS.len() - 5);
+		let units = &TEX_UNITS[1 + custom_texs..];
+
+		values.push(("u_normal_tex", &units[0]));
+		values.push(("u_emissive_tex", &units[1]));
+		values.push(("u_metallic_roughness_tex", &units[2]));
+		values.push(("u_shadow_map", &units[3]));
+		values.extend(self.material.values());
+		values.extend(self.lights.values());
+
 		if let Some(custom) = &self.custom {
 			for (name, v) in &custom.values {
 				values.push((name, v));
@@ -59,6 +131,8 @@ impl UniformLayout for Uniform {
 		if let Some(custom) = &self.custom {
 			textures.extend(custom.textures.iter());
 		}
+		textures.extend(self.material.textures());
+		textures.extend(self.lights.textures());
 		return textures;
 	}
 
@@ -0,0 +1,345 @@
+// wengwengweng
+
+use super::*;
+
+/// Kinds of Light, angles are in radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+	/// lights everything evenly
+	Ambient,
+	/// infinitely far away, like the sun
+	Directional {
+		dir: Vec3,
+	},
+	/// falls off with distance squared, range of 0 means it never cuts off
+	Point {
+		pos: Vec3,
+		range: f32,
+	},
+	/// a point light restricted to a cone, fading from inner to outer
+	Spot {
+		pos: Vec3,
+		dir: Vec3,
+		range: f32,
+		inner: f32,
+		outer: f32,
+	},
+}
+
+/// A Light Source. See [mod-level doc](index.html) for Usage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+	pub kind: LightKind,
+	pub color: Color,
+	pub intensity: f32,
+}
+
+impl Light {
+
+	fn new(kind: LightKind) -> Self {
+		return Self {
+			kind: kind,
+			color: rgba!(1),
+			intensity: 1.0,
+		};
+	}
+
+	/// create an ambient light
+	pub fn ambient() -> Self {
+		return Self::new(LightKind::Ambient);
+	}
+
+	/// create a directional light pointing towards dir
+	pub fn directional(dir: Vec3) -> Self {
+		return Self::new(LightKind::Directional {
+			dir: dir,
+		});
+	}
+
+	/// create a point light
+	pub fn point(pos: Vec3) -> Self {
+		return Self::new(LightKind::Point {
+			pos: pos,
+			range: 0.0,
+		});
+	}
+
+	/// create a spot light with cone angle
+	pub fn spot(pos: Vec3, dir: Vec3, angle: f32) -> Self {
+		return Self::new(LightKind::Spot {
+			pos: pos,
+			dir: dir,
+			range: 0.0,
+			inner: angle * 0.8,
+			outer: angle,
+		});
+	}
+
+	/// set color
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+
+	/// set intensity
+	pub fn intensity(mut self, i: f32) -> Self {
+		self.intensity = i;
+		return self;
+	}
+
+	/// set range of a point or spot light
+	pub fn range(mut self, r: f32) -> Self {
+		match &mut self.kind {
+			LightKind::Point { range, .. } => *range = r,
+			LightKind::Spot { range, .. } => *range = r,
+			_ => {},
+		}
+		return self;
+	}
+
+	/// set inner & outer cone angle of a spot light
+	pub fn cone(mut self, i: f32, o: f32) -> Self {
+		if let LightKind::Spot { inner, outer, .. } = &mut self.kind {
+			*inner = i;
+			*outer = o;
+		}
+		return self;
+	}
+
+}
+
+/// Depth Map for Directional Light Shadows. See [mod-level doc](index.html) for Usage.
+#[derive(Clone)]
+pub struct ShadowMap {
+	canvas: Canvas,
+	/// center of the shadowed area
+	pub center: Vec3,
+	/// half size of the shadowed area
+	pub extent: f32,
+	/// depth offset against shadow acne
+	pub bias: f32,
+}
+
+impl ShadowMap {
+
+	/// create a shadow map with a square depth texture
+	pub fn new(ctx: &Gfx, size: i32) -> Result<Self> {
+
+		let dpi = ctx.dpi();
+		let size = (size as f32 / dpi) as i32;
+
+		return Ok(Self {
+			canvas: Canvas::new(ctx, size, size)?,
+			center: vec3!(0),
+			extent: 16.0,
+			bias: 0.005,
+		});
+
+	}
+
+	/// set the area that casts and receives shadows
+	pub fn bounds(mut self, center: Vec3, extent: f32) -> Self {
+		self.center = center;
+		self.extent = extent;
+		return self;
+	}
+
+	/// set bias
+	pub fn bias(mut self, b: f32) -> Self {
+		self.bias = b;
+		return self;
+	}
+
+	/// get the packed depth texture
+	pub fn tex(&self) -> &Texture {
+		return self.canvas.tex();
+	}
+
+	pub(super) fn canvas(&self) -> &Canvas {
+		return &self.canvas;
+	}
+
+	// ortho camera looking down dir, covering the bounds
+	pub(super) fn light_cam(&self, dir: Vec3) -> RawCam {
+
+		let z = -dir.unit();
+		let up = if z.y.abs() > 0.99 {
+			vec3!(0, 0, 1)
+		} else {
+			vec3!(0, 1, 0)
+		};
+		let x = Vec3::cross(up, z).unit();
+		let y = Vec3::cross(z, x);
+		let c = self.center;
+		let e = self.extent;
+
+		let view = mat4!(
+			x.x, y.x, z.x, 0.0,
+			x.y, y.y, z.y, 0.0,
+			x.z, y.z, z.z, 0.0,
+			-Vec3::dot(x, c), -Vec3::dot(y, c), -Vec3::dot(z, c), 1.0,
+		);
+
+		let proj = OrthoCam {
+			width: e * 2.0,
+			height: e * 2.0,
+			near: -e * 2.0,
+			far: e * 2.0,
+		}.proj();
+
+		return RawCam {
+			proj: proj,
+			view: view,
+		};
+
+	}
+
+}
+
+// the first directional light is the one that casts shadow
+pub(super) fn shadow_caster(lights: &[Light]) -> Option<(usize, Vec3)> {
+	return lights
+		.iter()
+		.enumerate()
+		.find_map(|(i, l)| match l.kind {
+			LightKind::Directional { dir } => Some((i, dir)),
+			_ => None,
+		});
+}
+
+// light values sent with every draw, the shadow map is bound after the material textures
+#[derive(Clone, PartialEq)]
+pub(super) struct LightUniform {
+	pub count: i32,
+	pub pos: Vec<Vec4>,
+	pub dir: Vec<Vec4>,
+	pub color: Vec<Vec4>,
+	pub params: Vec<Vec4>,
+	pub eye: Vec3,
+	pub has_shadow: f32,
+	pub light_space: Mat4,
+	pub bias: f32,
+	pub texel: f32,
+	pub shadow_map: Texture,
+}
+
+impl LightUniform {
+
+	pub fn new(lights: &[Light], shadow: Option<&ShadowMap>, empty: &Texture) -> Self {
+
+		let caster = shadow.and_then(|s| {
+			return shadow_caster(lights).map(|(i, dir)| (i, s, s.light_cam(dir)));
+		});
+
+		let mut pos = vec![];
+		let mut dir = vec![];
+		let mut color = vec![];
+		let mut params = vec![];
+
+		for (i, l) in lights.iter().enumerate() {
+
+			let casts = match caster {
+				Some((ci, _, _)) if ci == i => 1.0,
+				_ => 0.0,
+			};
+
+			let (p, d, prm) = match l.kind {
+				LightKind::Ambient => (
+					vec4!(0, 0, 0, 0),
+					vec3!(0),
+					vec4!(0),
+				),
+				LightKind::Directional { dir } => (
+					vec4!(0, 0, 0, 1),
+					dir.unit(),
+					vec4!(0, 0, 0, casts),
+				),
+				LightKind::Point { pos, range } => (
+					vec4!(pos.x, pos.y, pos.z, 2),
+					vec3!(0),
+					vec4!(range, 0, 0, 0),
+				),
+				LightKind::Spot { pos, dir, range, inner, outer } => (
+					vec4!(pos.x, pos.y, pos.z, 3),
+					dir.unit(),
+					vec4!(range, inner.cos(), outer.cos(), 0),
+				),
+			};
+
+			pos.push(p);
+			dir.push(vec4!(d.x, d.y, d.z, 0));
+			color.push(vec4!(
+				l.color.r * l.intensity,
+				l.color.g * l.intensity,
+				l.color.b * l.intensity,
+				1,
+			));
+			params.push(prm);
+
+		}
+
+		return match caster {
+			Some((_, s, cam)) => Self {
+				count: lights.len() as i32,
+				pos: pos,
+				dir: dir,
+				color: color,
+				params: params,
+				eye: vec3!(0),
+				has_shadow: 1.0,
+				light_space: cam.proj * cam.view,
+				bias: s.bias,
+				texel: 1.0 / s.tex().width() as f32,
+				shadow_map: s.tex().clone(),
+			},
+			None => Self {
+				count: lights.len() as i32,
+				pos: pos,
+				dir: dir,
+				color: color,
+				params: params,
+				eye: vec3!(0),
+				has_shadow: 0.0,
+				light_space: mat4!(),
+				bias: 0.0,
+				texel: 0.0,
+				shadow_map: empty.clone(),
+			},
+		};
+
+	}
+
+}
+
+impl UniformLayout for LightUniform {
+
+	fn values(&self) -> UniformValues {
+
+		let mut values: UniformValues = vec![
+			("u_light_count", &self.count),
+			("u_has_shadow", &self.has_shadow),
+		];
+
+		if self.count > 0 {
+			values.push(("u_light_pos", &self.pos));
+			values.push(("u_light_dir", &self.dir));
+			values.push(("u_light_color", &self.color));
+			values.push(("u_light_params", &self.params));
+			values.push(("u_eye_pos", &self.eye));
+		}
+
+		if self.has_shadow == 1.0 {
+			values.push(("u_light_space", &self.light_space));
+			values.push(("u_shadow_bias", &self.bias));
+			values.push(("u_shadow_texel", &self.texel));
+		}
+
+		return values;
+
+	}
+
+	fn textures(&self) -> Vec<&Texture> {
+		return vec![&self.shadow_map];
+	}
+
+}
@@ -0,0 +1,169 @@
+// wengwengweng
+
+use super::*;
+
+/// How Transparency is Handled
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AlphaMode {
+	Opaque,
+	/// discard pixels with alpha under the cutoff
+	Mask(f32),
+	/// alpha blend, drawn without depth write
+	Blend,
+}
+
+/// Surface Properties of a Mesh
+#[derive(Clone, PartialEq)]
+pub struct Material {
+	pub name: Option<String>,
+	pub base_color: Color,
+	pub base_color_tex: Option<Texture>,
+	pub normal_tex: Option<Texture>,
+	pub emissive: Color,
+	pub emissive_tex: Option<Texture>,
+	pub metallic: f32,
+	pub roughness: f32,
+	/// metalness in blue, roughness in green
+	pub metallic_roughness_tex: Option<Texture>,
+	pub alpha_mode: AlphaMode,
+}
+
+impl Default for Material {
+	fn default() -> Self {
+		return Self {
+			name: None,
+			base_color: rgba!(1),
+			base_color_tex: None,
+			normal_tex: None,
+			emissive: rgba!(0, 0, 0, 1),
+			emissive_tex: None,
+			metallic: 0.0,
+			roughness: 1.0,
+			metallic_roughness_tex: None,
+			alpha_mode: AlphaMode::Opaque,
+		};
+	}
+}
+
+// material with textures as indices into the model images
+#[derive(Clone, Serialize, Deserialize)]
+pub(super) struct MaterialData {
+	pub name: Option<String>,
+	pub base_color: Color,
+	pub base_color_tex: Option<usize>,
+	pub normal_tex: Option<usize>,
+	pub emissive: Color,
+	pub emissive_tex: Option<usize>,
+	pub metallic: f32,
+	pub roughness: f32,
+	pub metallic_roughness_tex: Option<usize>,
+	pub alpha_mode: AlphaMode,
+}
+
+impl MaterialData {
+
+	pub fn new() -> Self {
+		return Self {
+			name: None,
+			base_color: rgba!(1),
+			base_color_tex: None,
+			normal_tex: None,
+			emissive: rgba!(0, 0, 0, 1),
+			emissive_tex: None,
+			metallic: 0.0,
+			roughness: 1.0,
+			metallic_roughness_tex: None,
+			alpha_mode: AlphaMode::Opaque,
+		};
+	}
+
+	pub fn to_material(&self, textures: &[Texture]) -> Material {
+
+		let tex = |i: Option<usize>| i.and_then(|i| textures.get(i)).cloned();
+
+		return Material {
+			name: self.name.clone(),
+			base_color: self.base_color,
+			base_color_tex: tex(self.base_color_tex),
+			normal_tex: tex(self.normal_tex),
+			emissive: self.emissive,
+			emissive_tex: tex(self.emissive_tex),
+			metallic: self.metallic,
+			roughness: self.roughness,
+			metallic_roughness_tex: tex(self.metallic_roughness_tex),
+			alpha_mode: self.alpha_mode,
+		};
+
+	}
+
+}
+
+// material values sent with every draw, textures are bound right after u_tex and the custom textures
+#[derive(Clone, PartialEq)]
+pub(super) struct MaterialUniform {
+	pub emissive: Color,
+	pub metallic: f32,
+	pub roughness: f32,
+	pub alpha_mask: f32,
+	pub alpha_cutoff: f32,
+	pub has_normal_tex: f32,
+	pub normal_tex: Texture,
+	pub emissive_tex: Texture,
+	pub metallic_roughness_tex: Texture,
+}
+
+impl MaterialUniform {
+
+	pub fn new(m: Option<&Material>, empty: &Texture) -> Self {
+
+		let default = Material::default();
+		let m = m.unwrap_or(&default);
+
+		return Self {
+			emissive: m.emissive,
+			metallic: m.metallic,
+			roughness: m.roughness,
+			alpha_mask: match m.alpha_mode {
+				AlphaMode::Mask(_) => 1.0,
+				_ => 0.0,
+			},
+			alpha_cutoff: match m.alpha_mode {
+				AlphaMode::Mask(c) => c,
+				_ => 0.0,
+			},
+			has_normal_tex: if m.normal_tex.is_some() {
+				1.0
+			} else {
+				0.0
+			},
+			normal_tex: m.normal_tex.clone().unwrap_or(empty.clone()),
+			emissive_tex: m.emissive_tex.clone().unwrap_or(empty.clone()),
+			metallic_roughness_tex: m.metallic_roughness_tex.clone().unwrap_or(empty.clone()),
+		};
+
+	}
+
+}
+
+impl UniformLayout for MaterialUniform {
+
+	fn values(&self) -> UniformValues {
+		return vec![
+			("u_emissive", &self.emissive),
+			("u_metallic", &self.metallic),
+			("u_roughness", &self.roughness),
+			("u_alpha_mask", &self.alpha_mask),
+			("u_alpha_cutoff", &self.alpha_cutoff),
+			("u_has_normal_tex", &self.has_normal_tex),
+		];
+	}
+
+	fn textures(&self) -> Vec<&Texture> {
+		return vec![
+			&self.normal_tex,
+			&self.emissive_tex,
+			&self.metallic_roughness_tex,
+		];
+	}
+
+}
@@ -12,11 +12,17 @@ pub struct MeshData {
 /// A Buffered GPU Mesh
 #[derive(Clone, PartialEq)]
 pub struct Mesh {
-	vbuf: VertexBuffer<Vertex>,
+	vbuf: MeshBuffer,
 	ibuf: IndexBuffer,
 	count: usize,
 }
 
+#[derive(Clone, PartialEq)]
+pub(super) enum MeshBuffer {
+	Static(VertexBuffer<Vertex>),
+	Skinned(VertexBuffer<SkinnedVertex>),
+}
+
 impl Mesh {
 
 	/// create a mesh from vertices and indices
@@ -26,7 +32,21 @@ impl Mesh {
 		let ibuf = IndexBuffer::from(ctx, &indices)?;
 
 		return Ok(Self {
-			vbuf,
+			vbuf: MeshBuffer::Static(vbuf),
+			ibuf,
+			count: indices.len(),
+		});
+
+	}
+
+	/// create a skinned mesh, drawn with the skinned variant of the current shader
+	pub fn new_skinned(ctx: &impl GLCtx, verts: &[SkinnedVertex], indices: &[u32]) -> Result<Self> {
+
+		let vbuf = VertexBuffer::<SkinnedVertex>::from(ctx, &verts)?;
+		let ibuf = IndexBuffer::from(ctx, &indices)?;
+
+		return Ok(Self {
+			vbuf: MeshBuffer::Skinned(vbuf),
 			ibuf,
 			count: indices.len(),
 		});
@@ -38,7 +58,7 @@ impl Mesh {
 		return Self::new(ctx, &data.vertices, &data.indices);
 	}
 
-	pub(super) fn vbuf(&self) -> &VertexBuffer<Vertex> {
+	pub(super) fn vbuf(&self) -> &MeshBuffer {
 		return &self.vbuf;
 	}
 
@@ -137,6 +137,16 @@
 //! })?;
 //! ```
 //!
+//! Textures from [`UniformLayout::textures`](trait.UniformLayout.html#method.textures) are bound from texture unit 1 on (0 is the current texture), so point samplers to them with an `i32` value:
+//!
+//! ```ignore
+//! fn values(&self) -> UniformValues {
+//!     return vec![
+//!         ("u_noise", &1),
+//!     ];
+//! }
+//! ```
+//!
 //! custom shaders have access to these following inputs:
 //!
 //! | prefix  | type      | name          | desc                            | visibility |
@@ -145,14 +155,105 @@
 //! | varing  | vec3      | v_normal      | vertex normal                   | all        |
 //! | varing  | vec2      | v_uv          | vertex texture coord            | all        |
 //! | varing  | vec4      | v_color       | vertex color                    | all        |
+//! | varing  | vec3      | v_world_pos   | vertex position after u_model   | all        |
+//! | varing  | vec3      | v_world_normal | vertex normal after u_normal_mat | all      |
 //! | uniform | mat4      | u_model       | uniform model matrix            | vert       |
+//! | uniform | mat4      | u_normal_mat  | inverse transpose of u_model    | vert       |
 //! | uniform | mat4      | u_proj        | uniform projection matrix       | vert       |
 //! | uniform | mat4      | u_view        | uniform view matrix             | vert       |
 //! | uniform | mat4      | u_view        | uniform view matrix             | vert       |
 //! | uniform | sampler2D | u_tex         | current texture                 | frag       |
+//! | uniform | sampler2D | u_normal_tex  | material normal map             | frag       |
+//! | uniform | sampler2D | u_emissive_tex | material emissive texture      | frag       |
+//! | uniform | sampler2D | u_metallic_roughness_tex | material metallic (b) & roughness (g) | frag |
+//! | uniform | vec4      | u_emissive    | material emissive color         | frag       |
+//! | uniform | float     | u_metallic    | material metalness              | frag       |
+//! | uniform | float     | u_roughness   | material roughness              | frag       |
+//! | uniform | float     | u_alpha_mask  | 1.0 if material alpha mode is mask | frag    |
+//! | uniform | float     | u_alpha_cutoff | with alpha mask, pixels with alpha < this are discarded | frag |
+//! | uniform | float     | u_has_normal_tex | 1.0 if material has a normal map | frag    |
 //! | uniform | vec4      | u_color       | uniform color                   | frag       |
+//! | uniform | mat4[MAX_JOINTS] | u_joints | joint matrices of current skin, skinned variant only | vert |
+//! | uniform | float     | u_skinned     | 1.0 if joints are given, skinned variant only | vert |
+//! | uniform | vec4[8]   | u_light_color | light color * intensity         | frag       |
+//! | uniform | int       | u_light_count | number of active lights         | frag       |
+//! | uniform | vec3      | u_eye_pos     | camera position, when lit       | frag       |
+//! | uniform | sampler2D | u_shadow_map  | packed shadow depth             | frag       |
+//! | uniform | float     | u_has_shadow  | 1.0 if a shadow map is used     | frag       |
 //! |         | vec4()    | default_pos   | get the default vertex position | vert       |
 //! |         | vec4()    | default_color | get the default fragment color  | frag       |
+//! |         | vec4(vec4) | lit          | apply current lights to a color | frag       |
+//!
+//! Skinned meshes are drawn with a second variant of each shader, compiled on first use with `SKINNED` defined. Vertex code that reads skinning inputs directly should guard them with `#ifdef SKINNED`.
+//!
+//! ## Animation
+//!
+//! [`shapes::model`](shapes/fn.model.html) can play a single clip with `.anim()` and `.time()`. For blending use an [`Animator`](struct.Animator.html), which crossfades between clips, plays additive or masked layers on top, and can run a state machine:
+//!
+//! ```ignore
+//! // init
+//! let mut anim = gfx::Animator::new(&model);
+//!
+//! anim.add_state("idle", gfx::AnimState::new("Idle")
+//!     .transition("walk", gfx::Cond::Above("speed".into(), 0.1), 0.2))?;
+//! anim.add_state("walk", gfx::AnimState::new("Walk")
+//!     .transition("idle", gfx::Cond::Below("speed".into(), 0.1), 0.2))?;
+//! anim.set_state("idle", 0.0)?;
+//!
+//! // wave with the upper body only
+//! let arms = anim.add_layer(gfx::LayerMode::Override, model.find_node("Spine").map(|n| model.subtree(n)));
+//! anim.play_on(arms, "Wave", 0.3)?;
+//!
+//! // update
+//! anim.set_param("speed", speed);
+//! anim.update(d.app.dt());
+//!
+//! for e in anim.drain_events() {
+//!     // e.g. play a footstep sound
+//! }
+//!
+//! // draw
+//! d.gfx.draw(&shapes::model(&model).pose(&anim.pose(&model)))?;
+//! ```
+//!
+//! ## Lighting
+//!
+//! Use [`use_lights`](struct.Gfx.html#method.use_lights) to light everything inside with up to 8 [`Light`](struct.Light.html)s. It switches to a Blinn-Phong shader that also reads the current [`Material`](struct.Material.html)'s metallic, roughness and emissive (normal maps are not used yet). Custom shaders can get the same result with `lit()`:
+//!
+//! ```glsl
+//! vec4 frag() {
+//!     return lit(default_color());
+//! }
+//! ```
+//!
+//! Point and spot lights fall off with distance squared, so they usually need a higher intensity.
+//!
+//! The first directional light can cast shadow with a [`ShadowMap`](struct.ShadowMap.html). Render the scene into it with [`draw_shadow`](struct.Gfx.html#method.draw_shadow) first, then pass it to [`use_lights_ex`](struct.Gfx.html#method.use_lights_ex):
+//!
+//! ```ignore
+//! // init
+//! let shadow = gfx::ShadowMap::new(gfx, 2048)?
+//!     .bounds(vec3!(0), 12.0);
+//!
+//! // draw
+//! let lights = [
+//!     gfx::Light::ambient().intensity(0.2),
+//!     gfx::Light::directional(vec3!(-1, -2, -1)),
+//!     gfx::Light::point(vec3!(0, 2, 0)).color(rgba!(1, 0.5, 0, 1)).intensity(4.0),
+//! ];
+//!
+//! gfx.draw_shadow(&shadow, &lights, |gfx| {
+//!     return draw_scene(gfx);
+//! })?;
+//!
+//! gfx.use_cam(&cam, |gfx| {
+//!     return gfx.use_lights_ex(&lights, Some(&shadow), |gfx| {
+//!         return draw_scene(gfx);
+//!     });
+//! })?;
+//! ```
+//!
+//! Shaders from [`draw_with`](struct.Gfx.html#method.draw_with) are skipped inside `draw_shadow`, so the same drawing code can be used for both passes.
 //!
 //! ## Memory Management
 //!
@@ -174,6 +275,9 @@ export!(camera);
 export!(font);
 export!(uniform);
 export!(model);
+export!(animator);
+export!(material);
+export!(light);
 
 pub mod shapes;
 pub mod fonts;
@@ -196,6 +300,13 @@ use geom::*;
 const DRAW_COUNT: usize = 65536;
 const DEFAULT_NEAR: f32 = -4096.0;
 const DEFAULT_FAR: f32 = 4096.0;
+// size of u_joints in the skinned variant of template.vert, gles2 only guarantees 128 vertex uniform vectors
+#[cfg(not(any(web, mobile)))]
+const MAX_JOINTS: usize = 64;
+#[cfg(any(web, mobile))]
+const MAX_JOINTS: usize = 24;
+// size of u_light_* in template.frag
+const MAX_LIGHTS: usize = 8;
 
 /// The Graphics Context. See [mod-level doc](index.html) for usage.
 pub struct Gfx {
@@ -214,11 +325,16 @@ pub struct Gfx {
 
 	empty_tex: gfx::Texture,
 
-	default_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
-	cur_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
+	default_pipeline: Program,
+	lit_pipeline: Program,
+	shadow_pipeline: Program,
+	cur_pipeline: Program,
 	cur_custom_uniform: Option<UniformData>,
+	cur_lights: LightUniform,
 
+	clear_color: Color,
 	on_canvas: bool,
+	on_shadow: bool,
 
 	default_font: gfx::BitmapFont,
 
@@ -281,12 +397,10 @@ impl Gfx {
 			far: DEFAULT_FAR,
 		};
 
-		let vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", shaders::DEFAULT_VERT);
-		let frag_src = shaders::TEMPLATE_FRAG.replace("{{user}}", shaders::DEFAULT_FRAG);
-		#[cfg(any(web, mobile))]
-		let frag_src = format!("{}{}", "precision mediump float;", frag_src);
-
-		let pipeline = Pipeline::new(&gl, &vert_src, &frag_src)?;
+		let pipeline = Program::new(&gl, shaders::DEFAULT_VERT, shaders::DEFAULT_FRAG)?;
+		let lit_pipeline = Program::new(&gl, shaders::DEFAULT_VERT, shaders::LIT_FRAG)?;
+		let shadow_pipeline = Program::new(&gl, shaders::DEFAULT_VERT, shaders::SHADOW_FRAG)?;
+		let empty_tex = Texture::from_raw(&gl, 1, 1, &[255; 4])?;
 
 		let font_data = conf.default_font
 			.clone()
@@ -308,15 +422,20 @@ impl Gfx {
 			transform: mat4!(),
 
 			default_pipeline: pipeline.clone(),
+			lit_pipeline: lit_pipeline,
+			shadow_pipeline: shadow_pipeline,
 			cur_pipeline: pipeline,
 			cur_custom_uniform: None,
+			cur_lights: LightUniform::new(&[], None, &empty_tex),
 
+			clear_color: conf.clear_color,
 			on_canvas: false,
+			on_shadow: false,
 
 			draw_calls_last: 0,
 			draw_calls: 0,
 
-			empty_tex: Texture::from_raw(&gl, 1, 1, &[255; 4])?,
+			empty_tex: empty_tex,
 
 			default_font: font,
 
@@ -423,7 +542,7 @@ impl Gfx {
 		}
 
 		canvas.bind();
-		f(self)?;
+		let r = f(self);
 		self.flush();
 		canvas.unbind();
 
@@ -442,7 +561,7 @@ impl Gfx {
 			);
 		}
 
-		return Ok(());
+		return r;
 
 	}
 
@@ -454,11 +573,16 @@ impl Gfx {
 		f: impl FnOnce(&mut Self) -> Result<()>,
 	) -> Result<()> {
 
+		// everything renders depth in a shadow pass
+		if self.on_shadow {
+			return f(self);
+		}
+
 		let prev_pipeline = self.cur_pipeline.clone();
 		let prev_uniform = self.cur_custom_uniform.clone();
 
 		self.flush();
-		self.cur_pipeline = Pipeline::clone(&shader.pipeline());
+		self.cur_pipeline = shader.program().clone();
 		self.cur_custom_uniform = Some(UniformData::from_uniform(uniform));
 		f(self)?;
 		self.flush();
@@ -469,6 +593,102 @@ impl Gfx {
 
 	}
 
+	/// light everything inside with up to 8 lights, using the default lit shader unless inside [`draw_with`](#method.draw_with)
+	pub fn use_lights(
+		&mut self,
+		lights: &[Light],
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+		return self.use_lights_ex(lights, None, f);
+	}
+
+	/// like [`use_lights`](#method.use_lights), with a [`ShadowMap`](struct.ShadowMap.html) from [`draw_shadow`](#method.draw_shadow) for the first directional light
+	pub fn use_lights_ex(
+		&mut self,
+		lights: &[Light],
+		shadow: Option<&ShadowMap>,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		if lights.len() > MAX_LIGHTS {
+			return Err(format!("cannot use more than {} lights", MAX_LIGHTS));
+		}
+
+		if self.on_shadow {
+			return f(self);
+		}
+
+		let prev_pipeline = self.cur_pipeline.clone();
+		let prev_lights = self.cur_lights.clone();
+
+		self.flush();
+
+		if self.cur_custom_uniform.is_none() {
+			self.cur_pipeline = self.lit_pipeline.clone();
+		}
+
+		self.cur_lights = LightUniform::new(lights, shadow, &self.empty_tex);
+		let r = f(self);
+		self.flush();
+		self.cur_pipeline = prev_pipeline;
+		self.cur_lights = prev_lights;
+
+		return r;
+
+	}
+
+	/// render depth of everything inside from the first directional light into a [`ShadowMap`](struct.ShadowMap.html)
+	pub fn draw_shadow(
+		&mut self,
+		shadow: &ShadowMap,
+		lights: &[Light],
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		let dir = match light::shadow_caster(lights) {
+			Some((_, dir)) => dir,
+			None => return Err(format!("no directional light to cast shadow")),
+		};
+
+		let cam = shadow.light_cam(dir);
+
+		return self.draw_on(shadow.canvas(), |gfx| {
+
+			let prev_pipeline = gfx.cur_pipeline.clone();
+			let prev_uniform = gfx.cur_custom_uniform.take();
+			let prev_lights = gfx.cur_lights.clone();
+			let cc = gfx.clear_color;
+
+			unsafe {
+				gfx.gl.disable(Capability::Blend.as_glow());
+				gfx.gl.clear_color(1.0, 1.0, 1.0, 1.0);
+				gfx.clear();
+				gfx.gl.clear_color(cc.r, cc.g, cc.b, cc.a);
+			}
+
+			gfx.cur_pipeline = gfx.shadow_pipeline.clone();
+			gfx.cur_lights = LightUniform::new(&[], None, &gfx.empty_tex);
+			gfx.on_shadow = true;
+
+			// restore everything even if f fails, so later draws aren't stuck in the shadow pass
+			let r = gfx.use_cam(&cam, f);
+
+			gfx.flush();
+			gfx.on_shadow = false;
+			gfx.cur_pipeline = prev_pipeline;
+			gfx.cur_custom_uniform = prev_uniform;
+			gfx.cur_lights = prev_lights;
+
+			unsafe {
+				gfx.gl.enable(Capability::Blend.as_glow());
+			}
+
+			return r;
+
+		});
+
+	}
+
 	/// draw with stencil operations
 	pub fn draw_masked_ex(
 		&mut self,
@@ -606,12 +826,12 @@ impl Gfx {
 
 		self.apply_cam(cam);
 
-		f(self)?;
+		let r = f(self);
 
 		self.view = oview;
 		self.proj = oproj;
 
-		return Ok(());
+		return r;
 
 	}
 
@@ -704,6 +924,19 @@ impl Gfx {
 
 	}
 
+	// eye position is only needed for lighting
+	fn light_uniform(&self) -> LightUniform {
+
+		let mut lights = self.cur_lights.clone();
+
+		if lights.count > 0 {
+			lights.eye = (self.view.inverse() * vec4!(0, 0, 0, 1)).xyz();
+		}
+
+		return lights;
+
+	}
+
 	pub(crate) fn apply_cam(&mut self, cam: &dyn Camera) {
 		self.proj = cam.proj();
 		self.view = cam.view();
@@ -2,20 +2,31 @@
 
 use std::io::Cursor;
 use std::path::Path;
+use std::collections::HashSet;
 
 use super::*;
 
 type NodeID = usize;
 
-// TODO: rework anim system
+/// Transform of Each Node at a Moment
+pub type Pose = HashMap<NodeID, Transform>;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub(super) struct NodeData {
 	pub id: NodeID,
 	pub children: Vec<NodeID>,
 	pub transform: Transform,
-	pub meshes: Vec<MeshData>,
+	pub meshes: Vec<NodeMesh>,
 	pub name: Option<String>,
+	pub skin: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(super) struct NodeMesh {
+	pub data: MeshData,
+	// joints & weights of each vertex if the mesh is skinned
+	pub skin: Option<Vec<(Vec4, Vec4)>>,
+	pub material: Option<usize>,
 }
 
 /// Data for Creating [`Model`](`struct.Model.html`)
@@ -23,126 +34,308 @@ pub(super) struct NodeData {
 pub struct ModelData {
 	nodes: HashMap<NodeID, NodeData>,
 	root_nodes: Vec<NodeID>,
-	img: Option<img::Image>,
-	anims: HashMap<NodeID, Anim>,
-	anim_len: f32,
+	images: Vec<img::Image>,
+	materials: Vec<MaterialData>,
+	anims: Vec<AnimClip>,
+	skins: Vec<Skin>,
 }
 
 #[derive(Clone)]
 pub(super) struct Node {
-	meshes: Vec<Mesh>,
+	meshes: Vec<(Mesh, Option<usize>)>,
 	id: NodeID,
 	name: Option<String>,
 	children: Vec<NodeID>,
 	transform: Transform,
+	skin: Option<usize>,
 }
 
 impl Node {
 	pub fn transform(&self) -> Transform {
 		return self.transform;
 	}
-	pub fn meshes(&self) -> &[Mesh] {
+	pub fn meshes(&self) -> &[(Mesh, Option<usize>)] {
 		return &self.meshes;
 	}
 	pub fn children(&self) -> &[NodeID] {
 		return &self.children;
 	}
+	pub fn skin(&self) -> Option<usize> {
+		return self.skin;
+	}
 }
 
-type Track<T> = Vec<(f32, T)>;
-
-/// 3D Animation Data
+/// Joints That Deform a Mesh
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Anim {
-	pos: Track<Vec3>,
-	rot: Track<Vec4>,
-	scale: Track<Vec3>,
+pub struct Skin {
+	joints: Vec<NodeID>,
+	inverse_binds: Vec<Mat4>,
 }
 
-fn get_track_val<T: Lerp>(track: &Track<T>, t: f32) -> Option<T> {
+impl Skin {
 
-	if track.is_empty() {
-		return None;
+	/// the nodes that act as joints, vertex joint indices point into this
+	pub fn joints(&self) -> &[NodeID] {
+		return &self.joints;
 	}
 
-	if let Some((k, _)) = track.first() {
-		if *k > t {
-			return None;
-		}
+	/// get the matrices to send to the shader from world transforms of each node
+	pub fn joint_mats(&self, world: &HashMap<NodeID, Mat4>) -> Vec<Mat4> {
+		return self.joints
+			.iter()
+			.zip(&self.inverse_binds)
+			.map(|(id, ibm)| world.get(id).cloned().unwrap_or(mat4!()) * *ibm)
+			.collect();
 	}
 
-	if let Some((k, val)) = track.last() {
-		if *k <= t {
-			return Some(*val);
-		}
+}
+
+/// How Values Change Between Keyframes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Interpolation {
+	Linear,
+	Step,
+	CubicSpline,
+}
+
+/// Keyframes of a Single Property
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Track<T> {
+	interpolation: Interpolation,
+	frames: Vec<(f32, T)>,
+	// (in tangent, out tangent) of each frame, only for cubic spline
+	tangents: Vec<(T, T)>,
+}
+
+impl<T: Lerp> Track<T> {
+
+	fn new() -> Self {
+		return Self {
+			interpolation: Interpolation::Linear,
+			frames: vec![],
+			tangents: vec![],
+		};
 	}
 
-	for i in 0..track.len() - 1 {
+	fn from_gltf(interpolation: Interpolation, times: &[f32], values: Vec<T>) -> Result<Self> {
+
+		let mut frames = Vec::with_capacity(times.len());
+		let mut tangents = vec![];
+
+		if interpolation == Interpolation::CubicSpline {
+
+			if values.len() != times.len() * 3 {
+				return Err(format!("failed to read anim from glb"));
+			}
+
+			for (t, v) in times.iter().zip(values.chunks(3)) {
+				frames.push((*t, v[1]));
+				tangents.push((v[0], v[2]));
+			}
+
+		} else {
+
+			if values.len() != times.len() {
+				return Err(format!("failed to read anim from glb"));
+			}
 
-		let (k1, pos1) = track[i];
-		let (k2, pos2) = track[i + 1];
+			frames = times.iter().cloned().zip(values).collect();
 
-		if t >= k1 && t <= k2 {
-			let dt = t - k1;
-			return Some(pos1.lerp(pos2, dt));
 		}
 
-	}
+		return Ok(Self {
+			interpolation,
+			frames,
+			tangents,
+		});
 
-	return None;
+	}
 
-}
+	pub fn interpolation(&self) -> Interpolation {
+		return self.interpolation;
+	}
 
-impl Anim {
+	pub fn is_empty(&self) -> bool {
+		return self.frames.is_empty();
+	}
 
 	pub fn len(&self) -> f32 {
-
-		let t1 = self.pos
+		return self.frames
 			.last()
 			.map(|(t, _)| *t)
 			.unwrap_or(0.0);
+	}
 
-		let t2 = self.rot
-			.last()
-			.map(|(t, _)| *t)
-			.unwrap_or(0.0);
+	/// get value at a time, mix is used to blend 2 keyframes in linear mode
+	pub fn sample(&self, t: f32, mix: impl Fn(T, T, f32) -> T) -> Option<T> {
 
-		let t3 = self.scale
-			.last()
-			.map(|(t, _)| *t)
-			.unwrap_or(0.0);
+		let (k1, _) = self.frames.first()?;
+		let (k2, _) = self.frames.last()?;
 
-		return t1
-			.max(t2)
-			.max(t3)
-			;
+		// holds the first / last value outside of the range
+		if t <= *k1 {
+			return self.frames.first().map(|(_, v)| *v);
+		}
+
+		if t >= *k2 {
+			return self.frames.last().map(|(_, v)| *v);
+		}
+
+		// first frame after t
+		let i = self.frames
+			.iter()
+			.position(|(k, _)| *k > t)?;
+
+		let (k1, v1) = self.frames[i - 1];
+		let (k2, v2) = self.frames[i];
+		let dt = k2 - k1;
+
+		let f = if dt > 0.0 {
+			(t - k1) / dt
+		} else {
+			0.0
+		};
+
+		return match self.interpolation {
+
+			Interpolation::Step => Some(v1),
+			Interpolation::Linear => Some(mix(v1, v2, f)),
+
+			Interpolation::CubicSpline => {
+
+				let (_, out1) = self.tangents.get(i - 1)?;
+				let (in2, _) = self.tangents.get(i)?;
+				let f2 = f * f;
+				let f3 = f2 * f;
+
+				Some(
+					v1 * (2.0 * f3 - 3.0 * f2 + 1.0)
+					+ *out1 * ((f3 - 2.0 * f2 + f) * dt)
+					+ v2 * (-2.0 * f3 + 3.0 * f2)
+					+ *in2 * ((f3 - f2) * dt)
+				)
+
+			},
+
+		};
 
 	}
 
+}
+
+/// 3D Animation Data
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Anim {
+	pos: Track<Vec3>,
+	rot: Track<Vec4>,
+	scale: Track<Vec3>,
+}
+
+impl Anim {
+
+	fn new() -> Self {
+		return Self {
+			pos: Track::new(),
+			rot: Track::new(),
+			scale: Track::new(),
+		};
+	}
+
+	pub fn len(&self) -> f32 {
+		return self.pos.len()
+			.max(self.rot.len())
+			.max(self.scale.len())
+			;
+	}
+
 	pub fn get_transform(&self, t: f32) -> (Option<Vec3>, Option<Vec4>, Option<Vec3>) {
 
 		return (
-			get_track_val(&self.pos, t),
-			get_track_val(&self.rot, t),
-			get_track_val(&self.scale, t),
+			self.pos.sample(t, Vec3::lerp),
+			// cubic spline doesn't keep the quaternion normalized
+			self.rot.sample(t, Vec4::slerp).map(Vec4::unit),
+			self.scale.sample(t, Vec3::lerp),
 		);
 
 	}
 
+	/// apply to a transform, properties without keyframes are kept
+	pub fn apply(&self, t: f32, tr: Transform) -> Transform {
+
+		let (pos, rot, scale) = self.get_transform(t);
+
+		return Transform {
+			pos: pos.unwrap_or(tr.pos),
+			rot: rot.unwrap_or(tr.rot),
+			scale: scale.unwrap_or(tr.scale),
+		};
+
+	}
+
+}
+
+/// Named Animation Clip
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnimClip {
+	name: Option<String>,
+	channels: HashMap<NodeID, Anim>,
+	len: f32,
+}
+
+impl AnimClip {
+
+	fn new(name: Option<String>, channels: HashMap<NodeID, Anim>) -> Self {
+
+		let len = channels
+			.values()
+			.map(|a| a.len())
+			.fold(0.0, f32::max);
+
+		return Self {
+			name,
+			channels,
+			len,
+		};
+
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		return self.name.as_deref();
+	}
+
+	/// length in seconds
+	pub fn len(&self) -> f32 {
+		return self.len;
+	}
+
+	/// get the animation of a node
+	pub fn get(&self, id: NodeID) -> Option<&Anim> {
+		return self.channels.get(&id);
+	}
+
+	/// write transforms of every animated node at a time into a pose
+	pub fn sample(&self, t: f32, pose: &mut Pose) {
+		for (id, anim) in &self.channels {
+			if let Some(tr) = pose.get_mut(id) {
+				*tr = anim.apply(t, *tr);
+			}
+		}
+	}
+
 }
 
 /// 3D Model
 #[derive(Clone)]
 pub struct Model {
 	nodes: HashMap<NodeID, Node>,
-	anims: HashMap<NodeID, Anim>,
-	anim_len: f32,
+	anims: Vec<AnimClip>,
+	skins: Vec<Skin>,
+	materials: Vec<Material>,
 	root_nodes: Vec<NodeID>,
 	bbox: BBox,
-	texture: Option<Texture>,
 }
 
-fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf::Node) {
+fn read_gltf_node(buffers: &[Vec<u8>], nodes: &mut HashMap<NodeID, NodeData>, node: gltf::Node) {
 
 	let id = node.index();
 	let name = node.name();
@@ -160,7 +353,7 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 			.primitives()
 			.map(|prim| {
 
-			let reader = prim.reader(|_| Some(&bin));
+			let reader = prim.reader(|b| buffers.get(b.index()).map(|d| &d[..]));
 
 			let positions = reader
 				.read_positions()
@@ -211,6 +404,24 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 						.collect::<Vec<Vec2>>();
 				}).unwrap_or_default();
 
+			let joints = reader
+				.read_joints(0)
+				.map(|joints| {
+					return joints
+						.into_u16()
+						.map(|j| vec4!(j[0], j[1], j[2], j[3]))
+						.collect::<Vec<Vec4>>();
+				}).unwrap_or_default();
+
+			let weights = reader
+				.read_weights(0)
+				.map(|weights| {
+					return weights
+						.into_f32()
+						.map(|w| vec4!(w[0], w[1], w[2], w[3]))
+						.collect::<Vec<Vec4>>();
+				}).unwrap_or_default();
+
 			let mut verts = Vec::with_capacity(positions.len());
 
 			for i in 0..positions.len() {
@@ -226,9 +437,24 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 
 			}
 
-			return MeshData {
-				vertices: verts,
-				indices: indices,
+			let skin = if joints.is_empty() || weights.is_empty() {
+				None
+			} else {
+				Some((0..positions.len()).map(|i| {
+					return (
+						joints.get(i).cloned().unwrap_or(vec4!(0)),
+						weights.get(i).cloned().unwrap_or(vec4!(0)),
+					);
+				}).collect())
+			};
+
+			return NodeMesh {
+				data: MeshData {
+					vertices: verts,
+					indices: indices,
+				},
+				skin: skin,
+				material: prim.material().index(),
 			};
 
 		}).collect();
@@ -241,12 +467,83 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 		children: node.children().map(|c| c.index()).collect(),
 		transform: transform,
 		meshes: meshes,
+		skin: node.skin().map(|s| s.index()),
 	});
 
 	for c in node.children() {
-		read_gltf_node(bin, nodes, c);
+		read_gltf_node(buffers, nodes, c);
+	}
+
+}
+
+// decode a data uri, or read a file relative to the gltf
+fn read_gltf_uri(uri: &str, read: &impl Fn(&str) -> Result<Vec<u8>>) -> Result<Vec<u8>> {
+
+	if uri.starts_with("data:") {
+
+		let comma = uri
+			.find(',')
+			.ok_or_else(|| format!("invalid data uri"))?;
+
+		if !uri[..comma].ends_with(";base64") {
+			return Err(format!("only base64 data uris are supported"));
+		}
+
+		return base64::decode(&uri[comma + 1..])
+			.map_err(|e| format!("failed to decode base64: {}", e));
+
 	}
 
+	return read(&percent_decode(uri));
+
+}
+
+// don't put a whole data uri in errors
+fn gltf_uri_name(uri: &str) -> &str {
+	if uri.starts_with("data:") {
+		return "data uri";
+	} else {
+		return uri;
+	}
+}
+
+// uris can have escaped chars like %20
+fn percent_decode(s: &str) -> String {
+
+	let bytes = s.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+
+		if bytes[i] == b'%' && i + 2 < bytes.len() {
+
+			let hex = &bytes[i + 1..i + 3];
+
+			// from_str_radix also takes a sign, like "%+1"
+			if hex.iter().all(|c| c.is_ascii_hexdigit()) {
+
+				let b = std::str::from_utf8(hex)
+					.ok()
+					.and_then(|h| u8::from_str_radix(h, 16).ok());
+
+				if let Some(b) = b {
+					out.push(b);
+					i += 3;
+					continue;
+				}
+
+			}
+
+		}
+
+		out.push(bytes[i]);
+		i += 1;
+
+	}
+
+	return String::from_utf8_lossy(&out).into_owned();
+
 }
 
 impl Model {
@@ -272,16 +569,33 @@ impl Model {
 				let img_src = fs::read(&path).ok();
 				let img_src = img_src.as_deref();
 
-				let data = gfx::Model::load_obj(&obj_src, mtl_src, img_src)?;
+				// textures in mtl are relative to the obj
+				let dir = path
+					.parent()
+					.map(|p| p.to_owned())
+					.unwrap_or_default();
+
+				let data = gfx::Model::load_obj_with(&obj_src, mtl_src, img_src, |p| {
+					return fs::read(dir.join(p)).ok();
+				})?;
 
 				return Ok(data);
 
 			},
 
-			"glb" => {
+			"gltf" | "glb" => {
 
 				let bytes = fs::read(&path)?;
-				let data = gfx::Model::load_glb(&bytes)?;
+
+				// buffers and images are relative to the gltf
+				let dir = path
+					.parent()
+					.map(|p| p.to_owned())
+					.unwrap_or_default();
+
+				let data = gfx::Model::load_gltf(&bytes, |uri| {
+					return fs::read(dir.join(uri));
+				})?;
 
 				return Ok(data);
 
@@ -308,10 +622,15 @@ impl Model {
 			name: None,
 			children: vec![],
 			transform: Transform::new(),
-			meshes: vec![MeshData {
-				vertices: verts,
-				indices: indices,
+			meshes: vec![NodeMesh {
+				data: MeshData {
+					vertices: verts,
+					indices: indices,
+				},
+				skin: None,
+				material: None,
 			}],
+			skin: None,
 		};
 
 		return ModelData {
@@ -319,76 +638,160 @@ impl Model {
 				0 => node,
 			],
 			root_nodes: vec![0],
-			img: None,
-			anims: hmap![],
-			anim_len: 0.0,
+			images: vec![],
+			materials: vec![],
+			anims: vec![],
+			skins: vec![],
 		};
 
 	}
 
 	/// load [`ModelData`](struct.ModelData.html) from glb bytes
 	pub fn load_glb(bytes: &[u8]) -> Result<ModelData> {
+		return Self::load_gltf(bytes, |uri| {
+			return Err(format!("cannot read external file {} from glb bytes, use load_gltf", uri));
+		});
+	}
+
+	/// load [`ModelData`](struct.ModelData.html) from gltf or glb bytes, external buffers and images are read with the callback
+	pub fn load_gltf(bytes: &[u8], read: impl Fn(&str) -> Result<Vec<u8>>) -> Result<ModelData> {
 
-		use gltf::Glb;
 		use gltf::Gltf;
 
 		// init
-		let glb = Glb::from_slice(bytes)
-			.map_err(|_| format!("failed to parse glb"))?;
-		let document = Gltf::from_slice(&glb.json)
-			.map_err(|_| format!("failed to parse document from glb"))?;
-		let bin = glb.bin
-			.ok_or_else(|| format!("failed to parse bin from glb"))?;
+		let gltf = Gltf::from_slice(bytes)
+			.map_err(|e| format!("failed to parse gltf: {}", e))?;
+		let document = gltf.document;
+		let mut blob = gltf.blob;
+
+		// buffers
+		use gltf::buffer::Source as BufferSource;
+
+		let mut buffers = Vec::with_capacity(document.buffers().len());
+
+		for b in document.buffers() {
+
+			let (name, data) = match b.source() {
+				BufferSource::Bin => {
+					let data = blob
+						.take()
+						.ok_or_else(|| format!("buffer {} points to a missing glb bin chunk", b.index()))?;
+					(format!("glb bin"), data)
+				},
+				BufferSource::Uri(uri) => {
+					let data = read_gltf_uri(uri, &read)
+						.map_err(|e| format!("failed to load buffer {} ({}): {}", b.index(), gltf_uri_name(uri), e))?;
+					(gltf_uri_name(uri).to_string(), data)
+				},
+			};
+
+			if data.len() < b.length() {
+				return Err(format!(
+					"buffer {} ({}) has {} bytes, expected {}",
+					b.index(),
+					name,
+					data.len(),
+					b.length(),
+				));
+			}
+
+			buffers.push(data);
+
+		}
+
+		let get_buffer = |b: gltf::Buffer| buffers.get(b.index()).map(|d| &d[..]);
 
 		// image
 		use gltf::image::Source;
 
-		let mut img = None;
+		let mut images = Vec::with_capacity(document.images().len());
 
 		for i in document.images() {
 
-			match i.source() {
+			let img = match i.source() {
 
 				Source::View { view, .. } => {
 
 					let offset = view.offset();
 					let len = view.length();
-					let buf = &bin[offset..offset + len];
+					let buf = buffers[view.buffer().index()]
+						.get(offset..offset + len)
+						.ok_or_else(|| format!("image {} is out of buffer bounds", i.index()))?;
 
-					img = Some(img::Image::from_bytes(buf)?);
+					img::Image::from_bytes(buf)?
 
 				},
 
-				_ => {},
+				Source::Uri { uri, .. } => {
 
-			}
+					let buf = read_gltf_uri(uri, &read)
+						.map_err(|e| format!("failed to load image {} ({}): {}", i.index(), gltf_uri_name(uri), e))?;
+
+					img::Image::from_bytes(&buf)?
+
+				},
+
+			};
+
+			images.push(img);
 
 		}
 
+		// materials
+		use gltf::material::AlphaMode as GltfAlphaMode;
+
+		let img_id = |t: gltf::texture::Texture| Some(t.source().index());
+
+		let materials = document
+			.materials()
+			.map(|m| {
+
+				let pbr = m.pbr_metallic_roughness();
+				let c = pbr.base_color_factor();
+				let e = m.emissive_factor();
+
+				return MaterialData {
+					name: m.name().map(String::from),
+					// linear color space like vertex colors
+					base_color: rgba!(c[0], c[1], c[2], c[3]).to_srgb(),
+					base_color_tex: pbr.base_color_texture().and_then(|t| img_id(t.texture())),
+					normal_tex: m.normal_texture().and_then(|t| img_id(t.texture())),
+					emissive: rgba!(e[0], e[1], e[2], 1).to_srgb(),
+					emissive_tex: m.emissive_texture().and_then(|t| img_id(t.texture())),
+					metallic: pbr.metallic_factor(),
+					roughness: pbr.roughness_factor(),
+					metallic_roughness_tex: pbr.metallic_roughness_texture().and_then(|t| img_id(t.texture())),
+					alpha_mode: match m.alpha_mode() {
+						GltfAlphaMode::Opaque => AlphaMode::Opaque,
+						GltfAlphaMode::Mask => AlphaMode::Mask(m.alpha_cutoff()),
+						GltfAlphaMode::Blend => AlphaMode::Blend,
+					},
+				};
+
+			})
+			.collect::<Vec<MaterialData>>();
+
 		// anims
-		let mut anims: HashMap<NodeID, Anim> = hmap![];
+		use gltf::animation::util::ReadOutputs;
+
+		let mut anims = vec![];
 
 		for a in document.animations() {
 
+			let mut channels: HashMap<NodeID, Anim> = hmap![];
+
 			for c in a.channels() {
 
-				let reader = c.reader(|_| Some(&bin));
+				let reader = c.reader(get_buffer);
 				let node_id = c.target().node().index();
-				let sampler = c.sampler();
-
-				let mut anim = anims.entry(node_id).or_insert(Anim {
-					pos: vec![],
-					rot: vec![],
-					scale: vec![],
-				});
+				let anim = channels.entry(node_id).or_insert_with(Anim::new);
 
-				// TODO
-				use gltf::animation::Interpolation;
+				use gltf::animation::Interpolation as GltfInterpolation;
 
-				match sampler.interpolation() {
-					Interpolation::Linear => {},
-					Interpolation::Step => {},
-					Interpolation::CubicSpline => {},
+				let interpolation = match c.sampler().interpolation() {
+					GltfInterpolation::Linear => Interpolation::Linear,
+					GltfInterpolation::Step => Interpolation::Step,
+					GltfInterpolation::CubicSpline => Interpolation::CubicSpline,
 				};
 
 				let frames: Vec<f32> = reader
@@ -396,43 +799,30 @@ impl Model {
 					.ok_or_else(|| format!("failed to read anim"))?
 					.collect();
 
-				use gltf::animation::util::ReadOutputs;
-
 				match reader
 					.read_outputs()
 					.ok_or_else(|| format!("failed to read anim"))? {
 
 					ReadOutputs::Translations(translations) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in translations.enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec3!(v[0], v[1], v[2])));
-						}
-						anim.pos = values;
+						let values = translations
+							.map(|v| vec3!(v[0], v[1], v[2]))
+							.collect();
+						anim.pos = Track::from_gltf(interpolation, &frames, values)?;
 					}
 
 					ReadOutputs::Rotations(rotations) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in rotations.into_f32().enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec4!(v[0], v[1], v[2], v[3])));
-						}
-						anim.rot = values;
+						let values = rotations
+							.into_f32()
+							.map(|v| vec4!(v[0], v[1], v[2], v[3]))
+							.collect();
+						anim.rot = Track::from_gltf(interpolation, &frames, values)?;
 					}
 
 					ReadOutputs::Scales(scales) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in scales.enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec3!(v[0], v[1], v[2])));
-						}
-						anim.scale = values;
+						let values = scales
+							.map(|v| vec3!(v[0], v[1], v[2]))
+							.collect();
+						anim.scale = Track::from_gltf(interpolation, &frames, values)?;
 					}
 
 					_ => {}
@@ -441,12 +831,44 @@ impl Model {
 
 			}
 
+			anims.push(AnimClip::new(a.name().map(String::from), channels));
+
 		}
 
-		let mut anim_len = 0.0f32;
+		// skins
+		let mut skins = vec![];
+
+		for s in document.skins() {
+
+			let joints = s.joints()
+				.map(|j| j.index())
+				.collect::<Vec<NodeID>>();
+
+			if joints.len() > MAX_JOINTS {
+				return Err(format!("skin has {} joints, max is {}", joints.len(), MAX_JOINTS));
+			}
+
+			let inverse_binds = s
+				.reader(get_buffer)
+				.read_inverse_bind_matrices()
+				.map(|mats| {
+					return mats
+						.map(|m| Mat4::new([
+							m[0][0], m[0][1], m[0][2], m[0][3],
+							m[1][0], m[1][1], m[1][2], m[1][3],
+							m[2][0], m[2][1], m[2][2], m[2][3],
+							m[3][0], m[3][1], m[3][2], m[3][3],
+						]))
+						.collect::<Vec<Mat4>>();
+				})
+				// defaults to identity if not present
+				.unwrap_or_else(|| vec![mat4!(); joints.len()]);
+
+			skins.push(Skin {
+				joints,
+				inverse_binds,
+			});
 
-		for a in anims.values() {
-			anim_len = anim_len.max(a.len());
 		}
 
 		// mesh
@@ -456,29 +878,131 @@ impl Model {
 		for s in document.scenes() {
 			for n in s.nodes() {
 				root_nodes.push(n.index());
-				read_gltf_node(&bin, &mut nodes, n);
+				read_gltf_node(&buffers, &mut nodes, n);
 			}
 		}
 
 		return Ok(ModelData {
 			nodes,
 			root_nodes,
-			img,
+			images,
+			materials,
 			anims,
-			anim_len,
+			skins,
 		});
 
 	}
 
-	/// load [`ModelData`](struct.ModelData.html) from obj file
+	/// load [`ModelData`](struct.ModelData.html) from obj file, img is used as the texture for materials without one
 	pub fn load_obj(obj: &str, mtl: Option<&str>, img: Option<&[u8]>) -> Result<ModelData> {
+		return Self::load_obj_with(obj, mtl, img, |_| None);
+	}
 
-		let (models, materials) = tobj::load_obj_buf(&mut Cursor::new(obj), true, |_| {
+	/// load [`ModelData`](struct.ModelData.html) from obj file, textures referenced in mtl are read with the callback
+	pub fn load_obj_with(
+		obj: &str,
+		mtl: Option<&str>,
+		img: Option<&[u8]>,
+		read: impl Fn(&str) -> Option<Vec<u8>>,
+	) -> Result<ModelData> {
+
+		let (models, mtls) = tobj::load_obj_buf(&mut Cursor::new(obj), true, |_| {
 			return mtl
 				.map(|m| tobj::load_mtl_buf(&mut Cursor::new(m)))
 				.unwrap_or(Ok((vec![], hmap![])));
 		}).map_err(|_| format!("failed to parse obj"))?;
 
+		let mut images = vec![];
+
+		let default_tex = if let Some(bytes) = img {
+			images.push(img::Image::from_bytes(bytes)?);
+			Some(0)
+		} else {
+			None
+		};
+
+		// path -> image index
+		let mut img_ids: HashMap<String, usize> = hmap![];
+
+		let mut load_img = |path: &str| -> Result<Option<usize>> {
+
+			if path.is_empty() {
+				return Ok(None);
+			}
+
+			if let Some(id) = img_ids.get(path) {
+				return Ok(Some(*id));
+			}
+
+			return match read(path) {
+				Some(bytes) => {
+					images.push(img::Image::from_bytes(&bytes)?);
+					img_ids.insert(path.to_owned(), images.len() - 1);
+					Ok(Some(images.len() - 1))
+				},
+				None => Ok(None),
+			};
+
+		};
+
+		let mut materials = Vec::with_capacity(mtls.len() + 1);
+
+		for m in &mtls {
+
+			let param = |k: &str| {
+				return m.unknown_param
+					.get(k)
+					.map(|v| v
+						.split_whitespace()
+						.filter_map(|n| n.parse::<f32>().ok())
+						.collect::<Vec<f32>>()
+					)
+					.unwrap_or_default();
+			};
+
+			// tobj gives 0 if "d" is missing
+			let alpha = if m.dissolve > 0.0 {
+				m.dissolve
+			} else {
+				1.0
+			};
+
+			let emissive = match param("Ke").as_slice() {
+				[r, g, b, ..] => rgba!(*r, *g, *b, 1),
+				_ => rgba!(0, 0, 0, 1),
+			};
+
+			materials.push(MaterialData {
+				name: Some(m.name.clone()),
+				base_color: rgba!(m.diffuse[0], m.diffuse[1], m.diffuse[2], alpha),
+				base_color_tex: load_img(&m.diffuse_texture)?.or(default_tex),
+				normal_tex: load_img(&m.normal_texture)?,
+				emissive: emissive,
+				emissive_tex: load_img(m.unknown_param.get("map_Ke").map(|s| s.as_str()).unwrap_or(""))?,
+				// pbr extension, or roughness from specular exponent
+				metallic: param("Pm").first().cloned().unwrap_or(0.0),
+				roughness: param("Pr")
+					.first()
+					.cloned()
+					.unwrap_or_else(|| f32::sqrt(2.0 / (m.shininess + 2.0))),
+				metallic_roughness_tex: None,
+				alpha_mode: if alpha < 1.0 {
+					AlphaMode::Blend
+				} else {
+					AlphaMode::Opaque
+				},
+			});
+
+		}
+
+		// for meshes without a material
+		let default_mat = default_tex.map(|t| {
+			let mut m = MaterialData::new();
+			m.base_color_tex = Some(t);
+			materials.push(m);
+			return materials.len() - 1;
+		});
+
 		let mut root_nodes = Vec::with_capacity(models.len());
 		let mut nodes = HashMap::with_capacity(models.len());
 
@@ -504,14 +1028,6 @@ impl Model {
 					.collect()
 			};
 
-			let mcolor = m.material_id
-				.map(|id| materials.get(id))
-				.flatten()
-				.map(|m| m.diffuse)
-				.map(|c| rgba!(c[0], c[1], c[2], 1))
-				.unwrap_or(rgba!(1))
-				;
-
 			for i in 0..vert_count {
 
 				let vx = m.positions[i * 3 + 0];
@@ -525,36 +1041,40 @@ impl Model {
 					pos: vec3!(vx, vy, vz),
 					normal: normals[i],
 					uv: vec2!(tx, 1.0 - ty),
-					color: mcolor,
+					color: rgba!(1),
 				});
 
 			}
 
+			let material = m.material_id
+				.filter(|id| *id < mtls.len())
+				.or(default_mat);
+
 			nodes.insert(i, NodeData {
 				id: i,
 				name: None,
 				children: vec![],
 				transform: gfx::Transform::new(),
-				meshes: vec![MeshData {
-					vertices: verts,
-					indices: m.indices,
+				meshes: vec![NodeMesh {
+					data: MeshData {
+						vertices: verts,
+						indices: m.indices,
+					},
+					skin: None,
+					material: material,
 				}],
+				skin: None,
 			});
 
 		}
 
-		let img = if let Some(bytes) = img {
-			Some(img::Image::from_bytes(bytes)?)
-		} else {
-			None
-		};
-
 		return Ok(ModelData {
 			nodes,
 			root_nodes,
-			img,
-			anims: hmap![],
-			anim_len: 0.0,
+			images,
+			materials,
+			anims: vec![],
+			skins: vec![],
 		});
 
 	}
@@ -564,14 +1084,18 @@ impl Model {
 
 		let bbox = get_bbox(&data);
 
-		let tex = if let Some(img) = data.img {
-			Some(Texture::from_img(ctx, img)?)
-		} else {
-			None
-		};
+		let textures = data.images
+			.into_iter()
+			.map(|img| Texture::from_img(ctx, img))
+			.collect::<Result<Vec<Texture>>>()?;
+
+		let materials = data.materials
+			.iter()
+			.map(|m| m.to_material(&textures))
+			.collect();
 
 		let anims = data.anims;
-		let anim_len = data.anim_len;
+		let skins = data.skins;
 		let root_nodes = data.root_nodes;
 
 		let nodes = data.nodes
@@ -581,8 +1105,34 @@ impl Model {
 				let meshes = node.meshes
 					.into_iter()
 					// TODO: don't unwrap here
-					.map(|m| Mesh::from_meshdata(ctx, &m).unwrap())
-					.collect::<Vec<Mesh>>();
+					.map(|m| {
+
+						let mesh = match m.skin {
+							Some(skin) => {
+
+								let verts = m.data.vertices
+									.iter()
+									.zip(skin)
+									.map(|(v, (joints, weights))| SkinnedVertex {
+										pos: v.pos,
+										uv: v.uv,
+										normal: v.normal,
+										color: v.color,
+										joints: joints,
+										weights: weights,
+									})
+									.collect::<Vec<SkinnedVertex>>();
+
+								Mesh::new_skinned(ctx, &verts, &m.data.indices)
+
+							},
+							None => Mesh::from_meshdata(ctx, &m.data),
+						};
+
+						return (mesh.unwrap(), m.material);
+
+					})
+					.collect::<Vec<(Mesh, Option<usize>)>>();
 
 				return (id, Node {
 					id: node.id,
@@ -590,6 +1140,7 @@ impl Model {
 					children: node.children,
 					transform: node.transform,
 					meshes: meshes,
+					skin: node.skin,
 				});
 
 			})
@@ -598,10 +1149,10 @@ impl Model {
 		return Ok(Self {
 			bbox: bbox,
 			nodes,
-			anim_len,
 			anims,
+			skins,
+			materials,
 			root_nodes,
-			texture: tex,
 		});
 
 	}
@@ -635,20 +1186,96 @@ impl Model {
 		return self.nodes.get(&id);
 	}
 
-	pub fn get_anim(&self, id: NodeID) -> Option<&Anim> {
-		return self.anims.get(&id);
+	/// find a node by name
+	pub fn find_node(&self, name: &str) -> Option<NodeID> {
+		return self.nodes
+			.values()
+			.find(|n| n.name.as_deref() == Some(name))
+			.map(|n| n.id);
 	}
 
-	pub fn anim_len(&self) -> f32 {
-		return self.anim_len;
+	/// get a node and all its descendants, used for masking animation layers
+	pub fn subtree(&self, id: NodeID) -> HashSet<NodeID> {
+
+		let mut set = hset![];
+		let mut stack = vec![id];
+
+		while let Some(id) = stack.pop() {
+			if let Some(node) = self.nodes.get(&id) {
+				if set.insert(id) {
+					stack.extend(&node.children);
+				}
+			}
+		}
+
+		return set;
+
+	}
+
+	/// get all animation clips
+	pub fn anims(&self) -> &[AnimClip] {
+		return &self.anims;
+	}
+
+	/// get an animation clip by name
+	pub fn get_anim(&self, name: &str) -> Option<&AnimClip> {
+		return self.anims
+			.iter()
+			.find(|a| a.name() == Some(name));
+	}
+
+	pub fn skins(&self) -> &[Skin] {
+		return &self.skins;
+	}
+
+	/// get the rest transform of every node
+	pub fn rest_pose(&self) -> Pose {
+		return self.nodes
+			.iter()
+			.map(|(id, n)| (*id, n.transform))
+			.collect();
+	}
+
+	/// get the world transform of every node from a pose, relative to the model
+	pub fn world_transforms(&self, pose: &Pose) -> HashMap<NodeID, Mat4> {
+
+		let mut world = HashMap::with_capacity(self.nodes.len());
+
+		for id in &self.root_nodes {
+			self.world_transforms_inner(pose, mat4!(), *id, &mut world);
+		}
+
+		return world;
+
+	}
+
+	fn world_transforms_inner(&self, pose: &Pose, parent: Mat4, id: NodeID, world: &mut HashMap<NodeID, Mat4>) {
+
+		if let Some(node) = self.nodes.get(&id) {
+
+			let tr = pose
+				.get(&id)
+				.unwrap_or(&node.transform);
+
+			let tr = parent * tr.as_mat4();
+
+			world.insert(id, tr);
+
+			for c in &node.children {
+				self.world_transforms_inner(pose, tr, *c, world);
+			}
+
+		}
+
 	}
 
 	pub fn root_nodes(&self) -> &[NodeID] {
 		return &self.root_nodes;
 	}
 
-	pub fn texture(&self) -> Option<&Texture> {
-		return self.texture.as_ref();
+	/// get all materials, meshes refer to them by index
+	pub fn materials(&self) -> &[Material] {
+		return &self.materials;
 	}
 
 	/// get center position
@@ -679,7 +1306,7 @@ fn get_bbox_inner(
 
 			for m in &node.meshes {
 
-				for v in &m.vertices {
+				for v in &m.data.vertices {
 
 					let pos = tr * v.pos;
 
@@ -713,3 +1340,29 @@ fn get_bbox(model: &ModelData) -> BBox {
 
 }
 
+#[test]
+fn gltf_uri() {
+
+	let read = |p: &str| -> Result<Vec<u8>> {
+		return Ok(p.as_bytes().to_vec());
+	};
+
+	assert_eq!(read_gltf_uri("data:application/octet-stream;base64,AQID", &read), Ok(vec![1, 2, 3]));
+	assert!(read_gltf_uri("data:application/octet-stream,abc", &read).is_err());
+	assert!(read_gltf_uri("data:application/octet-stream;base64", &read).is_err());
+	assert!(read_gltf_uri("data:application/octet-stream;base64,!!", &read).is_err());
+	assert_eq!(read_gltf_uri("tex/a%20b.png", &read), Ok(b"tex/a b.png".to_vec()));
+
+	assert_eq!(gltf_uri_name("data:application/octet-stream;base64,AQID"), "data uri");
+	assert_eq!(gltf_uri_name("a.bin"), "a.bin");
+
+	assert_eq!(percent_decode("a%20b%2Fc"), "a b/c");
+	assert_eq!(percent_decode("a%2"), "a%2");
+	assert_eq!(percent_decode("a%"), "a%");
+	assert_eq!(percent_decode("%41"), "A");
+	assert_eq!(percent_decode("a%zzb"), "a%zzb");
+	assert_eq!(percent_decode("a%+1b"), "a%+1b");
+	assert_eq!(percent_decode("%e2%82%ac"), "\u{20ac}");
+	assert_eq!(percent_decode("%ff"), "\u{fffd}");
+
+}
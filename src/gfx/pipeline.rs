@@ -77,11 +77,14 @@ impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 
 				if loc.is_some() {
 					match value.into_uniform() {
+						I1(i) => self.gl.uniform_1_i32(loc.as_ref(), i),
 						F1(f) => self.gl.uniform_1_f32(loc.as_ref(), f),
 						F2(f) => self.gl.uniform_2_f32(loc.as_ref(), f[0], f[1]),
 						F3(f) => self.gl.uniform_3_f32(loc.as_ref(), f[0], f[1], f[2]),
 						F4(f) => self.gl.uniform_4_f32(loc.as_ref(), f[0], f[1], f[2], f[3]),
+						F4Arr(a) => self.gl.uniform_4_f32_slice(loc.as_ref(), &a),
 						Mat4(a) => self.gl.uniform_matrix_4_f32_slice(loc.as_ref(), false, &a),
+						Mat4Arr(a) => self.gl.uniform_matrix_4_f32_slice(loc.as_ref(), false, &a),
 					}
 				}
 
@@ -1,11 +1,13 @@
 // wengwengweng
 
+use std::cell::RefCell;
+
 use super::*;
 
 /// Custom Shader. See [mod-level doc](index.html) for Usage.
 #[derive(Clone, PartialEq)]
 pub struct Shader<U: UniformLayout> {
-	pipeline: Pipeline<Vertex, Uniform>,
+	program: Program,
 	_custom_uniform: PhantomData<U>,
 }
 
@@ -31,6 +33,35 @@ impl<U: UniformLayout> Shader<U> {
 
 	/// create shader from both vertex and fragment code
 	pub fn from_vert_frag(ctx: &impl GLCtx, vert: &str, frag: &str) -> Result<Self> {
+		return Ok(Self {
+			program: Program::new(ctx, vert, frag)?,
+			_custom_uniform: PhantomData,
+		});
+	}
+
+	/// create default shader
+	pub fn default(ctx: &impl GLCtx) -> Result<Self> {
+		return Self::from_vert_frag(ctx, shaders::DEFAULT_VERT, shaders::DEFAULT_FRAG);
+	}
+
+	pub(super) fn program(&self) -> &Program {
+		return &self.program;
+	}
+
+}
+
+// a shader built from the templates, the skinned variant is only compiled when a skinned mesh is drawn with it
+#[derive(Clone)]
+pub(super) struct Program {
+	pipeline: Pipeline<Vertex, Uniform>,
+	skinned: Rc<RefCell<Option<Pipeline<SkinnedVertex, Uniform>>>>,
+	vert_src: Rc<String>,
+	frag_src: Rc<String>,
+}
+
+impl Program {
+
+	pub fn new(ctx: &impl GLCtx, vert: &str, frag: &str) -> Result<Self> {
 
 		let vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", vert);
 		let frag_src = shaders::TEMPLATE_FRAG.replace("{{user}}", frag);
@@ -39,19 +70,36 @@ impl<U: UniformLayout> Shader<U> {
 
 		return Ok(Self {
 			pipeline: Pipeline::new(ctx, &vert_src, &frag_src)?,
-			_custom_uniform: PhantomData,
+			skinned: Rc::new(RefCell::new(None)),
+			vert_src: Rc::new(vert_src),
+			frag_src: Rc::new(frag_src),
 		});
 
 	}
 
-	/// create default shader
-	pub fn default(ctx: &impl GLCtx) -> Result<Self> {
-		return Self::from_vert_frag(ctx, shaders::DEFAULT_VERT, shaders::DEFAULT_FRAG);
+	pub fn pipeline(&self) -> &Pipeline<Vertex, Uniform> {
+		return &self.pipeline;
 	}
 
-	pub(super) fn pipeline(&self) -> &Pipeline<Vertex, Uniform> {
-		return &self.pipeline;
+	pub fn skinned(&self, ctx: &impl GLCtx) -> Result<Pipeline<SkinnedVertex, Uniform>> {
+
+		if let Some(p) = &*self.skinned.borrow() {
+			return Ok(p.clone());
+		}
+
+		let vert_src = format!("#define SKINNED\n#define MAX_JOINTS {}\n{}", MAX_JOINTS, self.vert_src);
+		let pipeline = Pipeline::new(ctx, &vert_src, &self.frag_src)?;
+
+		*self.skinned.borrow_mut() = Some(pipeline.clone());
+
+		return Ok(pipeline);
+
 	}
 
 }
 
+impl PartialEq for Program {
+	fn eq(&self, other: &Self) -> bool {
+		return self.pipeline == other.pipeline;
+	}
+}
@@ -0,0 +1,6 @@
+// wengwengweng
+
+vec4 frag() {
+	return lit(default_color());
+}
+
@@ -4,4 +4,6 @@ pub const TEMPLATE_VERT: &str = include_str!("template.vert");
 pub const DEFAULT_VERT: &str = include_str!("default.vert");
 pub const TEMPLATE_FRAG: &str = include_str!("template.frag");
 pub const DEFAULT_FRAG: &str = include_str!("default.frag");
+pub const LIT_FRAG: &str = include_str!("lit.frag");
+pub const SHADOW_FRAG: &str = include_str!("shadow.frag");
 
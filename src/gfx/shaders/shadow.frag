@@ -0,0 +1,14 @@
+// wengwengweng
+
+// pack depth into rgb so it survives an 8 bit color attachment
+vec4 frag() {
+
+	float d = clamp(gl_FragCoord.z, 0.0, 0.9999);
+	vec3 enc = fract(d * vec3(1.0, 255.0, 65025.0));
+
+	enc -= enc.yzz * vec3(1.0 / 255.0, 1.0 / 255.0, 0.0);
+
+	return vec4(enc, default_color().a);
+
+}
+
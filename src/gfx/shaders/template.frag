@@ -4,21 +4,157 @@ varying vec3 v_pos;
 varying vec2 v_uv;
 varying vec3 v_normal;
 varying vec4 v_color;
+varying vec3 v_world_pos;
+varying vec3 v_world_normal;
 
 uniform sampler2D u_tex;
 uniform vec4 u_color;
 
+uniform sampler2D u_normal_tex;
+uniform sampler2D u_emissive_tex;
+uniform sampler2D u_metallic_roughness_tex;
+uniform vec4 u_emissive;
+uniform float u_metallic;
+uniform float u_roughness;
+uniform float u_alpha_mask;
+uniform float u_alpha_cutoff;
+uniform float u_has_normal_tex;
+
+// keep size in sync with MAX_LIGHTS
+uniform vec4 u_light_pos[8];
+uniform vec4 u_light_dir[8];
+uniform vec4 u_light_color[8];
+uniform vec4 u_light_params[8];
+uniform int u_light_count;
+uniform vec3 u_eye_pos;
+
+uniform sampler2D u_shadow_map;
+uniform mat4 u_light_space;
+uniform float u_shadow_bias;
+uniform float u_shadow_texel;
+uniform float u_has_shadow;
+
 vec4 default_color() {
 	return v_color * u_color * texture2D(u_tex, v_uv);
 }
 
+float unpack_depth(vec4 c) {
+	return dot(c.rgb, vec3(1.0, 1.0 / 255.0, 1.0 / 65025.0));
+}
+
+// 3x3 pcf, 1.0 is fully lit
+float shadow(vec3 n, vec3 l) {
+
+	if (u_has_shadow == 0.0) {
+		return 1.0;
+	}
+
+	vec4 lp = u_light_space * vec4(v_world_pos, 1.0);
+	vec3 p = lp.xyz / lp.w * 0.5 + 0.5;
+
+	if (p.x < 0.0 || p.x > 1.0 || p.y < 0.0 || p.y > 1.0 || p.z > 1.0) {
+		return 1.0;
+	}
+
+	float bias = max(u_shadow_bias * (1.0 - dot(n, l)), u_shadow_bias * 0.1);
+	float sum = 0.0;
+
+	for (int x = -1; x <= 1; x++) {
+		for (int y = -1; y <= 1; y++) {
+			vec2 uv = p.xy + vec2(float(x), float(y)) * u_shadow_texel;
+			float d = unpack_depth(texture2D(u_shadow_map, uv));
+			sum += p.z - bias > d ? 0.0 : 1.0;
+		}
+	}
+
+	return sum / 9.0;
+
+}
+
+// blinn-phong with the current lights & material, unchanged if there's no light
+vec4 lit(vec4 c) {
+
+	if (u_light_count == 0) {
+		return c;
+	}
+
+	vec3 n = normalize(v_world_normal);
+	vec3 v = normalize(u_eye_pos - v_world_pos);
+	vec4 mr = texture2D(u_metallic_roughness_tex, v_uv);
+	float metallic = u_metallic * mr.b;
+	float roughness = clamp(u_roughness * mr.g, 0.04, 1.0);
+	float shininess = max(2.0 / pow(roughness, 4.0) - 2.0, 1.0);
+	vec3 diffuse = c.rgb * (1.0 - metallic);
+	vec3 spec = mix(vec3(0.04), c.rgb, metallic);
+	vec3 color = u_emissive.rgb * texture2D(u_emissive_tex, v_uv).rgb;
+
+	for (int i = 0; i < 8; i++) {
+
+		if (i >= u_light_count) {
+			break;
+		}
+
+		float kind = u_light_pos[i].w;
+		vec4 params = u_light_params[i];
+		vec3 light_color = u_light_color[i].rgb;
+
+		// ambient
+		if (kind == 0.0) {
+			color += light_color * c.rgb;
+			continue;
+		}
+
+		vec3 l = -u_light_dir[i].xyz;
+		float atten = 1.0;
+
+		// point & spot
+		if (kind >= 2.0) {
+
+			vec3 d = u_light_pos[i].xyz - v_world_pos;
+			float dis = max(length(d), 0.0001);
+
+			l = d / dis;
+			atten = 1.0 / (dis * dis);
+
+			if (params.x > 0.0) {
+				float w = clamp(1.0 - pow(dis / params.x, 4.0), 0.0, 1.0);
+				atten *= w * w;
+			}
+
+			if (kind == 3.0) {
+				atten *= smoothstep(params.z, params.y, dot(-l, u_light_dir[i].xyz));
+			}
+
+		}
+
+		float ndl = max(dot(n, l), 0.0);
+
+		if (params.w == 1.0) {
+			atten *= shadow(n, l);
+		}
+
+		vec3 h = normalize(l + v);
+		float s = pow(max(dot(n, h), 0.0), shininess) * (shininess + 8.0) / 25.13;
+
+		color += light_color * atten * ndl * (diffuse + spec * s);
+
+	}
+
+	return vec4(color, c.a);
+
+}
+
 {{user}}
 
 void main() {
 
 	gl_FragColor = frag();
 
-	if (gl_FragColor.a == 0.0) {
+	if (u_alpha_mask == 1.0) {
+		if (gl_FragColor.a < u_alpha_cutoff) {
+			discard;
+		}
+	} else if (gl_FragColor.a == 0.0) {
 		discard;
 	}
 
@@ -9,8 +9,11 @@ varying vec3 v_pos;
 varying vec3 v_normal;
 varying vec2 v_uv;
 varying vec4 v_color;
+varying vec3 v_world_pos;
+varying vec3 v_world_normal;
 
 uniform mat4 u_model;
+uniform mat4 u_normal_mat;
 uniform mat4 u_view;
 uniform mat4 u_proj;
 
@@ -18,14 +21,46 @@ vec4 default_pos() {
 	return u_proj * u_view * u_model * vec4(v_pos, 1.0);
 }
 
+#ifdef SKINNED
+attribute vec4 a_joints;
+attribute vec4 a_weights;
+
+// MAX_JOINTS is defined by the renderer when compiling this variant
+uniform mat4 u_joints[MAX_JOINTS];
+uniform float u_skinned;
+
+mat4 skin_mat() {
+
+	if (u_skinned == 0.0 || dot(a_weights, vec4(1.0)) == 0.0) {
+		return mat4(1.0);
+	}
+
+	return
+		a_weights.x * u_joints[int(a_joints.x)] +
+		a_weights.y * u_joints[int(a_joints.y)] +
+		a_weights.z * u_joints[int(a_joints.z)] +
+		a_weights.w * u_joints[int(a_joints.w)];
+
+}
+#endif
+
 {{user}}
 
 void main() {
 
+#ifdef SKINNED
+	mat4 skin = skin_mat();
+	v_pos = (skin * vec4(a_pos, 1.0)).xyz;
+	v_normal = normalize((skin * vec4(a_normal, 0.0)).xyz);
+#else
 	v_pos = a_pos;
+	v_normal = normalize(a_normal);
+#endif
+
 	v_uv = a_uv;
 	v_color = a_color;
-	v_normal = normalize(a_normal);
+	v_world_pos = (u_model * vec4(v_pos, 1.0)).xyz;
+	v_world_normal = normalize((u_normal_mat * vec4(v_normal, 0.0)).xyz);
 	gl_Position = vert();
 
 }
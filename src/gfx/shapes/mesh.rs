@@ -8,6 +8,8 @@ pub struct Mesh<'a> {
 	prim: Primitive,
 	tex: Option<&'a gfx::Texture>,
 	color: Color,
+	joints: Option<&'a [Mat4]>,
+	material: Option<&'a gfx::Material>,
 }
 
 impl<'a> Mesh<'a> {
@@ -17,6 +19,8 @@ impl<'a> Mesh<'a> {
 			prim: Primitive::Triangle,
 			tex: None,
 			color: rgba!(1),
+			joints: None,
+			material: None,
 		};
 	}
 	pub fn texture(mut self, tex: &'a gfx::Texture) -> Self {
@@ -31,6 +35,16 @@ impl<'a> Mesh<'a> {
 		self.color = c;
 		return self;
 	}
+	/// base color & texture are multiplied with color & texture, the rest is sent to shaders
+	pub fn material(mut self, m: &'a gfx::Material) -> Self {
+		self.material = Some(m);
+		return self;
+	}
+	/// skin the mesh with joint matrices, indexed by each vertex's joints
+	pub fn joints(mut self, j: &'a [Mat4]) -> Self {
+		self.joints = Some(j);
+		return self;
+	}
 }
 
 pub fn mesh<'a>(m: &'a gfx::Mesh) -> Mesh<'a> {
@@ -41,22 +55,49 @@ impl<'a> Drawable for Mesh<'a> {
 
 	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
 
-		let tex = self.tex.unwrap_or(&ctx.empty_tex);
+		let tex = self.tex
+			.or(self.material.and_then(|m| m.base_color_tex.as_ref()))
+			.unwrap_or(&ctx.empty_tex);
 
-		ctx.cur_pipeline.draw(
-			self.prim,
-			self.mesh.vbuf(),
-			self.mesh.ibuf(),
-			self.mesh.count(),
-			&gfx::Uniform {
-				proj: ctx.proj,
-				view: ctx.view,
-				model: ctx.transform,
-				color: self.color,
-				tex: tex.clone(),
-				custom: ctx.cur_custom_uniform.clone(),
+		let color = match self.material {
+			Some(m) => self.color * m.base_color,
+			None => self.color,
+		};
+
+		let uniform = gfx::Uniform {
+			proj: ctx.proj,
+			view: ctx.view,
+			model: ctx.transform,
+			normal_mat: gfx::normal_mat(ctx.transform),
+			color: color,
+			tex: tex.clone(),
+			joints: self.joints.map(|j| j.to_vec()),
+			material: gfx::MaterialUniform::new(self.material, &ctx.empty_tex),
+			lights: ctx.light_uniform(),
+			custom: ctx.cur_custom_uniform.clone(),
+		};
+
+		match self.mesh.vbuf() {
+			gfx::MeshBuffer::Static(vbuf) => {
+				ctx.cur_pipeline.pipeline().draw(
+					self.prim,
+					vbuf,
+					self.mesh.ibuf(),
+					self.mesh.count(),
+					&uniform,
+				);
+			},
+			gfx::MeshBuffer::Skinned(vbuf) => {
+				let pipeline = ctx.cur_pipeline.skinned(&*ctx)?;
+				pipeline.draw(
+					self.prim,
+					vbuf,
+					self.mesh.ibuf(),
+					self.mesh.count(),
+					&uniform,
+				);
 			},
-		);
+		}
 
 		ctx.draw_calls += 1;
 
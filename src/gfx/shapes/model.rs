@@ -1,5 +1,7 @@
 // wengwengweng
 
+use std::collections::HashMap;
+
 use super::*;
 
 #[derive(Clone)]
@@ -7,7 +9,9 @@ pub struct Model<'a> {
 	model: &'a gfx::Model,
 	color: Color,
 	prim: Primitive,
+	anim: Option<&'a gfx::AnimClip>,
 	time: f32,
+	pose: Option<&'a gfx::Pose>,
 }
 
 pub fn model<'a>(m: &'a gfx::Model) -> Model<'a> {
@@ -20,7 +24,9 @@ impl<'a> Model<'a> {
 			model: m,
 			color: rgba!(1),
 			prim: Primitive::Triangle,
+			anim: m.anims().first(),
 			time: 0.0,
+			pose: None,
 		};
 	}
 	pub fn color(mut self, color: Color) -> Self {
@@ -31,10 +37,24 @@ impl<'a> Model<'a> {
 		self.color.a = a;
 		return self;
 	}
+	/// play an animation clip by name, defaults to the first clip, draws rest pose if not found
+	pub fn anim(mut self, name: &str) -> Self {
+		self.anim = self.model.get_anim(name);
+		return self;
+	}
+	pub fn clip(mut self, clip: &'a gfx::AnimClip) -> Self {
+		self.anim = Some(clip);
+		return self;
+	}
 	pub fn time(mut self, t: f32) -> Self {
 		self.time = t;
 		return self;
 	}
+	/// draw with a pose, usually from an [`Animator`](../struct.Animator.html), ignores anim & time
+	pub fn pose(mut self, pose: &'a gfx::Pose) -> Self {
+		self.pose = Some(pose);
+		return self;
+	}
 	pub fn prim(mut self, prim: gfx::Primitive) -> Self {
 		self.prim = prim;
 		return self;
@@ -45,8 +65,19 @@ impl<'a> Drawable for Model<'a> {
 
 	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
 
+		let world = match self.pose {
+			Some(pose) => self.model.world_transforms(pose),
+			None => {
+				let mut pose = self.model.rest_pose();
+				if let Some(anim) = self.anim {
+					anim.sample(self.time, &mut pose);
+				}
+				self.model.world_transforms(&pose)
+			},
+		};
+
 		for t in self.model.root_nodes() {
-			draw_mesh(ctx, &self, Mat4::identity(), *t)?;
+			draw_mesh(ctx, &self, &world, *t)?;
 		}
 
 		return Ok(());
@@ -55,43 +86,49 @@ impl<'a> Drawable for Model<'a> {
 
 }
 
-fn draw_mesh(ctx: &mut Gfx, dctx: &Model, ptr: Mat4, id: usize) -> Result<()> {
+fn draw_mesh(ctx: &mut Gfx, dctx: &Model, world: &HashMap<usize, Mat4>, id: usize) -> Result<()> {
 
 	let model = &dctx.model;
 
 	if let Some(node) = model.get_node(id) {
 
-		let mut tr = node.transform();
-
-		if let Some(anim) = model.get_anim(id) {
+		// skinned meshes are positioned by joints, not by the node they're attached to
+		let joints = node
+			.skin()
+			.and_then(|s| model.skins().get(s))
+			.map(|s| s.joint_mats(world));
 
-			let (pos, rot, scale) = anim.get_transform(dctx.time);
-
-			tr.pos = pos.unwrap_or(tr.pos);
-			tr.rot = rot.unwrap_or(tr.rot);
-			tr.scale = scale.unwrap_or(tr.scale);
-
-		}
-
-		let tr = ptr * tr.as_mat4();
+		let tr = match joints {
+			Some(_) => mat4!(),
+			None => world.get(&id).cloned().unwrap_or(mat4!()),
+		};
 
-		for m in node.meshes() {
+		for (m, mat) in node.meshes() {
 
 			let mut shape = mesh(m)
 				.prim(dctx.prim)
 				.color(dctx.color)
 				;
 
-			if let Some(tex) = model.texture() {
-				shape = shape.texture(tex);
+			let mat = mat.and_then(|i| model.materials().get(i));
+
+			if let Some(mat) = mat {
+				shape = shape.material(mat);
 			}
 
-			ctx.draw_t(tr, &shape)?;
+			if let Some(joints) = &joints {
+				shape = shape.joints(joints);
+			}
+
+			match mat.map(|m| m.alpha_mode) {
+				Some(gfx::AlphaMode::Blend) => ctx.no_depth_write(|ctx| ctx.draw_t(tr, &shape))?,
+				_ => ctx.draw_t(tr, &shape)?,
+			}
 
 		}
 
 		for c in node.children() {
-			draw_mesh(ctx, dctx, tr, *c)?;
+			draw_mesh(ctx, dctx, world, *c)?;
 		}
 
 	}
@@ -99,4 +136,3 @@ fn draw_mesh(ctx: &mut Gfx, dctx: &Model, ptr: Mat4, id: usize) -> Result<()> {
 	return Ok(());
 
 }
-
@@ -55,21 +55,27 @@ impl<'a> Drawable for Raw<'a> {
 
 		let tex = self.tex.unwrap_or(&ctx.empty_tex);
 
+		let model = if self.transformed {
+			mat4!()
+		} else {
+			ctx.transform
+		};
+
 		ctx.renderer.push(
 			self.prim,
 			&self.verts,
 			&self.indices,
-			&ctx.cur_pipeline,
+			ctx.cur_pipeline.pipeline(),
 			&gfx::Uniform {
 				proj: ctx.proj,
 				view: ctx.view,
-				model: if self.transformed {
-					mat4!()
-				} else {
-					ctx.transform
-				},
+				model: model,
+				normal_mat: gfx::normal_mat(model),
 				color: self.color,
 				tex: tex.clone(),
+				joints: None,
+				material: gfx::MaterialUniform::new(None, &ctx.empty_tex),
+				lights: ctx.light_uniform(),
 				custom: ctx.cur_custom_uniform.clone(),
 			},
 		)?;
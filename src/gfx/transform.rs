@@ -23,8 +23,8 @@ impl Transform {
 	pub fn as_mat4(&self) -> Mat4 {
 		return mat4!()
 			.t3(self.pos)
-			.s3(self.scale)
 			.rq(self.rot)
+			.s3(self.scale)
 			;
 	}
 
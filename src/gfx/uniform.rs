@@ -10,7 +10,7 @@ pub trait IntoUniformValue {
 
 impl IntoUniformValue for UniformValue {
 	fn into_uniform(&self) -> UniformValue {
-		return *self;
+		return self.clone();
 	}
 }
 
@@ -49,13 +49,24 @@ impl UniformData {
 	}
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum UniformValue {
+	I1(i32),
 	F1(f32),
 	F2([f32; 2]),
 	F3([f32; 3]),
 	F4([f32; 4]),
 	Mat4([f32; 16]),
+	/// a vec4 array, flattened
+	F4Arr(Vec<f32>),
+	/// a mat4 array, flattened
+	Mat4Arr(Vec<f32>),
+}
+
+impl IntoUniformValue for i32 {
+	fn into_uniform(&self) -> UniformValue {
+		return UniformValue::I1(*self);
+	}
 }
 
 impl IntoUniformValue for f32 {
@@ -124,6 +135,26 @@ impl IntoUniformValue for Mat4 {
 	}
 }
 
+impl IntoUniformValue for Vec<Mat4> {
+	fn into_uniform(&self) -> UniformValue {
+		return UniformValue::Mat4Arr(self
+			.iter()
+			.flat_map(|m| m.as_arr().to_vec())
+			.collect()
+		);
+	}
+}
+
+impl IntoUniformValue for Vec<Vec4> {
+	fn into_uniform(&self) -> UniformValue {
+		return UniformValue::F4Arr(self
+			.iter()
+			.flat_map(|v| v.as_arr().to_vec())
+			.collect()
+		);
+	}
+}
+
 impl IntoUniformValue for std::time::Duration {
 	fn into_uniform(&self) -> UniformValue {
 		return UniformValue::F1(self.as_secs_f32());
@@ -1,4 +1,569 @@
 // wengwengweng
 
+//! Path Finding
+//!
+//! all path results are `Vec<Vec2>` so they can go straight into [`shapes::lines`](../../gfx/shapes/fn.lines.html) for debugging
+//!  - [`Grid`](struct.Grid.html), A* on a 2D grid with per-cell costs
+//!  - [`NavMesh`](struct.NavMesh.html), A* over triangles with string pulling
+//!  - [`astar`](fn.astar.html) / [`dijkstra`](fn.dijkstra.html), generic graph search with custom neighbors
 
+use std::hash::Hash;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
+use crate::*;
+use math::*;
+use geom::ops::Triangulation;
+
+const SQRT2: f32 = std::f32::consts::SQRT_2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OpenNode {
+	idx: usize,
+	f: f32,
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+	// reversed so BinaryHeap pops the lowest f first
+	fn cmp(&self, other: &Self) -> Ordering {
+		return other.f
+			.partial_cmp(&self.f)
+			.unwrap_or(Ordering::Equal)
+			.then_with(|| other.idx.cmp(&self.idx));
+	}
+}
+
+impl PartialOrd for OpenNode {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		return Some(self.cmp(other));
+	}
+}
+
+/// generic A* search, returns the nodes from start to goal (inclusive) and the total cost
+///
+/// `neighbors` returns each reachable node with the cost to move there, `heuristic` estimates the cost from a node to the goal and should never overestimate
+pub fn astar<N, FN, IN, FH>(start: N, goal: N, mut neighbors: FN, mut heuristic: FH) -> Option<(Vec<N>, f32)>
+	where
+		N: Clone + Eq + Hash,
+		FN: FnMut(&N) -> IN,
+		IN: IntoIterator<Item = (N, f32)>,
+		FH: FnMut(&N) -> f32,
+{
+
+	let mut nodes = vec![start.clone()];
+	let mut indices = hmap![start.clone() => 0];
+	let mut parents: Vec<Option<usize>> = vec![None];
+	let mut costs = vec![0.0];
+	let mut closed = vec![false];
+	let mut open = BinaryHeap::new();
+
+	open.push(OpenNode {
+		idx: 0,
+		f: heuristic(&start),
+	});
+
+	while let Some(OpenNode { idx, .. }) = open.pop() {
+
+		if closed[idx] {
+			continue;
+		}
+
+		if nodes[idx] == goal {
+
+			let mut path = vec![nodes[idx].clone()];
+			let mut cur = idx;
+
+			while let Some(p) = parents[cur] {
+				path.push(nodes[p].clone());
+				cur = p;
+			}
+
+			path.reverse();
+
+			return Some((path, costs[idx]));
+
+		}
+
+		closed[idx] = true;
+
+		for (n, cost) in neighbors(&nodes[idx]) {
+
+			let g = costs[idx] + cost;
+
+			let nidx = match indices.get(&n) {
+				Some(i) => *i,
+				None => {
+					let i = nodes.len();
+					indices.insert(n.clone(), i);
+					nodes.push(n);
+					parents.push(None);
+					costs.push(f32::INFINITY);
+					closed.push(false);
+					i
+				},
+			};
+
+			if closed[nidx] || g >= costs[nidx] {
+				continue;
+			}
+
+			costs[nidx] = g;
+			parents[nidx] = Some(idx);
+
+			open.push(OpenNode {
+				idx: nidx,
+				f: g + heuristic(&nodes[nidx]),
+			});
+
+		}
+
+	}
+
+	return None;
+
+}
+
+/// generic Dijkstra search, same as [`astar`](fn.astar.html) without a heuristic
+pub fn dijkstra<N, FN, IN>(start: N, goal: N, neighbors: FN) -> Option<(Vec<N>, f32)>
+	where
+		N: Clone + Eq + Hash,
+		FN: FnMut(&N) -> IN,
+		IN: IntoIterator<Item = (N, f32)>,
+{
+	return astar(start, goal, neighbors, |_| 0.0);
+}
+
+pub fn manhattan(a: Pt, b: Pt) -> f32 {
+	return ((a.x - b.x).abs() + (a.y - b.y).abs()) as f32;
+}
+
+pub fn euclidean(a: Pt, b: Pt) -> f32 {
+	return Vec2::dist(a.into(), b.into());
+}
+
+/// distance with diagonal moves costing sqrt(2)
+pub fn octile(a: Pt, b: Pt) -> f32 {
+
+	let dx = (a.x - b.x).abs() as f32;
+	let dy = (a.y - b.y).abs() as f32;
+
+	return f32::max(dx, dy) + (SQRT2 - 1.0) * f32::min(dx, dy);
+
+}
+
+/// How a Grid Path Can Move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Moves {
+	/// up, down, left, right
+	Four,
+	/// also diagonals, without cutting corners
+	Eight,
+}
+
+/// A* on a Grid of Cell Costs
+#[derive(Clone, Debug)]
+pub struct Grid {
+	width: i32,
+	height: i32,
+	costs: Vec<f32>,
+	moves: Moves,
+	cell_size: Vec2,
+	offset: Vec2,
+}
+
+impl Grid {
+
+	/// create a grid where every cell costs 1.0
+	pub fn new(width: i32, height: i32, moves: Moves) -> Self {
+		return Self {
+			width: width,
+			height: height,
+			costs: vec![1.0; (width.max(0) * height.max(0)) as usize],
+			moves: moves,
+			cell_size: vec2!(1),
+			offset: vec2!(0),
+		};
+	}
+
+	pub fn width(&self) -> i32 {
+		return self.width;
+	}
+
+	pub fn height(&self) -> i32 {
+		return self.height;
+	}
+
+	/// set how cell positions map to path points, a cell (x, y) becomes `offset + vec2!(x, y) * size`
+	pub fn set_transform(&mut self, offset: Vec2, size: Vec2) {
+		self.offset = offset;
+		self.cell_size = size;
+	}
+
+	/// set the cost of moving into a cell, `f32::INFINITY` means blocked
+	pub fn set_cost(&mut self, p: Pt, c: f32) {
+		if let Some(i) = self.idx(p) {
+			self.costs[i] = c.max(0.0);
+		}
+	}
+
+	pub fn cost(&self, p: Pt) -> Option<f32> {
+		return self.idx(p).map(|i| self.costs[i]);
+	}
+
+	pub fn set_blocked(&mut self, p: Pt, b: bool) {
+		self.set_cost(p, if b { f32::INFINITY } else { 1.0 });
+	}
+
+	pub fn walkable(&self, p: Pt) -> bool {
+		return self.cost(p).map(|c| c.is_finite()).unwrap_or(false);
+	}
+
+	/// cell position to path point
+	pub fn to_world(&self, p: Pt) -> Vec2 {
+		return self.offset + vec2!(p.x, p.y) * self.cell_size;
+	}
+
+	fn idx(&self, p: Pt) -> Option<usize> {
+		if p.x < 0 || p.y < 0 || p.x >= self.width || p.y >= self.height {
+			return None;
+		}
+		return Some((p.y * self.width + p.x) as usize);
+	}
+
+	fn neighbors(&self, p: Pt) -> Vec<(Pt, f32)> {
+
+		let mut list = Vec::with_capacity(8);
+		let straight = [pt!(1, 0), pt!(-1, 0), pt!(0, 1), pt!(0, -1)];
+		let diagonal = [pt!(1, 1), pt!(-1, 1), pt!(1, -1), pt!(-1, -1)];
+
+		for d in &straight {
+			let n = p + *d;
+			if let Some(c) = self.cost(n) {
+				if c.is_finite() {
+					list.push((n, c));
+				}
+			}
+		}
+
+		if self.moves == Moves::Eight {
+			for d in &diagonal {
+				let n = p + *d;
+				// no cutting corners
+				if !self.walkable(pt!(p.x + d.x, p.y)) || !self.walkable(pt!(p.x, p.y + d.y)) {
+					continue;
+				}
+				if let Some(c) = self.cost(n) {
+					if c.is_finite() {
+						list.push((n, c * SQRT2));
+					}
+				}
+			}
+		}
+
+		return list;
+
+	}
+
+	/// find path with the default heuristic ([`manhattan`](fn.manhattan.html) for 4 moves, [`octile`](fn.octile.html) for 8 moves)
+	pub fn find(&self, from: Pt, to: Pt) -> Option<Vec<Vec2>> {
+		return match self.moves {
+			Moves::Four => self.find_with(from, to, manhattan),
+			Moves::Eight => self.find_with(from, to, octile),
+		};
+	}
+
+	/// find path with a custom heuristic
+	pub fn find_with(&self, from: Pt, to: Pt, heuristic: impl Fn(Pt, Pt) -> f32) -> Option<Vec<Vec2>> {
+		return self.find_cells_with(from, to, heuristic)
+			.map(|cells| cells.into_iter().map(|p| self.to_world(p)).collect());
+	}
+
+	/// find path as cell positions
+	pub fn find_cells_with(&self, from: Pt, to: Pt, heuristic: impl Fn(Pt, Pt) -> f32) -> Option<Vec<Pt>> {
+
+		if !self.walkable(from) || !self.walkable(to) {
+			return None;
+		}
+
+		return astar(
+			(from.x, from.y),
+			(to.x, to.y),
+			|(x, y)| self.neighbors(pt!(*x, *y)).into_iter().map(|(p, c)| ((p.x, p.y), c)),
+			|(x, y)| heuristic(pt!(*x, *y), to),
+		).map(|(path, _)| path.into_iter().map(|(x, y)| pt!(x, y)).collect());
+
+	}
+
+}
+
+/// A* over Walkable Triangles with String Pulling
+#[derive(Clone, Debug)]
+pub struct NavMesh {
+	pts: Vec<Vec2>,
+	tris: Vec<[usize; 3]>,
+	// neighbor triangle across edge i (tris[i] -> tris[i + 1])
+	neighbors: Vec<[Option<usize>; 3]>,
+}
+
+impl NavMesh {
+
+	/// create from a triangulation where every triangle is walkable
+	pub fn new(pts: &[Vec2], t: &Triangulation) -> Self {
+		return Self::filter(pts, t, |_| true);
+	}
+
+	/// create from a triangulation, keeping only triangles that pass the check
+	pub fn filter(pts: &[Vec2], t: &Triangulation, walkable: impl Fn([Vec2; 3]) -> bool) -> Self {
+
+		let mut tris = vec![];
+		let mut mapping = vec![None; t.len()];
+
+		for i in 0..t.len() {
+
+			let tri = [t.triangles[i * 3], t.triangles[i * 3 + 1], t.triangles[i * 3 + 2]];
+
+			if walkable([pts[tri[0]], pts[tri[1]], pts[tri[2]]]) {
+				mapping[i] = Some(tris.len());
+				tris.push(tri);
+			}
+
+		}
+
+		let mut neighbors = vec![[None; 3]; tris.len()];
+
+		for i in 0..t.len() {
+
+			let ti = match mapping[i] {
+				Some(ti) => ti,
+				None => continue,
+			};
+
+			for e in 0..3 {
+				let twin = t.halfedges[i * 3 + e];
+				if twin < t.halfedges.len() {
+					neighbors[ti][e] = mapping[twin / 3];
+				}
+			}
+
+		}
+
+		return Self {
+			pts: pts.to_vec(),
+			tris: tris,
+			neighbors: neighbors,
+		};
+
+	}
+
+	pub fn tri_count(&self) -> usize {
+		return self.tris.len();
+	}
+
+	pub fn tri(&self, i: usize) -> Option<[Vec2; 3]> {
+		return self.tris.get(i).map(|t| [self.pts[t[0]], self.pts[t[1]], self.pts[t[2]]]);
+	}
+
+	/// find which triangle a point is in
+	pub fn find_tri(&self, p: Vec2) -> Option<usize> {
+		return (0..self.tris.len()).find(|i| {
+			return self.tri(*i).map(|t| in_tri(p, t)).unwrap_or(false);
+		});
+	}
+
+	fn center(&self, i: usize) -> Vec2 {
+		let t = self.tris[i];
+		return (self.pts[t[0]] + self.pts[t[1]] + self.pts[t[2]]) / 3.0;
+	}
+
+	/// find a smoothed path between 2 points
+	pub fn find(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+
+		let start = self.find_tri(from)?;
+		let goal = self.find_tri(to)?;
+
+		let (tris, _) = astar(
+			start,
+			goal,
+			|i| {
+				return self.neighbors[*i]
+					.iter()
+					.filter_map(|n| *n)
+					.map(|n| (n, Vec2::dist(self.center(*i), self.center(n))))
+					.collect::<Vec<(usize, f32)>>();
+			},
+			|i| Vec2::dist(self.center(*i), to),
+		)?;
+
+		let mut portals = vec![(from, from)];
+
+		for w in tris.windows(2) {
+
+			let (cur, next) = (w[0], w[1]);
+			let e = (0..3).find(|e| self.neighbors[cur][*e] == Some(next))?;
+			let t = self.tris[cur];
+
+			// walking out of a triangle the edge start is on the left
+			portals.push((self.pts[t[e]], self.pts[t[(e + 1) % 3]]));
+
+		}
+
+		portals.push((to, to));
+
+		return Some(string_pull(&portals));
+
+	}
+
+}
+
+fn in_tri(p: Vec2, t: [Vec2; 3]) -> bool {
+
+	let d1 = triarea2(p, t[0], t[1]);
+	let d2 = triarea2(p, t[1], t[2]);
+	let d3 = triarea2(p, t[2], t[0]);
+	let neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+	let pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+	return !(neg && pos);
+
+}
+
+// positive if c is on the right of a -> b
+fn triarea2(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+	return (c.x - a.x) * (b.y - a.y) - (b.x - a.x) * (c.y - a.y);
+}
+
+// simple stupid funnel algorithm
+// http://digestingduck.blogspot.com/2010/03/simple-stupid-funnel-algorithm.html
+fn string_pull(portals: &[(Vec2, Vec2)]) -> Vec<Vec2> {
+
+	let mut path = vec![portals[0].0];
+	let mut apex = portals[0].0;
+	let mut left = portals[0].0;
+	let mut right = portals[0].1;
+	let mut left_i = 0;
+	let mut right_i = 0;
+	let mut i = 1;
+
+	while i < portals.len() {
+
+		let (l, r) = portals[i];
+
+		// tighten the right side
+		if triarea2(apex, right, r) <= 0.0 {
+			if apex == right || triarea2(apex, left, r) > 0.0 {
+				right = r;
+				right_i = i;
+			} else {
+				// right crossed over left, left becomes the new apex
+				path.push(left);
+				apex = left;
+				right = apex;
+				right_i = left_i;
+				i = left_i + 1;
+				continue;
+			}
+		}
+
+		// tighten the left side
+		if triarea2(apex, left, l) >= 0.0 {
+			if apex == left || triarea2(apex, right, l) < 0.0 {
+				left = l;
+				left_i = i;
+			} else {
+				// left crossed over right, right becomes the new apex
+				path.push(right);
+				apex = right;
+				left = apex;
+				left_i = right_i;
+				i = right_i + 1;
+				continue;
+			}
+		}
+
+		i += 1;
+
+	}
+
+	let end = portals[portals.len() - 1].0;
+
+	if path.last() != Some(&end) {
+		path.push(end);
+	}
+
+	return path;
+
+}
+
+
+#[test]
+fn grid_wall() {
+
+	// a wall at x = 5 with a gap at the top
+	let mut g = Grid::new(10, 10, Moves::Four);
+
+	for y in 0..9 {
+		g.set_blocked(pt!(5, y), true);
+	}
+
+	let p = g.find_cells_with(pt!(0, 0), pt!(9, 0), manhattan).unwrap();
+
+	assert_eq!(p.first(), Some(&pt!(0, 0)));
+	assert_eq!(p.last(), Some(&pt!(9, 0)));
+	assert!(p.contains(&pt!(5, 9)));
+	assert!(p.iter().all(|c| g.walkable(*c)));
+	assert_eq!(p.len(), 9 + 18 + 1);
+
+	g.set_blocked(pt!(5, 9), true);
+	assert!(g.find(pt!(0, 0), pt!(9, 0)).is_none());
+	assert!(g.find(pt!(0, 0), pt!(5, 0)).is_none());
+
+}
+
+#[test]
+fn dijkstra_line() {
+
+	// 0 - 1 - 2 - 3, with a costly shortcut 0 - 3
+	let edges = |n: &i32| -> Vec<(i32, f32)> {
+		let mut e = vec![];
+		if *n > 0 { e.push((n - 1, 1.0)); }
+		if *n < 3 { e.push((n + 1, 1.0)); }
+		if *n == 0 { e.push((3, 5.0)); }
+		return e;
+	};
+
+	assert_eq!(dijkstra(0, 3, edges), Some((vec![0, 1, 2, 3], 3.0)));
+	assert_eq!(dijkstra(0, 4, edges), None);
+
+}
+
+#[test]
+fn navmesh_corner() {
+
+	let mut pts = vec![];
+
+	for x in 0..=4 {
+		for y in 0..=4 {
+			pts.push(vec2!(x, y));
+		}
+	}
+
+	let t = geom::ops::triangulate(&pts).unwrap();
+
+	// a wall in the middle, going around it turns at its corners
+	let nm = NavMesh::filter(&pts, &t, |tri| {
+		let c = (tri[0] + tri[1] + tri[2]) / 3.0;
+		return !(c.x > 1.0 && c.x < 3.0 && c.y < 3.0);
+	});
+
+	let p = nm.find(vec2!(0.2, 0.2), vec2!(3.8, 0.2)).unwrap();
+
+	assert_eq!(p, vec![vec2!(0.2, 0.2), vec2!(1, 3), vec2!(3, 3), vec2!(3.8, 0.2)]);
+
+	// in sight, straight line
+	let p = nm.find(vec2!(0.2, 0.2), vec2!(0.8, 2.5)).unwrap();
+
+	assert_eq!(p, vec![vec2!(0.2, 0.2), vec2!(0.8, 2.5)]);
+
+}
@@ -183,6 +183,20 @@ impl Mat4 {
 
 	}
 
+	pub fn transpose(&self) -> Self {
+
+		let mut out = [0.0; 16];
+
+		for i in 0..4 {
+			for j in 0..4 {
+				out[i * 4 + j] = self.m[j * 4 + i];
+			}
+		}
+
+		return Self::new(out);
+
+	}
+
 	pub fn as_arr(&self) -> [f32; 16] {
 		return self.m;
 	}
@@ -344,6 +344,42 @@ impl Vec4 {
 		return vec3!(self.x, self.y, self.z);
 	}
 
+	pub fn len(self) -> f32 {
+		return f32::sqrt(Vec4::dot(self, self));
+	}
+
+	pub fn unit(self) -> Self {
+		return self / self.len();
+	}
+
+	pub fn dot(p1: Self, p2: Self) -> f32 {
+		return p1.x * p2.x + p1.y * p2.y + p1.z * p2.z + p1.w * p2.w;
+	}
+
+	/// spherical interpolation between 2 quaternions, takes the shortest path
+	pub fn slerp(self, to: Self, t: f32) -> Self {
+
+		let t = t.max(0.0).min(1.0);
+		let mut to = to;
+		let mut d = Vec4::dot(self, to);
+
+		if d < 0.0 {
+			to = to * -1.0;
+			d = -d;
+		}
+
+		// too close, sin(theta) goes to 0
+		if d > 0.9995 {
+			return self.lerp(to, t).unit();
+		}
+
+		let theta = d.acos();
+		let s = theta.sin();
+
+		return self * (((1.0 - t) * theta).sin() / s) + to * ((t * theta).sin() / s);
+
+	}
+
 }
 
 impl Color {
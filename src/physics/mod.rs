@@ -1,7 +1,49 @@
 // wengwengweng
 
 //! Simple Physics Engine
+//!
+//! [`World2D`](type.World2D.html) and [`World3D`](type.World3D.html) are fixed-timestep rigid body worlds, both sharing the same [`World`](struct.World.html) core. Bodies can be
+//!  - [`Static`](enum.BodyType.html#variant.Static), never moves
+//!  - [`Kinematic`](enum.BodyType.html#variant.Kinematic), moved only by its velocity, pushes dynamic bodies around
+//!  - [`Dynamic`](enum.BodyType.html#variant.Dynamic), affected by gravity, forces and collisions
+//!
+//! ```ignore
+//! let mut world = World2D::new();
+//!
+//! let ground = world.add(Body2D::builder(BodyType::Static, Rect::new(vec2!(-320, -8), vec2!(320, 8)))
+//!     .pos(vec2!(0, -200))
+//!     .build()?);
+//!
+//! let ball = world.add(Body2D::builder(BodyType::Dynamic, Circle::new(vec2!(0), 16.0))
+//!     .restitution(0.6)
+//!     .build()?);
+//!
+//! // in update()
+//! world.update(d.app.dt());
+//!
+//! for e in world.events() {
+//!     if let ContactEvent2D::Begin(c) = e {
+//!         // ...
+//!     }
+//! }
+//! ```
 
-pub struct World2D;
-pub struct World3D;
+use crate::*;
+use math::*;
+use geom::*;
+use geom::col::*;
 
+export!(world);
+export!(world2d);
+export!(world3d);
+
+/// Handle of a Body in a World
+pub type BodyID = usize;
+
+/// How a Body Reacts to the World
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BodyType {
+	Static,
+	Kinematic,
+	Dynamic,
+}
@@ -0,0 +1,492 @@
+// wengwengweng
+
+use std::time::Duration;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::ops::*;
+
+use super::*;
+
+const CORRECTION: f32 = 0.8;
+const MAX_STEPS: usize = 8;
+
+/// The Dimension a [`World`](struct.World.html) Lives in, implemented by [`Space2D`](struct.Space2D.html) and [`Space3D`](struct.Space3D.html)
+pub trait Space: Clone + Copy + Debug + PartialEq {
+
+	type Vec: Copy
+		+ Debug
+		+ PartialEq
+		+ Default
+		+ Add<Output = Self::Vec>
+		+ Sub<Output = Self::Vec>
+		+ Mul<f32, Output = Self::Vec>
+		+ Div<f32, Output = Self::Vec>
+		+ Neg<Output = Self::Vec>
+		+ AddAssign
+		+ SubAssign;
+
+	type Collider: Clone + Debug;
+
+	/// penetration allowed before positions get corrected
+	const SLOP: f32;
+
+	fn gravity() -> Self::Vec;
+	fn dot(a: Self::Vec, b: Self::Vec) -> f32;
+	/// check if a collider can be simulated
+	fn validate(c: &Self::Collider) -> Result<()>;
+	/// move a collider to world space
+	fn place(c: &Self::Collider, pos: Self::Vec) -> Self::Collider;
+	/// returns normal pointing from c1 to c2, and penetration depth, both in world space
+	fn collide(c1: &Self::Collider, c2: &Self::Collider) -> Option<(Self::Vec, f32)>;
+
+}
+
+/// A Rigid Body in a [`World`](struct.World.html)
+#[derive(Clone, Debug)]
+pub struct Body<S: Space> {
+	pub kind: BodyType,
+	pub collider: S::Collider,
+	pub pos: S::Vec,
+	pub vel: S::Vec,
+	pub mass: f32,
+	pub restitution: f32,
+	pub friction: f32,
+	pub gravity_scale: f32,
+	pub damping: f32,
+	force: S::Vec,
+}
+
+impl<S: Space> Body<S> {
+
+	pub fn builder(kind: BodyType, collider: impl Into<S::Collider>) -> BodyBuilder<S> {
+		return BodyBuilder {
+			kind: kind,
+			collider: collider.into(),
+			pos: S::Vec::default(),
+			vel: S::Vec::default(),
+			mass: 1.0,
+			restitution: 0.0,
+			friction: 0.4,
+			gravity_scale: 1.0,
+			damping: 0.0,
+		};
+	}
+
+	/// apply a force that will be integrated over the next step
+	pub fn apply_force(&mut self, f: S::Vec) {
+		if self.kind == BodyType::Dynamic {
+			self.force += f;
+		}
+	}
+
+	/// instantly change velocity
+	pub fn apply_impulse(&mut self, i: S::Vec) {
+		self.vel += i * self.inv_mass();
+	}
+
+	/// collider in world space
+	pub fn world_collider(&self) -> S::Collider {
+		return S::place(&self.collider, self.pos);
+	}
+
+	fn inv_mass(&self) -> f32 {
+		if self.kind == BodyType::Dynamic && self.mass > 0.0 {
+			return 1.0 / self.mass;
+		} else {
+			return 0.0;
+		}
+	}
+
+}
+
+/// A Builder for [`Body`](struct.Body.html)
+#[derive(Clone, Debug)]
+pub struct BodyBuilder<S: Space> {
+	kind: BodyType,
+	collider: S::Collider,
+	pos: S::Vec,
+	vel: S::Vec,
+	mass: f32,
+	restitution: f32,
+	friction: f32,
+	gravity_scale: f32,
+	damping: f32,
+}
+
+impl<S: Space> BodyBuilder<S> {
+
+	pub fn pos(mut self, p: S::Vec) -> Self {
+		self.pos = p;
+		return self;
+	}
+
+	pub fn vel(mut self, v: S::Vec) -> Self {
+		self.vel = v;
+		return self;
+	}
+
+	pub fn mass(mut self, m: f32) -> Self {
+		self.mass = m;
+		return self;
+	}
+
+	/// bounciness, 0.0 is no bounce, 1.0 is perfectly elastic
+	pub fn restitution(mut self, r: f32) -> Self {
+		self.restitution = r.max(0.0).min(1.0);
+		return self;
+	}
+
+	pub fn friction(mut self, f: f32) -> Self {
+		self.friction = f.max(0.0);
+		return self;
+	}
+
+	pub fn gravity_scale(mut self, s: f32) -> Self {
+		self.gravity_scale = s;
+		return self;
+	}
+
+	/// linear velocity damping per second
+	pub fn damping(mut self, d: f32) -> Self {
+		self.damping = d.max(0.0);
+		return self;
+	}
+
+	pub fn build(self) -> Result<Body<S>> {
+
+		S::validate(&self.collider)?;
+
+		return Ok(Body {
+			kind: self.kind,
+			collider: self.collider,
+			pos: self.pos,
+			vel: self.vel,
+			mass: self.mass,
+			restitution: self.restitution,
+			friction: self.friction,
+			gravity_scale: self.gravity_scale,
+			damping: self.damping,
+			force: S::Vec::default(),
+		});
+
+	}
+
+}
+
+/// A Contact Between 2 Bodies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact<S: Space> {
+	pub a: BodyID,
+	pub b: BodyID,
+	/// points from a to b
+	pub normal: S::Vec,
+	pub depth: f32,
+	/// impulse applied along the normal to separate the bodies
+	pub impulse: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContactEvent<S: Space> {
+	/// 2 bodies started touching
+	Begin(Contact<S>),
+	/// 2 bodies stopped touching
+	End(BodyID, BodyID),
+}
+
+/// Rigid Body World, see [`World2D`](type.World2D.html) and [`World3D`](type.World3D.html)
+pub struct World<S: Space> {
+	pub(super) bodies: BTreeMap<BodyID, Body<S>>,
+	last_id: BodyID,
+	gravity: S::Vec,
+	timestep: f32,
+	acc: f32,
+	iterations: usize,
+	contacts: Vec<Contact<S>>,
+	touching: HashSet<(BodyID, BodyID)>,
+	events: Vec<ContactEvent<S>>,
+}
+
+impl<S: Space> World<S> {
+
+	pub fn new() -> Self {
+		return Self {
+			bodies: bmap![],
+			last_id: 0,
+			gravity: S::gravity(),
+			timestep: 1.0 / 60.0,
+			acc: 0.0,
+			iterations: 4,
+			contacts: vec![],
+			touching: hset![],
+			events: vec![],
+		};
+	}
+
+	pub fn add(&mut self, b: Body<S>) -> BodyID {
+
+		let id = self.last_id;
+
+		self.bodies.insert(id, b);
+		self.last_id += 1;
+
+		return id;
+
+	}
+
+	pub fn remove(&mut self, id: BodyID) -> Option<Body<S>> {
+
+		let touching = self.touching
+			.iter()
+			.filter(|(a, b)| *a == id || *b == id)
+			.cloned()
+			.collect::<Vec<(BodyID, BodyID)>>();
+
+		for (a, b) in touching {
+			self.touching.remove(&(a, b));
+			self.events.push(ContactEvent::End(a, b));
+		}
+
+		return self.bodies.remove(&id);
+
+	}
+
+	pub fn get(&self, id: BodyID) -> Option<&Body<S>> {
+		return self.bodies.get(&id);
+	}
+
+	pub fn get_mut(&mut self, id: BodyID) -> Option<&mut Body<S>> {
+		return self.bodies.get_mut(&id);
+	}
+
+	pub fn bodies(&self) -> impl Iterator<Item = (BodyID, &Body<S>)> {
+		return self.bodies.iter().map(|(id, b)| (*id, b));
+	}
+
+	pub fn count(&self) -> usize {
+		return self.bodies.len();
+	}
+
+	pub fn gravity(&self) -> S::Vec {
+		return self.gravity;
+	}
+
+	pub fn set_gravity(&mut self, g: S::Vec) {
+		self.gravity = g;
+	}
+
+	pub fn timestep(&self) -> Duration {
+		return Duration::from_secs_f32(self.timestep);
+	}
+
+	/// set the fixed timestep (default 1/60 sec)
+	pub fn set_timestep(&mut self, t: Duration) {
+		self.timestep = t.as_secs_f32().max(0.0001);
+	}
+
+	/// set how many times contacts are resolved per step (default 4)
+	pub fn set_iterations(&mut self, i: usize) {
+		self.iterations = i.max(1);
+	}
+
+	/// contacts from the last step
+	pub fn contacts(&self) -> &[Contact<S>] {
+		return &self.contacts;
+	}
+
+	/// take contact events that happened since last call
+	pub fn events(&mut self) -> Vec<ContactEvent<S>> {
+		return std::mem::replace(&mut self.events, vec![]);
+	}
+
+	/// advance the world by real time, run as many fixed steps as needed
+	pub fn update(&mut self, dt: Duration) {
+
+		self.acc += dt.as_secs_f32();
+
+		let mut steps = 0;
+
+		while self.acc >= self.timestep {
+
+			self.acc -= self.timestep;
+			steps += 1;
+
+			if steps > MAX_STEPS {
+				// drop the time we can't catch up with
+				self.acc = 0.0;
+				break;
+			}
+
+			self.step();
+
+		}
+
+	}
+
+	/// advance the world by exactly 1 fixed step
+	pub fn step(&mut self) {
+
+		let dt = self.timestep;
+		let gravity = self.gravity;
+
+		for b in self.bodies.values_mut() {
+
+			match b.kind {
+				BodyType::Static => {
+					b.vel = S::Vec::default();
+				},
+				BodyType::Kinematic => {
+					b.pos += b.vel * dt;
+				},
+				BodyType::Dynamic => {
+					b.vel += (gravity * b.gravity_scale + b.force * b.inv_mass()) * dt;
+					b.vel = b.vel * f32::max(0.0, 1.0 - b.damping * dt);
+					b.pos += b.vel * dt;
+				},
+			}
+
+			b.force = S::Vec::default();
+
+		}
+
+		let mut contacts = bmap![];
+
+		for _ in 0..self.iterations {
+			for c in self.resolve() {
+				contacts
+					.entry((c.a, c.b))
+					.and_modify(|c2: &mut Contact<S>| c2.impulse += c.impulse)
+					.or_insert(c);
+			}
+		}
+
+		let touching = contacts
+			.keys()
+			.cloned()
+			.collect::<HashSet<(BodyID, BodyID)>>();
+
+		for (pair, c) in &contacts {
+			if !self.touching.contains(pair) {
+				self.events.push(ContactEvent::Begin(*c));
+			}
+		}
+
+		for (a, b) in &self.touching {
+			if !touching.contains(&(*a, *b)) {
+				self.events.push(ContactEvent::End(*a, *b));
+			}
+		}
+
+		self.touching = touching;
+		self.contacts = contacts.into_iter().map(|(_, c)| c).collect();
+
+	}
+
+	fn resolve(&mut self) -> Vec<Contact<S>> {
+
+		let ids = self.bodies.keys().cloned().collect::<Vec<BodyID>>();
+		let colliders = self.bodies
+			.values()
+			.map(|b| b.world_collider())
+			.collect::<Vec<S::Collider>>();
+
+		let mut contacts = vec![];
+
+		for i in 0..ids.len() {
+
+			for j in (i + 1)..ids.len() {
+
+				let (ida, idb) = (ids[i], ids[j]);
+				let a = &self.bodies[&ida];
+				let b = &self.bodies[&idb];
+
+				if a.kind != BodyType::Dynamic && b.kind != BodyType::Dynamic {
+					continue;
+				}
+
+				let (normal, depth) = match S::collide(&colliders[i], &colliders[j]) {
+					Some(c) => c,
+					None => continue,
+				};
+
+				let impulse = self.apply(ida, idb, normal, depth);
+
+				contacts.push(Contact {
+					a: ida,
+					b: idb,
+					normal: normal,
+					depth: depth,
+					impulse: impulse,
+				});
+
+			}
+
+		}
+
+		return contacts;
+
+	}
+
+	fn apply(&mut self, ida: BodyID, idb: BodyID, normal: S::Vec, depth: f32) -> f32 {
+
+		let a = &self.bodies[&ida];
+		let b = &self.bodies[&idb];
+		let inv_a = a.inv_mass();
+		let inv_b = b.inv_mass();
+		let inv_sum = inv_a + inv_b;
+
+		if inv_sum == 0.0 {
+			return 0.0;
+		}
+
+		let rv = b.vel - a.vel;
+		let vn = S::dot(rv, normal);
+		let mut j = 0.0;
+		let mut jt_vec = S::Vec::default();
+
+		// only push apart if not already separating
+		if vn < 0.0 {
+
+			let e = f32::max(a.restitution, b.restitution);
+
+			j = -(1.0 + e) * vn / inv_sum;
+
+			let tangent = rv - normal * vn;
+			let tlen = S::dot(tangent, tangent).sqrt();
+
+			if tlen > std::f32::EPSILON {
+
+				let tangent = tangent / tlen;
+				let mu = f32::sqrt(a.friction * b.friction);
+				let jt = (-S::dot(rv, tangent) / inv_sum).max(-j * mu).min(j * mu);
+
+				jt_vec = tangent * jt;
+
+			}
+
+		}
+
+		let impulse = normal * j + jt_vec;
+		let correction = normal * (f32::max(depth - S::SLOP, 0.0) / inv_sum * CORRECTION);
+
+		if let Some(a) = self.bodies.get_mut(&ida) {
+			a.vel -= impulse * inv_a;
+			a.pos -= correction * inv_a;
+		}
+
+		if let Some(b) = self.bodies.get_mut(&idb) {
+			b.vel += impulse * inv_b;
+			b.pos += correction * inv_b;
+		}
+
+		return j;
+
+	}
+
+}
+
+impl<S: Space> Default for World<S> {
+	fn default() -> Self {
+		return Self::new();
+	}
+}
@@ -0,0 +1,299 @@
+// wengwengweng
+
+use super::*;
+
+/// Collision Shape of a [`Body2D`](type.Body2D.html), relative to the body position
+#[derive(Clone, Debug, PartialEq)]
+pub enum Collider2D {
+	Circle(Circle),
+	Rect(Rect),
+	Polygon(Vec<Vec2>),
+}
+
+impl Collider2D {
+
+	/// get the shape for use with [`geom::intersect2d`](../geom/fn.intersect2d.html)
+	pub fn shape(&self) -> Shape2D<'_> {
+		return match self {
+			Collider2D::Circle(c) => Shape2D::Circle(*c),
+			Collider2D::Rect(r) => Shape2D::Rect(*r),
+			Collider2D::Polygon(verts) => Shape2D::Polygon(verts),
+		};
+	}
+
+	/// bounding rect
+	pub fn rect(&self) -> Rect {
+		return match self {
+			Collider2D::Circle(c) => c.rect(),
+			Collider2D::Rect(r) => *r,
+			Collider2D::Polygon(verts) => {
+				let (min, max) = verts.iter().fold((vec2!(f32::MAX), vec2!(f32::MIN)), |(min, max), p| {
+					return (
+						vec2!(f32::min(min.x, p.x), f32::min(min.y, p.y)),
+						vec2!(f32::max(max.x, p.x), f32::max(max.y, p.y)),
+					);
+				});
+				Rect::new(min, max)
+			},
+		};
+	}
+
+	fn solid(&self) -> Solid {
+		return match self {
+			Collider2D::Circle(c) => Solid::Circle(*c),
+			Collider2D::Rect(r) => {
+				let (p1, p2) = (r.p1, r.p2);
+				Solid::Polygon(vec![p1, vec2!(p2.x, p1.y), p2, vec2!(p1.x, p2.y)])
+			},
+			Collider2D::Polygon(verts) => Solid::Polygon(verts.clone()),
+		};
+	}
+
+}
+
+impl From<Circle> for Collider2D {
+	fn from(c: Circle) -> Self {
+		return Collider2D::Circle(c);
+	}
+}
+
+impl From<Rect> for Collider2D {
+	fn from(r: Rect) -> Self {
+		return Collider2D::Rect(r);
+	}
+}
+
+impl From<Vec<Vec2>> for Collider2D {
+	fn from(verts: Vec<Vec2>) -> Self {
+		return Collider2D::Polygon(verts);
+	}
+}
+
+impl From<&[Vec2]> for Collider2D {
+	fn from(verts: &[Vec2]) -> Self {
+		return Collider2D::Polygon(verts.to_vec());
+	}
+}
+
+// convex shape for the narrow phase
+enum Solid {
+	Circle(Circle),
+	Polygon(Vec<Vec2>),
+}
+
+/// 2D [`Space`](trait.Space.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Space2D;
+
+impl Space for Space2D {
+
+	type Vec = Vec2;
+	type Collider = Collider2D;
+
+	const SLOP: f32 = 0.01;
+
+	fn gravity() -> Vec2 {
+		return vec2!(0, -980);
+	}
+
+	fn dot(a: Vec2, b: Vec2) -> f32 {
+		return Vec2::dot(a, b);
+	}
+
+	fn validate(c: &Collider2D) -> Result<()> {
+
+		if let Collider2D::Polygon(verts) = c {
+			if verts.len() < 3 {
+				return Err(format!("polygon collider needs at least 3 vertices, got {}", verts.len()));
+			}
+		}
+
+		return Ok(());
+
+	}
+
+	fn place(c: &Collider2D, pos: Vec2) -> Collider2D {
+		return match c {
+			Collider2D::Circle(c) => Collider2D::Circle(Circle::new(c.center + pos, c.radius)),
+			Collider2D::Rect(r) => Collider2D::Rect(Rect::new(r.p1 + pos, r.p2 + pos)),
+			Collider2D::Polygon(verts) => Collider2D::Polygon(verts.iter().map(|p| *p + pos).collect()),
+		};
+	}
+
+	fn collide(c1: &Collider2D, c2: &Collider2D) -> Option<(Vec2, f32)> {
+
+		// a collider edited into a degenerate polygon after build() just doesn't collide
+		if Self::validate(c1).is_err() || Self::validate(c2).is_err() {
+			return None;
+		}
+
+		if !intersect2d(c1.rect(), c2.rect()) {
+			return None;
+		}
+
+		return collide(&c1.solid(), &c2.solid());
+
+	}
+
+}
+
+/// A Rigid Body in [`World2D`](type.World2D.html)
+pub type Body2D = Body<Space2D>;
+/// A Builder for [`Body2D`](type.Body2D.html)
+pub type Body2DBuilder = BodyBuilder<Space2D>;
+/// A Contact Between 2 Bodies in [`World2D`](type.World2D.html)
+pub type Contact2D = Contact<Space2D>;
+pub type ContactEvent2D = ContactEvent<Space2D>;
+/// 2D Rigid Body World
+pub type World2D = World<Space2D>;
+
+impl Body2D {
+
+	/// bounding rect in world space
+	pub fn rect(&self) -> Rect {
+		return self.world_collider().rect();
+	}
+
+}
+
+fn project(poly: &[Vec2], axis: Vec2) -> (f32, f32) {
+	return poly.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+		let proj = Vec2::dot(axis, *p);
+		return (f32::min(min, proj), f32::max(max, proj));
+	});
+}
+
+fn centroid(poly: &[Vec2]) -> Vec2 {
+	return poly.iter().fold(vec2!(0), |acc, p| acc + *p) / poly.len() as f32;
+}
+
+// returns normal pointing from the circle to the polygon, and penetration depth
+fn circle_poly(c: Circle, poly: &[Vec2]) -> Option<(Vec2, f32)> {
+
+	let len = poly.len();
+	let mut axis = Vec::with_capacity(len + 1);
+
+	for i in 0..len {
+		axis.push((poly[i] - poly[(i + 1) % len]).normal().unit());
+	}
+
+	let closest = poly.iter().fold(poly[0], |acc, p| {
+		if Vec2::dist(*p, c.center) < Vec2::dist(acc, c.center) {
+			return *p;
+		} else {
+			return acc;
+		}
+	});
+
+	if Vec2::dist(closest, c.center) > 0.0 {
+		axis.push((closest - c.center).unit());
+	}
+
+	let mut normal = vec2!(0);
+	let mut depth = f32::MAX;
+
+	for a in axis {
+
+		let (pmin, pmax) = project(poly, a);
+		let cp = Vec2::dot(c.center, a);
+		let (cmin, cmax) = (cp - c.radius, cp + c.radius);
+
+		if cmin > pmax || pmin > cmax {
+			return None;
+		}
+
+		let o = f32::min(cmax - pmin, pmax - cmin);
+
+		if o < depth {
+			depth = o;
+			normal = a;
+		}
+
+	}
+
+	if Vec2::dot(centroid(poly) - c.center, normal) < 0.0 {
+		normal = -normal;
+	}
+
+	return Some((normal, depth));
+
+}
+
+// returns normal pointing from s1 to s2, and penetration depth
+fn collide(s1: &Solid, s2: &Solid) -> Option<(Vec2, f32)> {
+
+	return match (s1, s2) {
+
+		(Solid::Circle(c1), Solid::Circle(c2)) => {
+
+			let d = c2.center - c1.center;
+			let dist = d.len();
+			let r = c1.radius + c2.radius;
+
+			if dist >= r {
+				None
+			} else if dist == 0.0 {
+				Some((vec2!(0, 1), r))
+			} else {
+				Some((d / dist, r - dist))
+			}
+
+		},
+
+		(Solid::Circle(c), Solid::Polygon(poly)) => circle_poly(*c, poly),
+
+		(Solid::Polygon(poly), Solid::Circle(c)) => {
+			circle_poly(*c, poly).map(|(n, d)| (-n, d))
+		},
+
+		(Solid::Polygon(p1), Solid::Polygon(p2)) => {
+
+			let (hit, mtv) = col::sat2d(p1, p2);
+			let depth = mtv.len();
+
+			if !hit || depth == 0.0 {
+				return None;
+			}
+
+			let mut normal = -mtv / depth;
+
+			if Vec2::dot(centroid(p2) - centroid(p1), normal) < 0.0 {
+				normal = -normal;
+			}
+
+			Some((normal, depth))
+
+		},
+
+	};
+
+}
+
+
+#[test]
+fn rest_on_ground() {
+
+	let mut w = World2D::new();
+
+	w.add(Body2D::builder(BodyType::Static, Rect::new(vec2!(-320, -8), vec2!(320, 8)))
+		.pos(vec2!(0, -200))
+		.build()
+		.unwrap());
+
+	let b = w.add(Body2D::builder(BodyType::Dynamic, Rect::new(vec2!(-10), vec2!(10)))
+		.build()
+		.unwrap());
+
+	for _ in 0..600 {
+		w.update(std::time::Duration::from_secs_f32(1.0 / 60.0));
+	}
+
+	let body = w.get(b).unwrap();
+
+	assert!((body.pos.y - -182.0).abs() < 1.0, "{:?}", body.pos);
+	assert!(body.vel.len() < 1.0, "{:?}", body.vel);
+
+	// polygons need at least 3 points
+	assert!(Body2D::builder(BodyType::Dynamic, vec![vec2!(0), vec2!(1, 0)]).build().is_err());
+
+}
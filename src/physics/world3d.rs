@@ -0,0 +1,339 @@
+// wengwengweng
+
+use super::*;
+
+/// Collision Shape of a [`Body3D`](type.Body3D.html), relative to the body position
+///
+/// planes are infinite and solid on the opposite side of their normal, so they're mostly useful as static floors / walls
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collider3D {
+	Sphere(Sphere),
+	Box(BBox),
+	Plane(Plane),
+}
+
+impl Collider3D {
+
+	/// get the shape in world space, for use with [`geom::intersect3d`](../geom/col/fn.intersect3d.html)
+	pub fn shape(&self, pos: Vec3) -> Shape3D {
+		return match Space3D::place(self, pos) {
+			Collider3D::Sphere(s) => Shape3D::Sphere(s),
+			Collider3D::Box(b) => Shape3D::Box(b),
+			Collider3D::Plane(p) => Shape3D::Plane(p),
+		};
+	}
+
+}
+
+impl From<Sphere> for Collider3D {
+	fn from(s: Sphere) -> Self {
+		return Collider3D::Sphere(s);
+	}
+}
+
+impl From<BBox> for Collider3D {
+	fn from(b: BBox) -> Self {
+		return Collider3D::Box(b);
+	}
+}
+
+impl From<Plane> for Collider3D {
+	fn from(p: Plane) -> Self {
+		return Collider3D::Plane(p);
+	}
+}
+
+/// 3D [`Space`](trait.Space.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Space3D;
+
+impl Space for Space3D {
+
+	type Vec = Vec3;
+	type Collider = Collider3D;
+
+	const SLOP: f32 = 0.001;
+
+	fn gravity() -> Vec3 {
+		return vec3!(0, -9.8, 0);
+	}
+
+	fn dot(a: Vec3, b: Vec3) -> f32 {
+		return Vec3::dot(a, b);
+	}
+
+	fn validate(_: &Collider3D) -> Result<()> {
+		return Ok(());
+	}
+
+	fn place(c: &Collider3D, pos: Vec3) -> Collider3D {
+		return match c {
+			Collider3D::Sphere(s) => Collider3D::Sphere(Sphere::new(s.center + pos, s.radius)),
+			Collider3D::Box(b) => Collider3D::Box(BBox::new(b.min + pos, b.max + pos)),
+			Collider3D::Plane(p) => Collider3D::Plane(Plane::new(p.normal, p.dist - Vec3::dot(p.normal, pos))),
+		};
+	}
+
+	fn collide(c1: &Collider3D, c2: &Collider3D) -> Option<(Vec3, f32)> {
+		return collide(c1.shape(vec3!(0)), c2.shape(vec3!(0)));
+	}
+
+}
+
+/// A Rigid Body in [`World3D`](type.World3D.html)
+pub type Body3D = Body<Space3D>;
+/// A Builder for [`Body3D`](type.Body3D.html)
+pub type Body3DBuilder = BodyBuilder<Space3D>;
+/// A Contact Between 2 Bodies in [`World3D`](type.World3D.html)
+pub type Contact3D = Contact<Space3D>;
+pub type ContactEvent3D = ContactEvent<Space3D>;
+/// 3D Rigid Body World
+pub type World3D = World<Space3D>;
+
+impl Body3D {
+
+	/// collider in world space
+	pub fn shape(&self) -> Shape3D {
+		return self.collider.shape(self.pos);
+	}
+
+	/// bounding box in world space, planes don't have one
+	pub fn bbox(&self) -> Option<BBox> {
+		return match self.shape() {
+			Shape3D::Sphere(s) => Some(s.bbox()),
+			Shape3D::Box(b) => Some(b),
+			_ => None,
+		};
+	}
+
+}
+
+/// Result of [`World3D::raycast`](type.World3D.html#method.raycast)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit3D {
+	pub id: BodyID,
+	pub point: Vec3,
+	pub normal: Vec3,
+	pub dist: f32,
+}
+
+impl World3D {
+
+	/// get the closest body hit by a ray
+	pub fn raycast(&self, r: Ray3) -> Option<RayHit3D> {
+
+		let mut closest: Option<RayHit3D> = None;
+
+		for (id, b) in &self.bodies {
+
+			let hit = match b.shape() {
+				Shape3D::Sphere(s) => col::ray_sphere(r, s).map(|pt| (pt, (pt - s.center).unit())),
+				Shape3D::Box(bbox) => col::ray_box(r, bbox).map(|pt| (pt, box_normal(bbox, pt))),
+				Shape3D::Plane(p) => col::ray_plane(r, p).map(|pt| (pt, p.normal)),
+				_ => None,
+			};
+
+			if let Some((pt, normal)) = hit {
+
+				let dist = Vec3::dist(pt, r.origin);
+
+				if closest.map(|c| dist < c.dist).unwrap_or(true) {
+					closest = Some(RayHit3D {
+						id: *id,
+						point: pt,
+						normal: normal,
+						dist: dist,
+					});
+				}
+
+			}
+
+		}
+
+		return closest;
+
+	}
+
+}
+
+// outward normal of the box face closest to a point
+fn box_normal(b: BBox, pt: Vec3) -> Vec3 {
+
+	let center = b.center();
+	let half = (b.max - b.min) * 0.5;
+	let d = pt - center;
+	let (x, y, z) = (d.x / half.x, d.y / half.y, d.z / half.z);
+
+	if x.abs() >= y.abs() && x.abs() >= z.abs() {
+		return vec3!(x.signum(), 0, 0);
+	} else if y.abs() >= z.abs() {
+		return vec3!(0, y.signum(), 0);
+	} else {
+		return vec3!(0, 0, z.signum());
+	}
+
+}
+
+fn box_corners(b: BBox) -> [Vec3; 8] {
+	return [
+		vec3!(b.min.x, b.min.y, b.min.z),
+		vec3!(b.max.x, b.min.y, b.min.z),
+		vec3!(b.min.x, b.max.y, b.min.z),
+		vec3!(b.max.x, b.max.y, b.min.z),
+		vec3!(b.min.x, b.min.y, b.max.z),
+		vec3!(b.max.x, b.min.y, b.max.z),
+		vec3!(b.min.x, b.max.y, b.max.z),
+		vec3!(b.max.x, b.max.y, b.max.z),
+	];
+}
+
+// returns normal pointing from the sphere to the box, and penetration depth
+fn sphere_box(s: Sphere, b: BBox) -> Option<(Vec3, f32)> {
+
+	if !intersect3d(s, b) {
+		return None;
+	}
+
+	let closest = s.center.clamp(b.min, b.max);
+	let d = closest - s.center;
+	let dist = d.len();
+
+	if dist > 0.0 {
+		return Some((d / dist, s.radius - dist));
+	}
+
+	// center is inside the box, push out through the nearest face
+	let faces = [
+		(s.center.x - b.min.x, vec3!(1, 0, 0)),
+		(b.max.x - s.center.x, vec3!(-1, 0, 0)),
+		(s.center.y - b.min.y, vec3!(0, 1, 0)),
+		(b.max.y - s.center.y, vec3!(0, -1, 0)),
+		(s.center.z - b.min.z, vec3!(0, 0, 1)),
+		(b.max.z - s.center.z, vec3!(0, 0, -1)),
+	];
+
+	let (dist, normal) = faces
+		.iter()
+		.fold(faces[0], |acc, f| if f.0 < acc.0 { *f } else { acc });
+
+	return Some((normal, s.radius + dist));
+
+}
+
+// returns normal pointing from b1 to b2, and penetration depth
+fn box_box(b1: BBox, b2: BBox) -> Option<(Vec3, f32)> {
+
+	if !intersect3d(b1, b2) {
+		return None;
+	}
+
+	let d = b2.center() - b1.center();
+	let overlaps = [
+		(f32::min(b1.max.x, b2.max.x) - f32::max(b1.min.x, b2.min.x), vec3!(d.x.signum(), 0, 0)),
+		(f32::min(b1.max.y, b2.max.y) - f32::max(b1.min.y, b2.min.y), vec3!(0, d.y.signum(), 0)),
+		(f32::min(b1.max.z, b2.max.z) - f32::max(b1.min.z, b2.min.z), vec3!(0, 0, d.z.signum())),
+	];
+
+	let (depth, normal) = overlaps
+		.iter()
+		.fold(overlaps[0], |acc, o| if o.0 < acc.0 { *o } else { acc });
+
+	if depth <= 0.0 {
+		return None;
+	}
+
+	return Some((normal, depth));
+
+}
+
+// planes are treated as half spaces, returns normal pointing into the plane
+fn sphere_plane(s: Sphere, p: Plane) -> Option<(Vec3, f32)> {
+
+	let d = Vec3::dot(p.normal, s.center) + p.dist;
+
+	if d >= s.radius {
+		return None;
+	}
+
+	return Some((-p.normal, s.radius - d));
+
+}
+
+fn box_plane(b: BBox, p: Plane) -> Option<(Vec3, f32)> {
+
+	let d = box_corners(b)
+		.iter()
+		.map(|c| Vec3::dot(p.normal, *c) + p.dist)
+		.fold(f32::MAX, f32::min);
+
+	if d >= 0.0 {
+		return None;
+	}
+
+	return Some((-p.normal, -d));
+
+}
+
+// returns normal pointing from s1 to s2, and penetration depth
+fn collide(s1: Shape3D, s2: Shape3D) -> Option<(Vec3, f32)> {
+
+	use Shape3D::*;
+
+	let flip = |c: Option<(Vec3, f32)>| c.map(|(n, d)| (-n, d));
+
+	return match (s1, s2) {
+
+		(Sphere(a), Sphere(b)) => {
+
+			if !intersect3d(a, b) {
+				return None;
+			}
+
+			let d = b.center - a.center;
+			let dist = d.len();
+
+			if dist == 0.0 {
+				Some((vec3!(0, 1, 0), a.radius + b.radius))
+			} else {
+				Some((d / dist, a.radius + b.radius - dist))
+			}
+
+		},
+
+		(Sphere(s), Box(b)) => sphere_box(s, b),
+		(Box(b), Sphere(s)) => flip(sphere_box(s, b)),
+		(Box(a), Box(b)) => box_box(a, b),
+		(Sphere(s), Plane(p)) => sphere_plane(s, p),
+		(Plane(p), Sphere(s)) => flip(sphere_plane(s, p)),
+		(Box(b), Plane(p)) => box_plane(b, p),
+		(Plane(p), Box(b)) => flip(box_plane(b, p)),
+		_ => None,
+
+	};
+
+}
+
+
+#[test]
+fn rest_on_plane() {
+
+	let mut w = World3D::new();
+
+	w.add(Body3D::builder(BodyType::Static, Plane::new(vec3!(0, 1, 0), 0.0)).build().unwrap());
+
+	let b = w.add(Body3D::builder(BodyType::Dynamic, Sphere::new(vec3!(0), 1.0))
+		.pos(vec3!(0, 5, 0))
+		.build()
+		.unwrap());
+
+	for _ in 0..300 {
+		w.update(std::time::Duration::from_secs_f32(1.0 / 60.0));
+	}
+
+	let body = w.get(b).unwrap();
+
+	// settled on top, not sinking through or bouncing
+	assert!((body.pos.y - 1.0).abs() < Space3D::SLOP * 10.0, "{:?}", body.pos);
+	assert!(body.vel.len() < 0.1, "{:?}", body.vel);
+
+}
@@ -502,6 +502,11 @@ impl<'a> WidgetManager<'a> {
 		return self.widget_light(Sep);
 	}
 
+	/// a level meter, e.g. from an [`Analyser`](../audio/struct.Analyser.html)'s rms and peak
+	pub fn meter(&mut self, label: &'static str, level: f32, peak: f32) -> Result<()> {
+		return self.widget_light(Meter::new(label, level, peak));
+	}
+
 	pub fn select<T: SelectValue>(
 		&mut self,
 		label: &'static str,
@@ -0,0 +1,82 @@
+// wengwengweng
+
+use super::*;
+
+// lowest db shown
+const FLOOR: f32 = -60.0;
+
+/// Level Meter, values are linear amplitude and shown in db
+pub struct Meter {
+	label: &'static str,
+	level: f32,
+	peak: f32,
+}
+
+impl Meter {
+	pub fn new(label: &'static str, level: f32, peak: f32) -> Self {
+		return Self {
+			label: label,
+			level: level,
+			peak: peak,
+		};
+	}
+}
+
+fn ratio(v: f32) -> f32 {
+	let db = 20.0 * v.max(0.000001).log10();
+	return ((db - FLOOR) / -FLOOR).max(0.0).min(1.0);
+}
+
+impl Widget for Meter {
+
+	fn draw(&mut self, gfx: &mut gfx::Gfx, ctx: &WidgetCtx) -> Result<f32> {
+
+		let mut y = 0.0;
+		let theme = ctx.theme();
+
+		let db = 20.0 * self.peak.max(0.000001).log10();
+
+		let label_shape = shapes::text(&format!("{}: {:.1}db", self.label, db.max(FLOOR)))
+			.size(theme.font_size)
+			.color(theme.title_color)
+			.align(gfx::Origin::TopLeft)
+			.format(gfx)
+			;
+
+		// draw label
+		gfx.draw(&label_shape)?;
+
+		y += label_shape.height() + theme.padding;
+
+		let height = theme.font_size;
+		let width = ctx.width();
+		let level_x = width * ratio(self.level);
+		let peak_x = width * ratio(self.peak);
+
+		// draw level
+		gfx.draw(
+			&shapes::rect(vec2!(0, -y), vec2!(level_x, -y - height))
+				.fill(theme.bar_color)
+		)?;
+
+		// draw box
+		gfx.draw(
+			&shapes::rect(vec2!(0, -y), vec2!(width, -y - height))
+				.no_fill()
+				.stroke(theme.border_color)
+				.line_join(shapes::LineJoin::Round)
+				.line_width(theme.line_width)
+		)?;
+
+		// draw peak
+		gfx.draw(
+			&shapes::line(vec2!(peak_x, -y), vec2!(peak_x, -y - height))
+				.color(theme.title_color)
+				.width(theme.line_width)
+		)?;
+
+		return Ok(y + height);
+
+	}
+
+}
@@ -12,4 +12,5 @@ export!(sep);
 export!(select);
 export!(tinput);
 export!(color);
+export!(meter);
 